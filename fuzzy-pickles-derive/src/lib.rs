@@ -59,24 +59,55 @@ fn impl_visit(ast: &syn::MacroInput) -> quote::Tokens {
     let method_name_base = camelcase_to_snake_case(&name.to_string());
     let method_name: quote::Ident = format!("visit{}", method_name_base).into();
     let exit_method_name: quote::Ident = format!("exit{}", method_name_base).into();
+    let method_name_mut: quote::Ident = format!("visit{}_mut", method_name_base).into();
+    let exit_method_name_mut: quote::Ident = format!("exit{}_mut", method_name_base).into();
 
-    let visit_fields = impl_visit_fields(ast);
+    let visit_fields = impl_visit_fields(ast, false);
+    let visit_fields_mut = impl_visit_fields(ast, true);
+    let child_nodes = impl_child_nodes(ast);
 
     quote! {
         impl Visit for #name {
-            fn visit<V>(&self, v: &mut V)
+            fn visit<V>(&self, v: &mut V) -> Control
                 where V: Visitor
             {
-                if Control::Continue == v.#method_name(self) {
-                    #visit_fields;
+                match v.#method_name(self) {
+                    Control::Continue => { #visit_fields }
+                    Control::Break => {}
+                    Control::Stop => return Control::Stop,
                 }
                 v.#exit_method_name(self);
+                Control::Continue
+            }
+        }
+
+        impl VisitMut for #name {
+            fn visit_mut<V>(&mut self, v: &mut V) -> Control
+                where V: VisitorMut
+            {
+                match v.#method_name_mut(self) {
+                    Control::Continue => { #visit_fields_mut }
+                    Control::Break => {}
+                    Control::Stop => return Control::Stop,
+                }
+                v.#exit_method_name_mut(self);
+                Control::Continue
+            }
+        }
+
+        impl ChildNodes for #name {
+            fn push_nodes<'a>(&'a self, nodes: &mut Vec<NodeRef<'a>>) {
+                nodes.push(NodeRef::#name(self));
+            }
+
+            fn push_child_nodes<'a>(&'a self, nodes: &mut Vec<NodeRef<'a>>) {
+                #child_nodes
             }
         }
     }
 }
 
-fn impl_visit_fields(ast: &syn::MacroInput) -> quote::Tokens {
+fn impl_child_nodes(ast: &syn::MacroInput) -> quote::Tokens {
     use syn::{Body, VariantData};
 
     match ast.body {
@@ -86,7 +117,49 @@ fn impl_visit_fields(ast: &syn::MacroInput) -> quote::Tokens {
 
             quote! {
                 match *self {
-                    #(#enum_name::#variant_names(ref x) => Visit::visit(x, v),)*
+                    #(#enum_name::#variant_names(ref x) => ChildNodes::push_nodes(x, nodes),)*
+                }
+            }
+        }
+        Body::Struct(VariantData::Struct(ref fields)) |
+        Body::Struct(VariantData::Tuple(ref fields)) => {
+            let field_names = fields
+                .iter()
+                .enumerate()
+                .filter(|&(_, ref f)| !is_ignore_field(f))
+                .map(|(i, f)| f.ident.clone().unwrap_or_else(|| i.into()));
+
+            quote! {
+                #(ChildNodes::push_nodes(&self.#field_names, nodes);)*
+            }
+        }
+        Body::Struct(VariantData::Unit) => quote! {},
+    }
+}
+
+fn impl_visit_fields(ast: &syn::MacroInput, is_mut: bool) -> quote::Tokens {
+    use syn::{Body, VariantData};
+
+    let (visit_trait, visit_method, borrow, binding) = if is_mut {
+        (quote! { VisitMut }, quote! { visit_mut }, quote! { &mut }, quote! { ref mut })
+    } else {
+        (quote! { Visit }, quote! { visit }, quote! { & }, quote! { ref })
+    };
+
+    match ast.body {
+        Body::Enum(ref e) => {
+            let enum_name = iter::repeat(&ast.ident);
+            let variant_names = e.iter().map(|variant| &variant.ident);
+            let visit_trait = iter::repeat(&visit_trait);
+            let visit_method = iter::repeat(&visit_method);
+            let binding = iter::repeat(&binding);
+
+            quote! {
+                let control = match *self {
+                    #(#enum_name::#variant_names(#binding x) => #visit_trait::#visit_method(x, v),)*
+                };
+                if control == Control::Stop {
+                    return Control::Stop;
                 }
             }
         }
@@ -97,15 +170,220 @@ fn impl_visit_fields(ast: &syn::MacroInput) -> quote::Tokens {
                 .enumerate()
                 .filter(|&(_, ref f)| !is_ignore_field(f))
                 .map(|(i, f)| f.ident.clone().unwrap_or_else(|| i.into()));
+            let visit_trait = iter::repeat(&visit_trait);
+            let visit_method = iter::repeat(&visit_method);
+            let borrow = iter::repeat(&borrow);
 
             quote! {
-                #(Visit::visit(&self.#field_names, v);)*
+                #(
+                    if #visit_trait::#visit_method(#borrow self.#field_names, v) == Control::Stop {
+                        return Control::Stop;
+                    }
+                )*
             }
         }
         Body::Struct(VariantData::Unit) => quote! {},
     }
 }
 
+#[proc_macro_derive(Fold, attributes(visit))]
+pub fn fold_derive(input: TokenStream) -> TokenStream {
+    // Construct a string representation of the type definition
+    let s = input.to_string();
+
+    // Parse the string representation
+    let ast = syn::parse_macro_input(&s).expect("Unable to parse input");
+
+    // Build the impl
+    let gen = impl_fold(&ast);
+
+    // Return the generated impl
+    gen.parse().expect("Unable to generate")
+}
+
+fn impl_fold(ast: &syn::MacroInput) -> quote::Tokens {
+    let name = &ast.ident;
+    let method_name_base = camelcase_to_snake_case(&name.to_string());
+    let method_name: quote::Ident = format!("fold{}", method_name_base).into();
+
+    let fold_fields = impl_fold_fields(ast);
+
+    quote! {
+        impl Foldable for #name {
+            fn fold_with<F>(self, f: &mut F) -> Self
+                where F: Fold
+            {
+                f.#method_name(self)
+            }
+
+            fn fold_children<F>(self, f: &mut F) -> Self
+                where F: Fold
+            {
+                #fold_fields
+            }
+        }
+    }
+}
+
+fn impl_fold_fields(ast: &syn::MacroInput) -> quote::Tokens {
+    use syn::{Body, VariantData};
+
+    let name = &ast.ident;
+
+    match ast.body {
+        Body::Enum(ref e) => {
+            let enum_name = iter::repeat(name);
+            let enum_name2 = iter::repeat(name);
+            let variant_names = e.iter().map(|variant| &variant.ident);
+            let variant_names2 = e.iter().map(|variant| &variant.ident);
+
+            quote! {
+                match self {
+                    #(#enum_name::#variant_names(x) => #enum_name2::#variant_names2(Foldable::fold_with(x, f)),)*
+                }
+            }
+        }
+        Body::Struct(VariantData::Struct(ref fields)) => {
+            let fields = fields.iter().map(|field| {
+                let field_name = &field.ident;
+                if is_ignore_field(field) {
+                    quote! { #field_name: self.#field_name }
+                } else {
+                    quote! { #field_name: Foldable::fold_with(self.#field_name, f) }
+                }
+            });
+
+            quote! {
+                #name { #(#fields,)* }
+            }
+        }
+        Body::Struct(VariantData::Tuple(ref fields)) => {
+            let fields = fields.iter().enumerate().map(|(i, field)| {
+                let i: syn::Ident = i.into();
+                if is_ignore_field(field) {
+                    quote! { self.#i }
+                } else {
+                    quote! { Foldable::fold_with(self.#i, f) }
+                }
+            });
+
+            quote! {
+                #name(#(#fields,)*)
+            }
+        }
+        Body::Struct(VariantData::Unit) => quote! { self },
+    }
+}
+
+#[proc_macro_derive(NodeRef)]
+pub fn node_ref_derive(input: TokenStream) -> TokenStream {
+    // Construct a string representation of the type definition
+    let s = input.to_string();
+
+    // Parse the string representation
+    let ast = syn::parse_macro_input(&s).expect("Unable to parse input");
+
+    // Build the impl
+    let gen = impl_node_ref(&ast);
+
+    // Return the generated impl
+    gen.parse().expect("Unable to generate")
+}
+
+// Each variant of the annotated enum wraps one node type; we generate
+// a parallel enum that borrows the node instead, as well as an enum
+// of just the variant names.
+fn impl_node_ref(ast: &syn::MacroInput) -> quote::Tokens {
+    use syn::{Body, VariantData};
+
+    let e = match ast.body {
+        Body::Enum(ref e) => e,
+        _ => panic!("Can only generate a NodeRef from an enum"),
+    };
+
+    struct Info<'a> {
+        name: &'a syn::Ident,
+        ty: &'a syn::Ty,
+    }
+
+    let variants: Vec<_> = e.iter().map(|variant| {
+        let ty = match variant.data {
+            VariantData::Tuple(ref fields) if fields.len() == 1 => &fields[0].ty,
+            _ => panic!("Each variant must have exactly one field"),
+        };
+
+        Info { name: &variant.ident, ty }
+    }).collect();
+
+    let kind_variants = variants.iter().map(|info| info.name);
+
+    let ref_variants = variants.iter().map(|&Info { name, ty, .. }| quote! { #name(&'a #ty) });
+
+    let kind_arms = variants.iter().map(|&Info { name, .. }| {
+        quote! { NodeRef::#name(_) => NodeKind::#name }
+    });
+
+    let extent_arms = variants.iter().map(|&Info { name, .. }| {
+        quote! { NodeRef::#name(n) => HasExtent::extent(n) }
+    });
+
+    let children_arms = variants.iter().map(|&Info { name, .. }| {
+        quote! { NodeRef::#name(n) => ChildNodes::push_child_nodes(n, &mut nodes) }
+    });
+
+    let from_impls = variants.iter().map(|&Info { name, ty, .. }| {
+        quote! {
+            impl<'a> From<&'a #ty> for NodeRef<'a> {
+                fn from(other: &'a #ty) -> Self {
+                    NodeRef::#name(other)
+                }
+            }
+        }
+    });
+
+    quote! {
+        /// The type of an AST node, without the node itself.
+        #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+        pub enum NodeKind {
+            #(#kind_variants,)*
+        }
+
+        /// A borrowed reference to any node of the AST.
+        #[derive(Debug, Copy, Clone)]
+        pub enum NodeRef<'a> {
+            #(#ref_variants,)*
+        }
+
+        impl<'a> NodeRef<'a> {
+            pub fn kind(&self) -> NodeKind {
+                match *self {
+                    #(#kind_arms,)*
+                }
+            }
+
+            pub fn extent(&self) -> Extent {
+                match *self {
+                    #(#extent_arms,)*
+                }
+            }
+
+            /// The direct children of this node, in source order. The
+            /// children of a node don't overlap, except for the name
+            /// and value of a shorthand struct literal field such as
+            /// `S { a }`, which are the same token.
+            pub fn children(&self) -> Vec<NodeRef<'a>> {
+                let mut nodes = Vec::new();
+                match *self {
+                    #(#children_arms,)*
+                }
+                nodes
+            }
+        }
+
+        #(#from_impls)*
+    }
+}
+
 fn is_ignore_field(field: &syn::Field) -> bool {
     use syn::MetaItem;
 
@@ -234,6 +512,102 @@ fn impl_decompose(ast: &syn::MacroInput) -> quote::Tokens {
     }
 }
 
+#[proc_macro_derive(Accessors)]
+pub fn accessors_derive(input: TokenStream) -> TokenStream {
+    // Construct a string representation of the type definition
+    let s = input.to_string();
+
+    // Parse the string representation
+    let ast = syn::parse_macro_input(&s).expect("Unable to parse input");
+
+    // Build the impl
+    let gen = impl_accessors(&ast);
+
+    // Return the generated impl
+    gen.parse().expect("Unable to generate")
+}
+
+fn impl_accessors(ast: &syn::MacroInput) -> quote::Tokens {
+    use syn::{Body, VariantData};
+
+    let name = &ast.ident;
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+
+    let fields = match ast.body {
+        Body::Struct(VariantData::Struct(ref fields)) => fields,
+        _ => panic!("Can only generate accessors for structs with named fields"),
+    };
+
+    // The extent is already available via `HasExtent`
+    let accessors = fields
+        .iter()
+        .filter_map(|f| f.ident.as_ref().map(|i| (i, &f.ty)))
+        .filter(|&(i, _)| i != "extent")
+        .map(|(field_name, ty)| {
+            let (return_ty, body) = accessor_parts(field_name, ty);
+
+            quote! {
+                pub fn #field_name(&self) -> #return_ty {
+                    #body
+                }
+            }
+        });
+
+    quote! {
+        impl #impl_generics #name #ty_generics #where_clause {
+            #(#accessors)*
+        }
+    }
+}
+
+fn accessor_parts(field_name: &syn::Ident, ty: &syn::Ty) -> (quote::Tokens, quote::Tokens) {
+    match single_type_parameter(ty) {
+        Some(("Option", inner)) => {
+            match single_type_parameter(inner) {
+                Some(("Box", inner)) => {
+                    (quote! { Option<&#inner> }, quote! { self.#field_name.as_ref().map(|x| &**x) })
+                }
+                _ if is_copy(inner) => (quote! { #ty }, quote! { self.#field_name }),
+                _ => (quote! { Option<&#inner> }, quote! { self.#field_name.as_ref() }),
+            }
+        }
+        Some(("Vec", inner)) => (quote! { &[#inner] }, quote! { &self.#field_name }),
+        Some(("Box", inner)) => (quote! { &#inner }, quote! { &self.#field_name }),
+        _ if is_copy(ty) => (quote! { #ty }, quote! { self.#field_name }),
+        _ => (quote! { &#ty }, quote! { &self.#field_name }),
+    }
+}
+
+// Splits `Foo<Bar>` into `("Foo", Bar)`
+fn single_type_parameter(ty: &syn::Ty) -> Option<(&str, &syn::Ty)> {
+    use syn::{Ty, PathParameters};
+
+    let segment = match *ty {
+        Ty::Path(None, ref path) => path.segments.last()?,
+        _ => return None,
+    };
+
+    match segment.parameters {
+        PathParameters::AngleBracketed(ref data) if data.types.len() == 1 => {
+            Some((segment.ident.as_ref(), &data.types[0]))
+        }
+        _ => None,
+    }
+}
+
+fn is_copy(ty: &syn::Ty) -> bool {
+    use syn::Ty;
+
+    const COPY_TYPES: &[&str] = &["Extent", "bool", "u8", "usize"];
+
+    match *ty {
+        Ty::Path(None, ref path) => {
+            path.segments.len() == 1 && COPY_TYPES.contains(&path.segments[0].ident.as_ref())
+        }
+        _ => false,
+    }
+}
+
 fn impl_has_extent(ast: &syn::MacroInput) -> quote::Tokens {
     let name = &ast.ident;
 
@@ -0,0 +1,347 @@
+extern crate fuzzy_pickles;
+#[cfg(feature = "json")]
+extern crate serde_json;
+
+use std::env;
+use std::fs::File;
+use std::io;
+use std::io::prelude::*;
+use std::process;
+
+#[cfg(feature = "json")]
+use fuzzy_pickles::File as RustFile;
+use fuzzy_pickles::{NodeRef, ParseOptions};
+use fuzzy_pickles::source::SourceMap;
+use fuzzy_pickles::tokenizer::{Token, Trivia};
+
+#[cfg(not(feature = "json"))]
+const USAGE: &str = "\
+Usage: ast-dump [OPTIONS] [FILE]
+
+Parses FILE (or standard input when FILE is `-` or missing) and
+prints the resulting AST.
+
+Options:
+    --format FORMAT    One of `tree` (default) or `sexp`
+    --max-depth N      Do not print nodes nested more than N levels
+                       deep. What was left out is marked with `...`
+    --hide-trivia      Do not print whitespace and comments
+    -h, --help         Print this message";
+
+#[cfg(feature = "json")]
+const USAGE: &str = "\
+Usage: ast-dump [OPTIONS] [FILE]
+
+Parses FILE (or standard input when FILE is `-` or missing) and
+prints the resulting AST.
+
+Options:
+    --format FORMAT    One of `tree` (default), `sexp` or `json`. JSON
+                       uses the serde layout of the AST
+    --max-depth N      Do not print nodes nested more than N levels
+                       deep; for JSON, objects and arrays. What was
+                       left out is marked with `...`, or with
+                       `{\"truncated\":true}` in JSON
+    --hide-trivia      Do not print whitespace and comments
+    -h, --help         Print this message";
+
+// Snippets longer than this are shortened in the tree output
+const MAX_SNIPPET_CHARS: usize = 40;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum Format {
+    Tree,
+    SExpression,
+    #[cfg(feature = "json")]
+    Json,
+}
+
+#[derive(Debug)]
+struct Options {
+    format: Format,
+    max_depth: Option<usize>,
+    hide_trivia: bool,
+    file: Option<String>,
+}
+
+impl Options {
+    fn from_args<I>(args: I) -> Result<Options, String>
+        where I: IntoIterator<Item = String>
+    {
+        let mut options = Options { format: Format::Tree, max_depth: None, hide_trivia: false, file: None };
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            match &*arg {
+                "--format" => {
+                    let format = args.next().ok_or("--format requires a value")?;
+                    options.format = match &*format {
+                        "tree" => Format::Tree,
+                        "sexp" => Format::SExpression,
+                        #[cfg(feature = "json")]
+                        "json" => Format::Json,
+                        _ => return Err(format!("Unknown format `{}`", format)),
+                    };
+                }
+                "--max-depth" => {
+                    let depth = args.next().ok_or("--max-depth requires a value")?;
+                    let depth = depth.parse().map_err(|_| format!("Invalid depth `{}`", depth))?;
+                    options.max_depth = Some(depth);
+                }
+                "--hide-trivia" => options.hide_trivia = true,
+                "-h" | "--help" => return Err(USAGE.to_string()),
+                "-" => options.file = None,
+                _ if arg.starts_with("--") => return Err(format!("Unknown option `{}`", arg)),
+                _ => options.file = Some(arg),
+            }
+        }
+
+        Ok(options)
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+enum Entry<'a> {
+    Node(NodeRef<'a>),
+    Trivia(Token),
+}
+
+struct Dumper<'a> {
+    source: &'a str,
+    options: &'a Options,
+    // Whitespace and comments, in order; empty when hidden
+    trivia: &'a [Token],
+    out: String,
+}
+
+impl<'a> Dumper<'a> {
+    /// The children of the node along with the trivia between them.
+    /// Trivia inside of a node without children is part of its text.
+    fn children(&self, node: NodeRef<'a>) -> Vec<Entry<'a>> {
+        let nodes = node.children();
+        if nodes.is_empty() {
+            return Vec::new();
+        }
+
+        let (start, end) = node.extent();
+        let first = match self.trivia.binary_search_by_key(&start, |t| t.extent().0) {
+            Ok(i) | Err(i) => i,
+        };
+        let mut trivia = self.trivia[first..].iter()
+            .take_while(|t| t.extent().1 <= end)
+            .peekable();
+
+        let mut entries = Vec::new();
+        for child in nodes {
+            let (child_start, child_end) = child.extent();
+            while let Some(&&t) = trivia.peek() {
+                if t.extent().0 >= child_start { break }
+                entries.push(Entry::Trivia(t));
+                trivia.next();
+            }
+            // Belongs to the child
+            while let Some(&&t) = trivia.peek() {
+                if t.extent().0 >= child_end { break }
+                trivia.next();
+            }
+            entries.push(Entry::Node(child));
+        }
+        entries.extend(trivia.map(|&t| Entry::Trivia(t)));
+        entries
+    }
+
+    fn is_too_deep(&self, depth: usize) -> bool {
+        self.options.max_depth.map_or(false, |max| depth > max)
+    }
+
+    fn snippet(&self, (start, end): (usize, usize)) -> &'a str {
+        &self.source[start..end]
+    }
+
+    fn tree(&mut self, entry: Entry<'a>, depth: usize) {
+        let indent = "  ".repeat(depth);
+        let node = match entry {
+            Entry::Node(node) => node,
+            Entry::Trivia(t) => {
+                let (start, end) = t.extent();
+                let line = format!("{}{} ({}, {}) {}\n", indent, trivia_kind(&t), start, end, shorten(self.snippet(t.extent())));
+                self.out.push_str(&line);
+                return;
+            }
+        };
+
+        let children = self.children(node);
+        let (start, end) = node.extent();
+
+        let line = format!("{}{:?} ({}, {}) {}\n", indent, node.kind(), start, end, shorten(self.snippet(node.extent())));
+        self.out.push_str(&line);
+
+        if children.is_empty() {
+            return;
+        }
+        if self.is_too_deep(depth + 1) {
+            self.out.push_str(&format!("{}  ...\n", indent));
+            return;
+        }
+        for child in children {
+            self.tree(child, depth + 1);
+        }
+    }
+
+    fn sexp(&mut self, entry: Entry<'a>, depth: usize) {
+        let node = match entry {
+            Entry::Node(node) => node,
+            Entry::Trivia(t) => {
+                self.out.push_str(&format!("({} {:?})", trivia_kind(&t), self.snippet(t.extent())));
+                return;
+            }
+        };
+
+        let children = self.children(node);
+
+        self.out.push_str(&format!("({:?}", node.kind()));
+        if children.is_empty() {
+            self.out.push_str(&format!(" {:?}", self.snippet(node.extent())));
+        } else if self.is_too_deep(depth + 1) {
+            self.out.push_str(" ...");
+        } else {
+            for child in children {
+                self.out.push(' ');
+                self.sexp(child, depth + 1);
+            }
+        }
+        self.out.push(')');
+    }
+}
+
+fn trivia_kind(t: &Token) -> &'static str {
+    match *t {
+        Token::Whitespace(..) => "Whitespace",
+        Token::Comment(..) => "Comment",
+        Token::CommentBlock(..) => "CommentBlock",
+        Token::DocComment(..) => "DocComment",
+        Token::DocCommentBlock(..) => "DocCommentBlock",
+        _ => "Token",
+    }
+}
+
+#[cfg(feature = "json")]
+fn json(file: &RustFile, trivia: &[Token], options: &Options) -> Result<String, String> {
+    use serde_json::{Map, Value};
+
+    let mut root = Map::new();
+    root.insert("file".to_string(), serde_json::to_value(file).map_err(|e| e.to_string())?);
+    if !options.hide_trivia {
+        root.insert("trivia".to_string(), serde_json::to_value(trivia).map_err(|e| e.to_string())?);
+    }
+
+    let mut root = Value::Object(root);
+    if let Some(max) = options.max_depth {
+        truncate_json(&mut root, 0, max);
+    }
+    serde_json::to_string(&root).map_err(|e| e.to_string())
+}
+
+// Replaces objects and arrays nested more than `max` levels deep with
+// a marker. Those that only contain numbers and strings, such as
+// extents, are kept as they don't hide anything.
+#[cfg(feature = "json")]
+fn truncate_json(value: &mut serde_json::Value, depth: usize, max: usize) {
+    use serde_json::Value;
+
+    fn is_container(v: &Value) -> bool {
+        match *v {
+            Value::Array(..) | Value::Object(..) => true,
+            _ => false,
+        }
+    }
+
+    let has_containers = match *value {
+        Value::Array(ref values) => values.iter().any(is_container),
+        Value::Object(ref map) => map.values().any(is_container),
+        _ => false,
+    };
+
+    if depth >= max && has_containers {
+        let mut marker = serde_json::Map::new();
+        marker.insert("truncated".to_string(), Value::Bool(true));
+        *value = Value::Object(marker);
+        return;
+    }
+
+    match *value {
+        Value::Array(ref mut values) => for v in values { truncate_json(v, depth + 1, max) },
+        Value::Object(ref mut map) => for v in map.values_mut() { truncate_json(v, depth + 1, max) },
+        _ => {}
+    }
+}
+
+fn shorten(s: &str) -> String {
+    if s.chars().count() <= MAX_SNIPPET_CHARS {
+        format!("{:?}", s)
+    } else {
+        let head: String = s.chars().take(MAX_SNIPPET_CHARS).collect();
+        format!("{:?}...", head)
+    }
+}
+
+fn read_input(file: &Option<String>) -> io::Result<(String, String)> {
+    let mut s = String::new();
+    match *file {
+        Some(ref fname) => {
+            File::open(fname)?.read_to_string(&mut s)?;
+            Ok((fname.clone(), s))
+        }
+        None => {
+            io::stdin().read_to_string(&mut s)?;
+            Ok(("<stdin>".to_string(), s))
+        }
+    }
+}
+
+fn main() {
+    let options = Options::from_args(env::args().skip(1)).unwrap_or_else(|msg| {
+        eprintln!("{}", msg);
+        process::exit(2);
+    });
+
+    let (name, text) = read_input(&options.file).unwrap_or_else(|e| {
+        eprintln!("Can't read {}: {}", options.file.as_ref().map_or("<stdin>", |f| f), e);
+        process::exit(2);
+    });
+
+    let mut sources = SourceMap::new();
+    let id = sources.add_file(name, text);
+    let source = sources.file(id);
+
+    let parse_options = ParseOptions::default().with_trivia(!options.hide_trivia);
+    let parsed = parse_options.parse(source.text()).unwrap_or_else(|detail| {
+        eprintln!("Unable to parse {}\n{}", source.name(), source.error_text(&detail));
+        process::exit(1);
+    });
+
+    let trivia = parsed.trivia.as_ref().map_or(&[][..], Trivia::tokens);
+
+    let mut dumper = Dumper { source: source.text(), options: &options, trivia, out: String::new() };
+    let root = Entry::Node(NodeRef::from(&parsed.file));
+    match options.format {
+        Format::Tree => dumper.tree(root, 0),
+        Format::SExpression => dumper.sexp(root, 0),
+        #[cfg(feature = "json")]
+        Format::Json => {
+            match json(&parsed.file, trivia, &options) {
+                Ok(json) => println!("{}", json),
+                Err(msg) => {
+                    eprintln!("{}", msg);
+                    process::exit(2);
+                }
+            }
+            return;
+        }
+    }
+    if options.format != Format::Tree {
+        dumper.out.push('\n');
+    }
+
+    print!("{}", dumper.out);
+}
@@ -0,0 +1,61 @@
+//! Helpers shared by the binaries. Each binary includes this module
+//! with `mod common;`.
+
+// Not every binary uses every helper
+#![allow(dead_code)]
+
+use std::any::Any;
+use std::fmt::Write;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Adds `path` to `files` if it is a file, otherwise every `.rs` file
+/// below it, sorted by path. Hidden and `target` directories are
+/// skipped.
+pub fn collect_files(path: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    if !path.is_dir() {
+        files.push(path.to_path_buf());
+        return Ok(());
+    }
+
+    let mut entries = fs::read_dir(path)?
+        .map(|e| e.map(|e| e.path()))
+        .collect::<Result<Vec<_>, _>>()?;
+    entries.sort();
+
+    for entry in entries {
+        let name = entry.file_name().map_or(String::new(), |n| n.to_string_lossy().into_owned());
+        if entry.is_dir() {
+            if !name.starts_with('.') && name != "target" {
+                collect_files(&entry, files)?;
+            }
+        } else if entry.extension().map_or(false, |e| e == "rs") {
+            files.push(entry);
+        }
+    }
+    Ok(())
+}
+
+/// Quotes the string as a JSON string literal.
+pub fn escape_json(s: &str) -> String {
+    let mut escaped = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            c if (c as u32) < 0x20 => { let _ = write!(escaped, "\\u{:04x}", c as u32); }
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+/// The message of a panic caught by `catch_unwind`.
+pub fn panic_message(payload: &(dyn Any + Send)) -> String {
+    payload.downcast_ref::<&str>().map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| String::from("<unknown panic>"))
+}
@@ -0,0 +1,221 @@
+extern crate fuzzy_pickles;
+
+mod common;
+
+use std::collections::BTreeMap;
+use std::env;
+use std::fs::File;
+use std::io::prelude::*;
+use std::panic;
+use std::path::PathBuf;
+use std::process;
+
+use fuzzy_pickles::{ErrorDetail, parse_rust_file};
+use fuzzy_pickles::source::{ColumnUnit, LineIndex};
+
+use common::{collect_files, panic_message};
+
+const USAGE: &str = "\
+Usage: conformance [OPTIONS] PATH...
+
+Parses every `.rs` file under the paths, such as a checkout of rustc's
+`tests/ui` or the cargo registry cache, and reports how many parsed,
+failed to tokenize, failed to parse or panicked. Failures are grouped
+by the set of errors that was expected, largest groups first.
+Hidden directories and `target` are skipped.
+
+Options:
+    --baseline FILE    Write the outcome for every file to FILE, one
+                       per line and sorted by path, so that runs can
+                       be compared with `diff`
+    --examples N       Files to list for each group (default 3)
+    -h, --help         Print this message";
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum Status {
+    Pass,
+    TokenizerError,
+    ParserError,
+    Panic,
+}
+
+impl Status {
+    fn name(&self) -> &'static str {
+        match *self {
+            Status::Pass => "pass",
+            Status::TokenizerError => "tokenizer-error",
+            Status::ParserError => "parser-error",
+            Status::Panic => "panic",
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Outcome {
+    path: String,
+    status: Status,
+    /// The errors that were expected, or the panic message
+    key: String,
+    /// Where the error occurred, as `line:column`
+    location: Option<String>,
+}
+
+fn join<I>(items: I) -> String
+    where I: IntoIterator,
+          I::Item: ::std::fmt::Debug,
+{
+    items.into_iter().map(|i| format!("{:?}", i)).collect::<Vec<_>>().join(", ")
+}
+
+fn check(path: String, text: &str) -> Outcome {
+    let detail = match panic::catch_unwind(|| parse_rust_file(text)) {
+        Ok(Ok(_)) => {
+            return Outcome { path, status: Status::Pass, key: String::new(), location: None };
+        }
+        Ok(Err(detail)) => detail,
+        Err(payload) => {
+            let msg = panic_message(&*payload);
+            return Outcome { path, status: Status::Panic, key: msg, location: None };
+        }
+    };
+
+    let (status, key) = match detail {
+        ErrorDetail::Tokenizer(ref d) => (Status::TokenizerError, join(d.errors())),
+        ErrorDetail::Parser(ref d) => (Status::ParserError, join(d.errors())),
+    };
+    let location = LineIndex::new(text).to_line_column(detail.location(), ColumnUnit::Char)
+        .map(|lc| format!("{}:{}", lc.line + 1, lc.column + 1));
+
+    Outcome { path, status, key, location }
+}
+
+fn percent(n: usize, total: usize) -> f64 {
+    if total == 0 { 0.0 } else { 100.0 * n as f64 / total as f64 }
+}
+
+fn report(outcomes: &[Outcome], unreadable: usize, examples: usize) {
+    let total = outcomes.len();
+    let count = |status| outcomes.iter().filter(|o| o.status == status).count();
+
+    println!("Checked {} files", total);
+    for &status in &[Status::Pass, Status::TokenizerError, Status::ParserError, Status::Panic] {
+        let n = count(status);
+        println!("  {:<16} {:>7} ({:.1}%)", status.name(), n, percent(n, total));
+    }
+    if unreadable != 0 {
+        println!("  {:<16} {:>7}", "unreadable", unreadable);
+    }
+
+    let mut groups: BTreeMap<(Status, &str), Vec<&Outcome>> = BTreeMap::new();
+    for o in outcomes.iter().filter(|o| o.status != Status::Pass) {
+        groups.entry((o.status, &o.key)).or_insert_with(Vec::new).push(o);
+    }
+
+    let mut groups: Vec<_> = groups.into_iter().collect();
+    groups.sort_by(|a, b| b.1.len().cmp(&a.1.len()).then(a.0.cmp(&b.0)));
+
+    for ((status, key), files) in groups {
+        println!();
+        println!("{:>7}  {}: {}", files.len(), status.name(), key);
+        for o in files.iter().take(examples) {
+            match o.location {
+                Some(ref location) => println!("         {}:{}", o.path, location),
+                None => println!("         {}", o.path),
+            }
+        }
+        if files.len() > examples {
+            println!("         ... and {} more", files.len() - examples);
+        }
+    }
+}
+
+fn write_baseline(fname: &str, outcomes: &[Outcome]) -> std::io::Result<()> {
+    let mut sorted: Vec<_> = outcomes.iter().collect();
+    sorted.sort_by(|a, b| a.path.cmp(&b.path));
+
+    let mut out = String::new();
+    for o in sorted {
+        out.push_str(&o.path);
+        out.push('\t');
+        out.push_str(o.status.name());
+        if !o.key.is_empty() {
+            out.push('\t');
+            out.push_str(&o.key.replace('\n', " "));
+        }
+        out.push('\n');
+    }
+
+    File::create(fname)?.write_all(out.as_bytes())
+}
+
+fn main() {
+    let mut paths = Vec::new();
+    let mut baseline = None;
+    let mut examples = 3;
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match &*arg {
+            "--baseline" => {
+                baseline = Some(args.next().unwrap_or_else(|| {
+                    eprintln!("--baseline requires a file name");
+                    process::exit(2);
+                }));
+            }
+            "--examples" => {
+                examples = args.next().and_then(|n| n.parse().ok()).unwrap_or_else(|| {
+                    eprintln!("--examples requires a number");
+                    process::exit(2);
+                });
+            }
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return;
+            }
+            _ if arg.starts_with("--") => {
+                eprintln!("Unknown option `{}`", arg);
+                process::exit(2);
+            }
+            _ => paths.push(PathBuf::from(arg)),
+        }
+    }
+    if paths.is_empty() {
+        eprintln!("{}", USAGE);
+        process::exit(2);
+    }
+
+    let mut fnames = Vec::new();
+    for path in &paths {
+        if let Err(e) = collect_files(path, &mut fnames) {
+            eprintln!("Can't read {}: {}", path.display(), e);
+            process::exit(2);
+        }
+    }
+
+    // Panics are reported, not printed as they happen
+    panic::set_hook(Box::new(|_| {}));
+
+    let mut outcomes = Vec::new();
+    let mut unreadable = 0;
+
+    for fname in fnames {
+        let name = fname.display().to_string();
+        let mut s = String::new();
+        if let Err(e) = File::open(&fname).and_then(|mut f| f.read_to_string(&mut s)) {
+            eprintln!("Can't read {}: {}", name, e);
+            unreadable += 1;
+            continue;
+        }
+
+        outcomes.push(check(name, &s));
+    }
+
+    report(&outcomes, unreadable, examples);
+
+    if let Some(fname) = baseline {
+        if let Err(e) = write_baseline(&fname, &outcomes) {
+            eprintln!("Can't write {}: {}", fname, e);
+            process::exit(2);
+        }
+    }
+}
@@ -0,0 +1,123 @@
+extern crate fuzzy_pickles;
+
+mod common;
+
+use std::env;
+use std::fs::File;
+use std::io::prelude::*;
+use std::path::PathBuf;
+use std::process;
+
+use fuzzy_pickles::lint::{Registry, Severity, TooManyStatements};
+use fuzzy_pickles::source::{ColumnUnit, SourceMap};
+
+use common::collect_files;
+
+const USAGE: &str = "\
+Usage: lint [OPTIONS] [PATH...]
+
+Checks each Rust file against the built-in lint rules. Directories
+are searched recursively for `.rs` files; hidden directories and
+`target` are skipped. Defaults to the current directory.
+
+Exits with 1 when any diagnostic is reported or a file cannot be
+parsed.
+
+Options:
+    --enable RULE          Enable a rule
+    --disable RULE         Disable a rule
+    --deny RULE            Report a rule as an error
+    --max-statements N     Statements allowed per function (default 50)
+    --list                 List the rules and exit
+    -h, --help             Print this message";
+
+fn fail(msg: &str) -> ! {
+    eprintln!("{}", msg);
+    process::exit(2);
+}
+
+fn main() {
+    let mut registry = Registry::with_builtin_rules();
+    let mut paths = Vec::new();
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match &*arg {
+            "--enable" | "--disable" | "--deny" => {
+                let rule = args.next().unwrap_or_else(|| fail(&format!("{} requires a rule", arg)));
+                let known = match &*arg {
+                    "--enable" => registry.enable(&rule),
+                    "--disable" => registry.disable(&rule),
+                    _ => registry.set_severity(&rule, Severity::Error),
+                };
+                if !known {
+                    fail(&format!("Unknown rule `{}`", rule));
+                }
+            }
+            "--max-statements" => {
+                let max = args.next().and_then(|n| n.parse().ok())
+                    .unwrap_or_else(|| fail("--max-statements requires a number"));
+                registry.configure(TooManyStatements { max });
+            }
+            "--list" => {
+                for rule in registry.rules() {
+                    println!("{:<22} {}", rule.name(), rule.description());
+                }
+                return;
+            }
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return;
+            }
+            _ if arg.starts_with("--") => fail(&format!("Unknown option `{}`", arg)),
+            _ => paths.push(PathBuf::from(arg)),
+        }
+    }
+    if paths.is_empty() {
+        paths.push(PathBuf::from("."));
+    }
+
+    let mut fnames = Vec::new();
+    for path in &paths {
+        if let Err(e) = collect_files(path, &mut fnames) {
+            fail(&format!("Can't read {}: {}", path.display(), e));
+        }
+    }
+
+    let mut sources = SourceMap::new();
+    let mut failed = false;
+
+    for fname in fnames {
+        let name = fname.display().to_string();
+        let mut s = String::new();
+        if let Err(e) = File::open(&fname).and_then(|mut f| f.read_to_string(&mut s)) {
+            eprintln!("Can't read {}: {}", name, e);
+            failed = true;
+            continue;
+        }
+
+        let id = sources.add_file(name, s);
+        let source = sources.file(id);
+
+        let file = match fuzzy_pickles::parse_rust_file(source.text()) {
+            Ok(file) => file,
+            Err(detail) => {
+                eprintln!("Unable to parse {}\n{}", source.name(), source.error_text(&detail));
+                failed = true;
+                continue;
+            }
+        };
+
+        for d in registry.check(&file, source.text()) {
+            failed = true;
+            match sources.resolve(source.span(d.extent), ColumnUnit::Char) {
+                Some(location) => println!("{}: {}[{}]: {}", location, d.severity, d.rule, d.message),
+                None => println!("{}: {}[{}]: {}", source.name(), d.severity, d.rule, d.message),
+            }
+        }
+    }
+
+    if failed {
+        process::exit(1);
+    }
+}
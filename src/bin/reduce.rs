@@ -0,0 +1,357 @@
+extern crate fuzzy_pickles;
+
+mod common;
+
+use std::cmp;
+use std::collections::BTreeSet;
+use std::env;
+use std::fs::File;
+use std::io::prelude::*;
+use std::panic;
+use std::process;
+
+use fuzzy_pickles::{ErrorDetail, parse_rust_file};
+use fuzzy_pickles::tokenizer::{self, Token, Tokens};
+
+use common::panic_message;
+
+const USAGE: &str = "\
+Usage: reduce [OPTIONS] FILE
+
+Shrinks a Rust file that fails to parse, or that makes the parser
+panic, into a smaller file that fails in the same way: with the same
+errors at the same token, or with the same panic message. Items,
+statements, bracketed groups and single tokens are deleted and
+whitespace is collapsed for as long as the failure is preserved.
+
+The reduced file is printed to standard output.
+
+Options:
+    --test        Print the reduced file inside of a `#[test]`
+    -h, --help    Print this message";
+
+#[derive(Debug, Clone, PartialEq)]
+enum Failure {
+    Tokenizer(usize, BTreeSet<tokenizer::Error>),
+    Parser(usize, BTreeSet<fuzzy_pickles::Error>),
+    Panic(String),
+}
+
+impl Failure {
+    fn from_detail(detail: &ErrorDetail) -> Failure {
+        match *detail {
+            ErrorDetail::Tokenizer(ref d) => Failure::Tokenizer(d.location(), d.errors().clone()),
+            ErrorDetail::Parser(ref d) => Failure::Parser(d.location(), d.errors().clone()),
+        }
+    }
+
+    /// The same failure once the edit has been applied, or `None` if
+    /// the edit would remove the text where the failure occurs.
+    fn after(&self, edit: &Edit) -> Option<Failure> {
+        let shift = |location: usize| {
+            if location < edit.start {
+                Some(location)
+            } else if location >= edit.end {
+                Some(location - (edit.end - edit.start) + edit.replacement.len())
+            } else {
+                None
+            }
+        };
+
+        match *self {
+            Failure::Tokenizer(l, ref e) => shift(l).map(|l| Failure::Tokenizer(l, e.clone())),
+            Failure::Parser(l, ref e) => shift(l).map(|l| Failure::Parser(l, e.clone())),
+            Failure::Panic(ref msg) => Some(Failure::Panic(msg.clone())),
+        }
+    }
+}
+
+// Parses the text, returning how it failed
+fn failure(text: &str) -> Option<Failure> {
+    match panic::catch_unwind(|| parse_rust_file(text)) {
+        Ok(Ok(_)) => None,
+        Ok(Err(detail)) => Some(Failure::from_detail(&detail)),
+        Err(payload) => {
+            let msg = panic_message(&*payload);
+            Some(Failure::Panic(msg))
+        }
+    }
+}
+
+/// Replaces a range of the text. Every edit makes the text shorter.
+#[derive(Debug, Copy, Clone)]
+struct Edit {
+    start: usize,
+    end: usize,
+    replacement: &'static str,
+}
+
+impl Edit {
+    fn delete(extent: (usize, usize)) -> Edit {
+        Edit { start: extent.0, end: extent.1, replacement: "" }
+    }
+
+    fn apply(&self, text: &str) -> String {
+        let mut s = String::with_capacity(text.len());
+        s.push_str(&text[..self.start]);
+        s.push_str(self.replacement);
+        s.push_str(&text[self.end..]);
+        s
+    }
+}
+
+// The tokens up to the first tokenizer error, if any
+fn tokens(text: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    for token in Tokens::new(text) {
+        match token {
+            Ok(token) => tokens.push(token),
+            Err(_) => break,
+        }
+    }
+    tokens
+}
+
+fn is_trivia(t: &Token) -> bool {
+    t.is_whitespace() || t.is_comment() || t.is_comment_block() ||
+        t.is_doc_comment() || t.is_doc_comment_block() || t.is_end_of_file()
+}
+
+fn is_open(t: &Token) -> bool {
+    t.is_left_curly() || t.is_left_paren() || t.is_left_square()
+}
+
+fn is_close(t: &Token) -> bool {
+    t.is_right_curly() || t.is_right_paren() || t.is_right_square()
+}
+
+/// Sequences of tokens ending in `;`, `,` or a closing `}` at the
+/// same level of nesting. These approximate items, statements,
+/// fields, arguments and match arms.
+fn chunks(tokens: &[Token]) -> Vec<Edit> {
+    let mut edits = Vec::new();
+    // The start of the current chunk at each level of nesting
+    let mut starts = vec![None];
+    let mut last_end = 0;
+
+    for t in tokens.iter().filter(|t| !is_trivia(t)) {
+        let (start, end) = t.extent();
+
+        if is_close(t) {
+            if let Some(Some(s)) = starts.pop() {
+                edits.push(Edit::delete((s, last_end)));
+            }
+            if starts.is_empty() {
+                starts.push(None);
+            }
+        } else if starts.last() == Some(&None) {
+            *starts.last_mut().expect("There is always a level") = Some(start);
+        }
+
+        if is_open(t) {
+            starts.push(None);
+        } else if t.is_semicolon() || t.is_comma() || t.is_right_curly() {
+            if let Some(Some(s)) = starts.last().cloned() {
+                edits.push(Edit::delete((s, end)));
+                *starts.last_mut().expect("There is always a level") = None;
+            }
+        }
+
+        last_end = end;
+    }
+
+    for s in starts.into_iter().filter_map(|s| s) {
+        edits.push(Edit::delete((s, last_end)));
+    }
+
+    edits
+}
+
+/// Bracketed groups, both with and without their brackets.
+fn groups(tokens: &[Token]) -> Vec<Edit> {
+    let mut edits = Vec::new();
+    let mut opens = Vec::new();
+
+    for t in tokens {
+        if is_open(t) {
+            opens.push(t.extent());
+        } else if is_close(t) {
+            if let Some(open) = opens.pop() {
+                let close = t.extent();
+                edits.push(Edit::delete((open.0, close.1)));
+                if open.1 < close.0 {
+                    edits.push(Edit::delete((open.1, close.0)));
+                }
+            }
+        }
+    }
+
+    edits
+}
+
+fn single_tokens(tokens: &[Token]) -> Vec<Edit> {
+    tokens.iter().filter(|t| !is_trivia(t)).map(|t| Edit::delete(t.extent())).collect()
+}
+
+/// Removes comments and shortens whitespace to a single character.
+/// Whitespace between tokens is kept so that they are not glued
+/// together into something less readable.
+fn trivia(text: &str, tokens: &[Token]) -> Vec<Edit> {
+    let mut edits = Vec::new();
+
+    for t in tokens {
+        let (start, end) = t.extent();
+        if t.is_whitespace() {
+            if start == 0 || end == text.len() {
+                edits.push(Edit::delete((start, end)));
+            }
+            let replacement = if text[start..end].contains('\n') { "\n" } else { " " };
+            if end - start > 1 {
+                edits.push(Edit { start, end, replacement });
+            }
+        } else if is_trivia(t) && !t.is_end_of_file() {
+            edits.push(Edit::delete((start, end)));
+        }
+    }
+
+    edits
+}
+
+struct Reducer {
+    text: String,
+    failure: Failure,
+    attempts: usize,
+}
+
+impl Reducer {
+    fn try_edit(&mut self, edit: &Edit) -> bool {
+        let expected = match self.failure.after(edit) {
+            Some(expected) => expected,
+            None => return false,
+        };
+
+        self.attempts += 1;
+        let candidate = edit.apply(&self.text);
+        if failure(&candidate).as_ref() == Some(&expected) {
+            self.text = candidate;
+            self.failure = expected;
+            true
+        } else {
+            false
+        }
+    }
+
+    // Tries each candidate edit in order of position, larger edits
+    // first. After a successful edit the candidates are recomputed
+    // and the search continues from the same index, which is roughly
+    // the same position in the text.
+    fn pass<F>(&mut self, candidates: F) -> bool
+        where F: Fn(&str) -> Vec<Edit>
+    {
+        let mut changed = false;
+        let mut i = 0;
+
+        loop {
+            let mut edits = candidates(&self.text);
+            edits.sort_by_key(|e| (e.start, cmp::Reverse(e.end)));
+            edits.dedup_by_key(|e| (e.start, e.end, e.replacement));
+
+            let edit = match edits.get(i) {
+                Some(edit) => *edit,
+                None => return changed,
+            };
+
+            if self.try_edit(&edit) {
+                changed = true;
+            } else {
+                i += 1;
+            }
+        }
+    }
+
+    fn reduce(&mut self) {
+        loop {
+            let mut changed = false;
+            changed |= self.pass(|text| chunks(&tokens(text)));
+            changed |= self.pass(|text| groups(&tokens(text)));
+            changed |= self.pass(|text| single_tokens(&tokens(text)));
+            changed |= self.pass(|text| trivia(text, &tokens(text)));
+            if !changed {
+                return;
+            }
+        }
+    }
+}
+
+fn as_test(text: &str) -> String {
+    let mut hashes = String::from("#");
+    while text.contains(&format!("\"{}", hashes)) {
+        hashes.push('#');
+    }
+
+    format!("#[test]\nfn reduced() {{\n    let p = parse_rust_file(r{h}\"{}\"{h});\n    assert!(p.is_ok());\n}}",
+            text, h = hashes)
+}
+
+fn main() {
+    let mut fname = None;
+    let mut test = false;
+
+    for arg in env::args().skip(1) {
+        match &*arg {
+            "--test" => test = true,
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return;
+            }
+            _ if arg.starts_with("--") => {
+                eprintln!("Unknown option `{}`", arg);
+                process::exit(2);
+            }
+            _ => fname = Some(arg),
+        }
+    }
+
+    let fname = fname.unwrap_or_else(|| {
+        eprintln!("{}", USAGE);
+        process::exit(2);
+    });
+
+    let mut text = String::new();
+    if let Err(e) = File::open(&fname).and_then(|mut f| f.read_to_string(&mut text)) {
+        eprintln!("Can't read {}: {}", fname, e);
+        process::exit(2);
+    }
+
+    // The parser is expected to panic while reducing
+    panic::set_hook(Box::new(|_| {}));
+
+    let original = match failure(&text) {
+        Some(failure) => failure,
+        None => {
+            eprintln!("{} parses successfully; there is nothing to reduce", fname);
+            process::exit(1);
+        }
+    };
+
+    let original_len = text.len();
+    let mut reducer = Reducer { text, failure: original, attempts: 0 };
+    reducer.reduce();
+
+    eprintln!("Reduced {} bytes to {} bytes in {} attempts", original_len, reducer.text.len(), reducer.attempts);
+    match reducer.failure {
+        Failure::Panic(ref msg) => eprintln!("The parser panics with: {}", msg),
+        _ => if let Err(detail) = parse_rust_file(&reducer.text) {
+            eprintln!("{}", detail.with_text(&reducer.text));
+        },
+    }
+
+    if test {
+        println!("{}", as_test(&reducer.text));
+    } else {
+        print!("{}", reducer.text);
+        if !reducer.text.ends_with('\n') {
+            println!();
+        }
+    }
+}
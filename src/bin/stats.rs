@@ -1,42 +1,541 @@
 extern crate fuzzy_pickles;
 
-use std::fs::File;
+mod common;
+
+use std::cmp;
 use std::env;
+use std::fmt::Write as FmtWrite;
+use std::fs::File;
 use std::io::prelude::*;
+use std::path::PathBuf;
+use std::process;
 
-use fuzzy_pickles::{Control, Visit, Visitor};
+use fuzzy_pickles::{Control, HasExtent, Visit, Visitor};
+use fuzzy_pickles::{Function, ImplFunction, TraitMemberFunction, FunctionQualifiers, Ident};
+use fuzzy_pickles::{Block, Item, Statement, UnsafeBlock, Impl, Trait};
+use fuzzy_pickles::source::{ColumnUnit, LineIndex, SourceMap};
+use fuzzy_pickles::tokenizer::Tokens;
 
-#[derive(Debug, Default)]
-struct Stats {
+use common::{collect_files, escape_json};
+
+const USAGE: &str = "\
+Usage: stats [OPTIONS] [PATH...]
+
+Reports code metrics for each Rust file, each function in those
+files, and the totals across all of them. Directories are searched
+recursively for `.rs` files; hidden directories and `target` are
+skipped. Defaults to the current directory.
+
+Options:
+    --format FORMAT    One of `table` (default), `csv` or `json`
+    -h, --help         Print this message";
+
+const ITEM_KINDS: &[&str] = &[
+    "attribute_containing", "const", "enum", "extern_block", "extern_crate", "function", "impl",
+    "macro_call", "module", "static", "struct", "trait", "type_alias", "union", "use",
+];
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum Format {
+    Table,
+    Csv,
+    Json,
+}
+
+#[derive(Debug, Copy, Clone, Default)]
+struct LineCounts {
+    total: usize,
+    code: usize,
+    comment: usize,
+    blank: usize,
+}
+
+impl LineCounts {
+    /// Classifies every line of the text. A line is code if any
+    /// non-comment token touches it, otherwise a comment line if a
+    /// comment touches it, otherwise blank.
+    fn new(text: &str, index: &LineIndex) -> LineCounts {
+        #[derive(Copy, Clone, PartialEq)]
+        enum Line { Blank, Comment, Code }
+
+        let mut lines = vec![Line::Blank; index.line_count()];
+
+        for token in Tokens::new(text) {
+            let token = match token {
+                Ok(token) => token,
+                Err(_) => break,
+            };
+            if token.is_whitespace() || token.is_end_of_file() {
+                continue;
+            }
+
+            let is_comment = token.is_comment() || token.is_comment_block() ||
+                token.is_doc_comment() || token.is_doc_comment_block();
+            let kind = if is_comment { Line::Comment } else { Line::Code };
+
+            let (start, end) = token.extent();
+            let first = line_of(index, start);
+            let last = line_of(index, cmp::max(start, end.saturating_sub(1)));
+            for line in &mut lines[first..last + 1] {
+                if *line != Line::Code {
+                    *line = kind;
+                }
+            }
+        }
+
+        // A trailing newline does not start another line
+        if text.ends_with('\n') {
+            lines.pop();
+        }
+
+        let count = |kind| lines.iter().filter(|&&l| l == kind).count();
+        LineCounts {
+            total: lines.len(),
+            code: count(Line::Code),
+            comment: count(Line::Comment),
+            blank: count(Line::Blank),
+        }
+    }
+
+    fn comment_ratio(&self) -> f64 {
+        let non_blank = self.code + self.comment;
+        if non_blank == 0 { 0.0 } else { self.comment as f64 / non_blank as f64 }
+    }
+
+    fn add(&mut self, other: &LineCounts) {
+        self.total += other.total;
+        self.code += other.code;
+        self.comment += other.comment;
+        self.blank += other.blank;
+    }
+}
+
+fn line_of(index: &LineIndex, offset: usize) -> usize {
+    index.to_line_column(offset, ColumnUnit::Byte).map_or(0, |lc| lc.line)
+}
+
+#[derive(Debug, Clone)]
+struct FunctionMetrics {
+    name: String,
+    line: usize,
+    lines: usize,
     statements: usize,
+    max_nesting: usize,
+    parameters: usize,
+    is_unsafe: bool,
+    unsafe_blocks: usize,
 }
 
-impl Visitor for Stats {
-    fn visit_statement(&mut self, _: &fuzzy_pickles::Statement) -> Control {
-        self.statements +=1;
+impl FunctionMetrics {
+    /// Being an `unsafe fn` counts once, as does each `unsafe` block.
+    fn unsafe_count(&self) -> usize {
+        self.unsafe_blocks + if self.is_unsafe { 1 } else { 0 }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct FileMetrics {
+    path: String,
+    items: Vec<usize>,
+    statements: usize,
+    lines: LineCounts,
+    max_nesting: usize,
+    unsafe_count: usize,
+    functions: Vec<FunctionMetrics>,
+}
+
+impl FileMetrics {
+    fn item_total(&self) -> usize {
+        self.items.iter().sum()
+    }
+}
+
+#[derive(Debug, Default)]
+struct Totals {
+    files: usize,
+    failures: usize,
+    metrics: FileMetrics,
+}
+
+impl Totals {
+    fn add(&mut self, file: &FileMetrics) {
+        let total = &mut self.metrics;
+
+        self.files += 1;
+        total.items.resize(ITEM_KINDS.len(), 0);
+        for (sum, count) in total.items.iter_mut().zip(&file.items) {
+            *sum += *count;
+        }
+        total.statements += file.statements;
+        total.lines.add(&file.lines);
+        total.max_nesting = cmp::max(total.max_nesting, file.max_nesting);
+        total.unsafe_count += file.unsafe_count;
+        total.functions.extend(file.functions.iter().cloned());
+    }
+}
+
+// The function currently being visited, along with how deeply nested
+// its blocks currently are.
+#[derive(Debug)]
+struct OpenFunction {
+    metrics: FunctionMetrics,
+    depth: usize,
+}
+
+struct Metrics<'a> {
+    text: &'a str,
+    index: &'a LineIndex,
+    file: FileMetrics,
+    open: Vec<OpenFunction>,
+}
+
+impl<'a> Metrics<'a> {
+    fn new(path: String, text: &'a str, index: &'a LineIndex) -> Self {
+        let file = FileMetrics {
+            path,
+            items: vec![0; ITEM_KINDS.len()],
+            lines: LineCounts::new(text, index),
+            ..FileMetrics::default()
+        };
+        Metrics { text, index, file, open: Vec::new() }
+    }
+
+    fn enter_function<E>(&mut self, node: &E, name: &Ident, qualifiers: &FunctionQualifiers, parameters: usize)
+        where E: HasExtent
+    {
+        let (start, end) = node.extent();
+        let (name_start, name_end) = name.extent();
+        let line = line_of(self.index, start);
+        let is_unsafe = qualifiers.is_unsafe().is_some();
+
+        if is_unsafe {
+            self.file.unsafe_count += 1;
+        }
+
+        let metrics = FunctionMetrics {
+            name: self.text[name_start..name_end].to_string(),
+            line: line + 1,
+            lines: line_of(self.index, end.saturating_sub(1)) - line + 1,
+            statements: 0,
+            max_nesting: 0,
+            parameters,
+            is_unsafe,
+            unsafe_blocks: 0,
+        };
+        self.open.push(OpenFunction { metrics, depth: 0 });
+    }
+
+    fn exit_function(&mut self) {
+        if let Some(f) = self.open.pop() {
+            self.file.max_nesting = cmp::max(self.file.max_nesting, f.metrics.max_nesting);
+            self.file.functions.push(f.metrics);
+        }
+    }
+}
+
+impl<'a> Visitor for Metrics<'a> {
+    fn visit_item(&mut self, item: &Item) -> Control {
+        let kind = match *item {
+            Item::AttributeContaining(..) => "attribute_containing",
+            Item::Const(..) => "const",
+            Item::Enum(..) => "enum",
+            Item::ExternBlock(..) => "extern_block",
+            Item::ExternCrate(..) => "extern_crate",
+            Item::Function(..) => "function",
+            Item::Impl(..) => "impl",
+            Item::MacroCall(..) => "macro_call",
+            Item::Module(..) => "module",
+            Item::Static(..) => "static",
+            Item::Struct(..) => "struct",
+            Item::Trait(..) => "trait",
+            Item::TypeAlias(..) => "type_alias",
+            Item::Union(..) => "union",
+            Item::Use(..) => "use",
+        };
+        if let Some(i) = ITEM_KINDS.iter().position(|&k| k == kind) {
+            self.file.items[i] += 1;
+        }
+        Control::Continue
+    }
+
+    fn visit_function(&mut self, f: &Function) -> Control {
+        let header = f.header();
+        self.enter_function(f, header.name(), header.qualifiers(), header.arguments().len());
         Control::Continue
     }
+
+    fn exit_function(&mut self, _: &Function) {
+        Metrics::exit_function(self);
+    }
+
+    fn visit_impl_function(&mut self, f: &ImplFunction) -> Control {
+        let header = f.header();
+        self.enter_function(f, header.name(), header.qualifiers(), header.arguments().len());
+        Control::Continue
+    }
+
+    fn exit_impl_function(&mut self, _: &ImplFunction) {
+        Metrics::exit_function(self);
+    }
+
+    fn visit_trait_member_function(&mut self, f: &TraitMemberFunction) -> Control {
+        // Only provided methods have a body worth measuring
+        if f.body().is_none() { return Control::Break }
+
+        let header = f.header();
+        self.enter_function(f, header.name(), header.qualifiers(), header.arguments().len());
+        Control::Continue
+    }
+
+    fn exit_trait_member_function(&mut self, f: &TraitMemberFunction) {
+        if f.body().is_some() {
+            Metrics::exit_function(self);
+        }
+    }
+
+    fn visit_block(&mut self, _: &Block) -> Control {
+        if let Some(f) = self.open.last_mut() {
+            f.depth += 1;
+            f.metrics.max_nesting = cmp::max(f.metrics.max_nesting, f.depth);
+        }
+        Control::Continue
+    }
+
+    fn exit_block(&mut self, _: &Block) {
+        if let Some(f) = self.open.last_mut() {
+            f.depth -= 1;
+        }
+    }
+
+    fn visit_statement(&mut self, _: &Statement) -> Control {
+        self.file.statements += 1;
+        if let Some(f) = self.open.last_mut() {
+            f.metrics.statements += 1;
+        }
+        Control::Continue
+    }
+
+    fn visit_unsafe_block(&mut self, _: &UnsafeBlock) -> Control {
+        self.file.unsafe_count += 1;
+        if let Some(f) = self.open.last_mut() {
+            f.metrics.unsafe_blocks += 1;
+        }
+        Control::Continue
+    }
+
+    fn visit_impl(&mut self, i: &Impl) -> Control {
+        if i.is_unsafe().is_some() {
+            self.file.unsafe_count += 1;
+        }
+        Control::Continue
+    }
+
+    fn visit_trait(&mut self, t: &Trait) -> Control {
+        if t.is_unsafe().is_some() {
+            self.file.unsafe_count += 1;
+        }
+        Control::Continue
+    }
+}
+
+fn escape_csv(s: &str) -> String {
+    if s.contains(',') || s.contains('"') || s.contains('\n') {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+fn print_table(files: &[FileMetrics], totals: &Totals) {
+    let width = files.iter().map(|f| f.path.len()).chain(Some(5)).max().unwrap_or(5);
+
+    println!("{:<w$} {:>6} {:>6} {:>6} {:>7} {:>7} {:>7} {:>7} {:>9} {:>8} {:>7}",
+             "File", "Items", "Fns", "Stmts", "Lines", "Code", "Comment", "Blank", "Comment%", "Nesting", "Unsafe",
+             w = width);
+    let row = |name: &str, f: &FileMetrics| {
+        println!("{:<w$} {:>6} {:>6} {:>6} {:>7} {:>7} {:>7} {:>7} {:>8.1}% {:>8} {:>7}",
+                 name, f.item_total(), f.functions.len(), f.statements,
+                 f.lines.total, f.lines.code, f.lines.comment, f.lines.blank,
+                 f.lines.comment_ratio() * 100.0, f.max_nesting, f.unsafe_count,
+                 w = width);
+    };
+    for f in files {
+        row(&f.path, f);
+    }
+    row("TOTAL", &totals.metrics);
+
+    let functions: Vec<_> = files.iter()
+        .flat_map(|f| f.functions.iter().map(move |func| (format!("{}:{}", f.path, func.line), func)))
+        .collect();
+    if !functions.is_empty() {
+        let width = functions.iter().map(|&(ref l, f)| l.len() + f.name.len() + 1).max().unwrap_or(0);
+        println!();
+        println!("{:<w$} {:>6} {:>7} {:>8} {:>7} {:>7}", "Function", "Stmts", "Lines", "Nesting", "Params", "Unsafe", w = width);
+        for (location, f) in functions {
+            let name = format!("{} {}", location, f.name);
+            println!("{:<w$} {:>6} {:>7} {:>8} {:>7} {:>7}",
+                     name, f.statements, f.lines, f.max_nesting, f.parameters, f.unsafe_count(), w = width);
+        }
+    }
+
+    println!();
+    println!("{} files, {} functions, {} failed to parse", totals.files, totals.metrics.functions.len(), totals.failures);
+    for (kind, count) in ITEM_KINDS.iter().zip(&totals.metrics.items) {
+        if *count > 0 {
+            println!("  {:<22} {:>6}", kind, count);
+        }
+    }
+}
+
+fn print_csv(files: &[FileMetrics], totals: &Totals) {
+    let mut header = String::from("scope,path,function,line,items,functions,statements,lines,code_lines,\
+                                   comment_lines,blank_lines,comment_ratio,max_nesting,parameters,unsafe");
+    for kind in ITEM_KINDS {
+        let _ = write!(header, ",items_{}", kind);
+    }
+    println!("{}", header);
+
+    let file_row = |scope: &str, f: &FileMetrics| {
+        let mut row = format!("{},{},,,{},{},{},{},{},{},{},{:.4},{},,{}",
+                              scope, escape_csv(&f.path), f.item_total(), f.functions.len(), f.statements,
+                              f.lines.total, f.lines.code, f.lines.comment, f.lines.blank,
+                              f.lines.comment_ratio(), f.max_nesting, f.unsafe_count);
+        for count in &f.items {
+            let _ = write!(row, ",{}", count);
+        }
+        println!("{}", row);
+    };
+
+    for f in files {
+        file_row("file", f);
+        for func in &f.functions {
+            let mut row = format!("function,{},{},{},,,{},{},,,,,{},{},{}",
+                                  escape_csv(&f.path), escape_csv(&func.name), func.line,
+                                  func.statements, func.lines, func.max_nesting, func.parameters,
+                                  func.unsafe_count());
+            for _ in ITEM_KINDS {
+                row.push(',');
+            }
+            println!("{}", row);
+        }
+    }
+    file_row("total", &totals.metrics);
+}
+
+fn json_file(out: &mut String, f: &FileMetrics, include_functions: bool) {
+    let _ = write!(out, "{{\"path\":{},\"items\":{{", escape_json(&f.path));
+    for (i, (kind, count)) in ITEM_KINDS.iter().zip(&f.items).enumerate() {
+        if i != 0 { out.push(',') }
+        let _ = write!(out, "\"{}\":{}", kind, count);
+    }
+    let _ = write!(out, "}},\"statements\":{},\"lines\":{{\"total\":{},\"code\":{},\"comment\":{},\"blank\":{}}},\
+                        \"comment_ratio\":{:.4},\"max_nesting\":{},\"unsafe\":{}",
+                   f.statements, f.lines.total, f.lines.code, f.lines.comment, f.lines.blank,
+                   f.lines.comment_ratio(), f.max_nesting, f.unsafe_count);
+    if include_functions {
+        out.push_str(",\"functions\":[");
+        for (i, func) in f.functions.iter().enumerate() {
+            if i != 0 { out.push(',') }
+            let _ = write!(out, "{{\"name\":{},\"line\":{},\"lines\":{},\"statements\":{},\"max_nesting\":{},\
+                                 \"parameters\":{},\"is_unsafe\":{},\"unsafe_blocks\":{}}}",
+                           escape_json(&func.name), func.line, func.lines, func.statements, func.max_nesting,
+                           func.parameters, func.is_unsafe, func.unsafe_blocks);
+        }
+        out.push(']');
+    } else {
+        let _ = write!(out, ",\"functions\":{}", f.functions.len());
+    }
+    out.push('}');
+}
+
+fn print_json(files: &[FileMetrics], totals: &Totals) {
+    let mut out = String::from("{\"files\":[");
+    for (i, f) in files.iter().enumerate() {
+        if i != 0 { out.push(',') }
+        json_file(&mut out, f, true);
+    }
+    let _ = write!(out, "],\"failures\":{},\"total_files\":{},\"totals\":", totals.failures, totals.files);
+    json_file(&mut out, &totals.metrics, false);
+    out.push('}');
+    println!("{}", out);
 }
 
 fn main() {
-    for fname in env::args().skip(1) {
-        let mut f = File::open(&fname)
-            .unwrap_or_else(|e| panic!("Can't open {}: {}", fname, e));
+    let mut format = Format::Table;
+    let mut paths = Vec::new();
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match &*arg {
+            "--format" => {
+                format = match args.next().as_ref().map(|s| &**s) {
+                    Some("table") => Format::Table,
+                    Some("csv") => Format::Csv,
+                    Some("json") => Format::Json,
+                    _ => {
+                        eprintln!("--format must be one of `table`, `csv` or `json`");
+                        process::exit(2);
+                    }
+                };
+            }
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return;
+            }
+            _ => paths.push(PathBuf::from(arg)),
+        }
+    }
+    if paths.is_empty() {
+        paths.push(PathBuf::from("."));
+    }
+
+    let mut fnames = Vec::new();
+    for path in &paths {
+        if let Err(e) = collect_files(path, &mut fnames) {
+            eprintln!("Can't read {}: {}", path.display(), e);
+            process::exit(2);
+        }
+    }
+
+    let mut sources = SourceMap::new();
+    let mut files = Vec::new();
+    let mut totals = Totals::default();
+
+    for fname in fnames {
+        let name = fname.display().to_string();
         let mut s = String::new();
-        f.read_to_string(&mut s)
-            .unwrap_or_else(|e| panic!("Can't read {}: {}", fname, e));
+        if let Err(e) = File::open(&fname).and_then(|mut f| f.read_to_string(&mut s)) {
+            eprintln!("Can't read {}: {}", name, e);
+            totals.failures += 1;
+            continue;
+        }
 
-        let file = match fuzzy_pickles::parse_rust_file(&s) {
+        let id = sources.add_file(name, s);
+        let source = sources.file(id);
+
+        let file = match fuzzy_pickles::parse_rust_file(source.text()) {
             Ok(file) => file,
             Err(detail) => {
-                panic!("Unable to parse {}\n{}", fname, detail.with_text(&s));
+                eprintln!("Unable to parse {}\n{}", source.name(), source.error_text(&detail));
+                totals.failures += 1;
+                continue;
             }
         };
 
-        let mut stats = Stats::default();
+        let mut metrics = Metrics::new(source.name().to_string(), source.text(), source.line_index());
+        file.visit(&mut metrics);
+
+        totals.add(&metrics.file);
+        files.push(metrics.file);
+    }
 
-        file.visit(&mut stats);
+    match format {
+        Format::Table => print_table(&files, &totals),
+        Format::Csv => print_csv(&files, &totals),
+        Format::Json => print_json(&files, &totals),
+    }
 
-//        println!("{}: {} statements", fname, stats.statements)
+    if totals.failures > 0 {
+        process::exit(1);
     }
 }
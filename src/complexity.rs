@@ -0,0 +1,343 @@
+//! Complexity measurements for functions and closures.
+//!
+//! Each `Function`, `ImplFunction`, `TraitMemberFunction` with a body
+//! and `Closure` is measured on its own; the code inside a nested
+//! function or closure only counts towards that nested one.
+
+use std::collections::BTreeSet;
+
+use super::*;
+
+/// What kind of code a `FunctionComplexity` measures.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum FunctionKind {
+    Function,
+    ImplFunction,
+    TraitMemberFunction,
+    Closure,
+}
+
+/// The complexity of a single function or closure.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct FunctionComplexity {
+    pub kind: FunctionKind,
+    /// The extent of the function's name; closures have none.
+    pub name: Option<Extent>,
+    pub extent: Extent,
+    /// McCabe's cyclomatic complexity: one more than the number of
+    /// decision points.
+    pub cyclomatic: usize,
+    /// Cognitive complexity, which also penalizes nesting.
+    pub cognitive: usize,
+}
+
+/// Measure every function and closure in the node, in the order that
+/// they start.
+///
+/// Cyclomatic complexity counts one for each `if`, `if let`, loop,
+/// match arm guard, `&&`, `||` and `?`, as well as one for each match
+/// arm after the first.
+///
+/// Cognitive complexity counts one for each `if`, `if let`, `match`
+/// and loop, plus one more for each level that they are nested
+/// within one another. An `else if` or `else` counts one regardless
+/// of nesting, as do `?` and each run of the same boolean operator.
+pub fn analyze<T>(node: &T) -> Vec<FunctionComplexity>
+    where T: Visit
+{
+    let mut analyzer = Analyzer::default();
+    node.visit(&mut analyzer);
+    analyzer.results
+}
+
+#[derive(Debug)]
+struct Open {
+    result: usize,
+    nesting: usize,
+}
+
+#[derive(Debug, Default)]
+struct Analyzer {
+    results: Vec<FunctionComplexity>,
+    open: Vec<Open>,
+    // `If`s that are part of an `else if` chain
+    else_ifs: BTreeSet<Extent>,
+    // Boolean operators that continue a run of the same operator
+    continuations: BTreeSet<Extent>,
+}
+
+impl Analyzer {
+    fn enter(&mut self, kind: FunctionKind, name: Option<Extent>, extent: Extent) {
+        self.open.push(Open { result: self.results.len(), nesting: 0 });
+        self.results.push(FunctionComplexity { kind, name, extent, cyclomatic: 1, cognitive: 0 });
+    }
+
+    fn exit(&mut self) {
+        self.open.pop();
+    }
+
+    fn current(&mut self) -> Option<(&mut FunctionComplexity, &mut usize)> {
+        let results = &mut self.results;
+        self.open.last_mut().map(move |open| (&mut results[open.result], &mut open.nesting))
+    }
+
+    fn decision(&mut self) {
+        if let Some((c, _)) = self.current() {
+            c.cyclomatic += 1;
+        }
+    }
+
+    fn flat(&mut self) {
+        if let Some((c, _)) = self.current() {
+            c.cognitive += 1;
+        }
+    }
+
+    fn enter_nested(&mut self) {
+        if let Some((c, nesting)) = self.current() {
+            c.cognitive += 1 + *nesting;
+            *nesting += 1;
+        }
+    }
+
+    fn exit_nested(&mut self) {
+        if let Some((_, nesting)) = self.current() {
+            *nesting -= 1;
+        }
+    }
+}
+
+fn boolean_operator(expr: &Expression) -> Option<(Extent, bool)> {
+    match *expr {
+        Expression::Binary(ref b) => match b.op {
+            BinaryOp::BooleanAnd(_) => Some((b.extent, true)),
+            BinaryOp::BooleanOr(_) => Some((b.extent, false)),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+impl Visitor for Analyzer {
+    fn visit_function(&mut self, f: &Function) -> Control {
+        self.enter(FunctionKind::Function, Some(f.header.name.extent), f.extent);
+        Control::Continue
+    }
+
+    fn exit_function(&mut self, _: &Function) {
+        self.exit();
+    }
+
+    fn visit_impl_function(&mut self, f: &ImplFunction) -> Control {
+        self.enter(FunctionKind::ImplFunction, Some(f.header.name.extent), f.extent);
+        Control::Continue
+    }
+
+    fn exit_impl_function(&mut self, _: &ImplFunction) {
+        self.exit();
+    }
+
+    fn visit_trait_member_function(&mut self, f: &TraitMemberFunction) -> Control {
+        if f.body.is_none() { return Control::Break }
+        self.enter(FunctionKind::TraitMemberFunction, Some(f.header.name.extent), f.extent);
+        Control::Continue
+    }
+
+    fn exit_trait_member_function(&mut self, f: &TraitMemberFunction) {
+        if f.body.is_some() {
+            self.exit();
+        }
+    }
+
+    fn visit_closure(&mut self, c: &Closure) -> Control {
+        self.enter(FunctionKind::Closure, None, c.extent);
+        Control::Continue
+    }
+
+    fn exit_closure(&mut self, _: &Closure) {
+        self.exit();
+    }
+
+    fn visit_if(&mut self, i: &If) -> Control {
+        self.decision();
+        if self.else_ifs.contains(&i.extent) {
+            self.flat();
+        } else {
+            self.enter_nested();
+        }
+        if i.else_body.is_some() {
+            self.flat();
+        }
+        self.else_ifs.extend(i.more.iter().map(|i| i.extent));
+        Control::Continue
+    }
+
+    fn exit_if(&mut self, i: &If) {
+        if !self.else_ifs.remove(&i.extent) {
+            self.exit_nested();
+        }
+    }
+
+    fn visit_if_let(&mut self, _: &IfLet) -> Control {
+        self.decision();
+        self.enter_nested();
+        Control::Continue
+    }
+
+    fn exit_if_let(&mut self, _: &IfLet) {
+        self.exit_nested();
+    }
+
+    fn visit_match(&mut self, m: &Match) -> Control {
+        if let Some((c, _)) = self.current() {
+            c.cyclomatic += m.arms.len().saturating_sub(1);
+        }
+        self.enter_nested();
+        Control::Continue
+    }
+
+    fn exit_match(&mut self, _: &Match) {
+        self.exit_nested();
+    }
+
+    fn visit_match_arm(&mut self, arm: &MatchArm) -> Control {
+        if arm.guard.is_some() {
+            self.decision();
+        }
+        Control::Continue
+    }
+
+    fn visit_for_loop(&mut self, _: &ForLoop) -> Control {
+        self.decision();
+        self.enter_nested();
+        Control::Continue
+    }
+
+    fn exit_for_loop(&mut self, _: &ForLoop) {
+        self.exit_nested();
+    }
+
+    fn visit_loop(&mut self, _: &Loop) -> Control {
+        self.decision();
+        self.enter_nested();
+        Control::Continue
+    }
+
+    fn exit_loop(&mut self, _: &Loop) {
+        self.exit_nested();
+    }
+
+    fn visit_while(&mut self, _: &While) -> Control {
+        self.decision();
+        self.enter_nested();
+        Control::Continue
+    }
+
+    fn exit_while(&mut self, _: &While) {
+        self.exit_nested();
+    }
+
+    fn visit_while_let(&mut self, _: &WhileLet) -> Control {
+        self.decision();
+        self.enter_nested();
+        Control::Continue
+    }
+
+    fn exit_while_let(&mut self, _: &WhileLet) {
+        self.exit_nested();
+    }
+
+    fn visit_binary(&mut self, b: &Binary) -> Control {
+        let is_and = match b.op {
+            BinaryOp::BooleanAnd(_) => true,
+            BinaryOp::BooleanOr(_) => false,
+            _ => return Control::Continue,
+        };
+
+        self.decision();
+        if !self.continuations.remove(&b.extent) {
+            self.flat();
+        }
+
+        for side in &[&b.lhs, &b.rhs] {
+            if let Some((extent, side_is_and)) = boolean_operator(&side.value) {
+                if side_is_and == is_and {
+                    self.continuations.insert(extent);
+                }
+            }
+        }
+        Control::Continue
+    }
+
+    fn visit_try_operator(&mut self, _: &TryOperator) -> Control {
+        self.decision();
+        self.flat();
+        Control::Continue
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn complexities(s: &str) -> Vec<(usize, usize)> {
+        let file = parse_rust_file(s).expect("Unable to parse");
+        analyze(&file).into_iter().map(|c| (c.cyclomatic, c.cognitive)).collect()
+    }
+
+    #[test]
+    fn straight_line_code_is_simple() {
+        assert_eq!(complexities("fn a() { b(); c }"), vec![(1, 0)]);
+    }
+
+    #[test]
+    fn if_else_chains() {
+        let c = complexities("fn a() { if b { } else if c { } else if d { } else { } }");
+        assert_eq!(c, vec![(4, 4)]);
+    }
+
+    #[test]
+    fn nesting_increases_cognitive_complexity() {
+        let c = complexities("fn a() { for b in c { while d { if e { } } } }");
+        assert_eq!(c, vec![(4, 6)]);
+    }
+
+    #[test]
+    fn match_arms_and_guards() {
+        let c = complexities("fn a() { match b { C => 1, D if e => 2, _ => 3 } }");
+        assert_eq!(c, vec![(4, 1)]);
+    }
+
+    #[test]
+    fn runs_of_boolean_operators() {
+        let c = complexities("fn a() { b && c && d || e }");
+        assert_eq!(c, vec![(4, 2)]);
+    }
+
+    #[test]
+    fn try_operators() {
+        let c = complexities("fn a() { b()?.c()?; }");
+        assert_eq!(c, vec![(3, 2)]);
+    }
+
+    #[test]
+    fn closures_and_methods_are_measured_separately() {
+        let file = parse_rust_file("impl A { fn b() { c(|d| if d { e }) } }").unwrap();
+        let c = analyze(&file);
+
+        assert_eq!(c.len(), 2);
+        assert_eq!(c[0].kind, FunctionKind::ImplFunction);
+        assert_eq!(c[0].name, Some((12, 13)));
+        assert_eq!((c[0].cyclomatic, c[0].cognitive), (1, 0));
+        assert_eq!(c[1].kind, FunctionKind::Closure);
+        assert_eq!(c[1].name, None);
+        assert_eq!(c[1].extent, (20, 34));
+        assert_eq!((c[1].cyclomatic, c[1].cognitive), (2, 1));
+    }
+
+    #[test]
+    fn trait_methods_without_bodies_are_skipped() {
+        let c = complexities("trait A { fn b(); fn c() { if d { } } }");
+        assert_eq!(c, vec![(2, 1)]);
+    }
+}
@@ -11,6 +11,12 @@ use super::*;
 
 pub fn expression<'s>(pm: &mut Master<'s>, pt: Point<'s>) ->
     Progress<'s, Attributed<Expression>>
+{
+    nested(expression_unlimited)(pm, pt)
+}
+
+fn expression_unlimited<'s>(pm: &mut Master<'s>, pt: Point<'s>) ->
+    Progress<'s, Attributed<Expression>>
 {
     match expression_shunting_yard(pm, pt, |_, state| state) {
         Ok(ShuntCar { value: expr, ept, .. }) => Progress::success(ept, expr),
@@ -22,6 +28,12 @@ pub fn expression<'s>(pm: &mut Master<'s>, pt: Point<'s>) ->
 // restrictions on what is allowed to follow them
 pub fn statement_expression<'s>(pm: &mut Master<'s>, pt: Point<'s>) ->
     Progress<'s, Attributed<Expression>>
+{
+    nested(statement_expression_unlimited)(pm, pt)
+}
+
+fn statement_expression_unlimited<'s>(pm: &mut Master<'s>, pt: Point<'s>) ->
+    Progress<'s, Attributed<Expression>>
 {
     let r = expression_shunting_yard(pm, pt, |shunting_yard, state| {
         match state {
@@ -69,6 +81,7 @@ where
 {
     let mut shunting_yard = ShuntingYard::new();
     let mut state = ExpressionState::Prefix;
+    let base_depth = pm.state.depth;
 
     loop {
         //println!("\nState: {:?}", state);
@@ -94,14 +107,25 @@ where
                             PrefixOrAtom::Prefix(op) => {
                                 shunting_yard.add_prefix(pm, op, pt, point)?;
                                 state = ExpressionState::Prefix;
+
+                                // Each prefix operator nests its operand one
+                                // level deeper, until the operator is applied
+                                pm.state.depth = base_depth + shunting_yard.pending_prefixes();
+                                if pm.state.depth > pm.state.max_depth {
+                                    return Err((pt, Error::NestingTooDeep));
+                                }
                             }
-                            PrefixOrAtom::Atom(expr) => {
+                            PrefixOrAtom::Atom(Attributed { extent, attributes, value }) => {
+                                let expr = Attributed { extent, attributes, value: *value };
                                 shunting_yard.add_expression(expr, pt, point);
                                 state = ExpressionState::Atom;
                             }
                         }
                         pt = point;
                     }
+                    peresil::Progress { status: peresil::Status::Failure(Error::NestingTooDeep), point } => {
+                        return Err((point, Error::NestingTooDeep));
+                    }
                     peresil::Progress { status: peresil::Status::Failure(_), point } => {
                         return shunting_yard.finish(pm, point);
                     }
@@ -124,6 +148,9 @@ where
                         }
                         pt = point;
                     }
+                    peresil::Progress { status: peresil::Status::Failure(Error::NestingTooDeep), point } => {
+                        return Err((point, Error::NestingTooDeep));
+                    }
                     peresil::Progress { status: peresil::Status::Failure(_), point } => {
                         return shunting_yard.finish(pm, point);
                     }
@@ -136,6 +163,9 @@ where
                         state = ExpressionState::Postfix;
                         pt = point;
                     }
+                    peresil::Progress { status: peresil::Status::Failure(Error::NestingTooDeep), point } => {
+                        return Err((point, Error::NestingTooDeep));
+                    }
                     peresil::Progress { status: peresil::Status::Failure(_), point } => {
                         return shunting_yard.finish(pm, point);
                     }
@@ -143,6 +173,7 @@ where
             }
         }
 
+        pm.state.depth = base_depth + shunting_yard.pending_prefixes();
         state = adapt_state(&shunting_yard, state);
     }
 }
@@ -155,7 +186,7 @@ enum OperatorPrefix {
     Not(Extent),
     RangeExclusive(Extent),
     RangeInclusive(Extent),
-    Reference { is_mutable: Option<Extent> },
+    Reference { operator: Extent, is_mutable: Option<Extent> },
 }
 
 #[derive(Debug)]
@@ -206,10 +237,11 @@ impl OperatorInfix {
 #[derive(Debug)]
 enum OperatorPostfix {
     Ascription { typ: Type },
-    AsType { typ: Type },
+    AsType { operator: Extent, typ: Type },
+    Await,
     Call { args: Vec<Attributed<Expression>> },
     FieldAccess { field: FieldName },
-    Slice { index: Attributed<Expression> },
+    Slice { index: Box<Attributed<Expression>> },
     Try(Extent),
 }
 
@@ -266,7 +298,7 @@ impl OperatorKind {
 #[derive(Debug)]
 enum PrefixOrAtom {
     Prefix(Attributed<OperatorPrefix>),
-    Atom(Attributed<Expression>),
+    Atom(Attributed<Box<Expression>>),
 }
 
 fn expression_prefix_or_atom<'s>(pm: &mut Master<'s>, pt: Point<'s>) ->
@@ -315,9 +347,9 @@ fn operator_prefix_reference<'s>(pm: &mut Master<'s>, pt: Point<'s>) ->
     Progress<'s, OperatorPrefix>
 {
     sequence!(pm, pt, {
-        _          = ampersand;
+        operator   = ampersand;
         is_mutable = optional(kw_mut);
-    }, |_, _| OperatorPrefix::Reference { is_mutable })
+    }, |_, _| OperatorPrefix::Reference { operator, is_mutable })
 }
 
 fn operator_infix<'s>(pm: &mut Master<'s>, pt: Point<'s>) ->
@@ -367,6 +399,7 @@ fn operator_postfix<'s>(pm: &mut Master<'s>, pt: Point<'s>) ->
     pm.alternate(pt)
         .one(operator_postfix_as_type)
         .one(operator_postfix_ascription)
+        .one(operator_postfix_await)
         .one(operator_postfix_call)
         .one(operator_postfix_field_access)
         .one(operator_postfix_slice)
@@ -378,6 +411,7 @@ fn operator_postfix_limited<'s>(pm: &mut Master<'s>, pt: Point<'s>) ->
     Progress<'s, OperatorPostfix>
 {
     pm.alternate(pt)
+        .one(operator_postfix_await)
         .one(operator_postfix_field_access)
         .one(map(question_mark, OperatorPostfix::Try))
         .finish()
@@ -387,9 +421,9 @@ fn operator_postfix_as_type<'s>(pm: &mut Master<'s>, pt: Point<'s>) ->
     Progress<'s, OperatorPostfix>
 {
     sequence!(pm, pt, {
-        _   = kw_as;
-        typ = typ_single;
-    }, |_, _| OperatorPostfix::AsType { typ })
+        operator = kw_as;
+        typ      = typ_single;
+    }, |_, _| OperatorPostfix::AsType { operator, typ })
 }
 
 fn operator_postfix_ascription<'s>(pm: &mut Master<'s>, pt: Point<'s>) ->
@@ -401,6 +435,15 @@ fn operator_postfix_ascription<'s>(pm: &mut Master<'s>, pt: Point<'s>) ->
     }, |_, _| OperatorPostfix::Ascription { typ })
 }
 
+fn operator_postfix_await<'s>(pm: &mut Master<'s>, pt: Point<'s>) ->
+    Progress<'s, OperatorPostfix>
+{
+    sequence!(pm, pt, {
+        _ = period;
+        _ = kw_await_2018;
+    }, |_, _| OperatorPostfix::Await)
+}
+
 // TODO: avoid recursion here
 fn operator_postfix_call<'s>(pm: &mut Master<'s>, pt: Point<'s>) ->
     Progress<'s, OperatorPostfix>
@@ -441,35 +484,36 @@ fn operator_postfix_slice<'s>(pm: &mut Master<'s>, pt: Point<'s>) ->
         _     = left_square;
         index = head_expression_no_longer_ambiguous(expression);
         _     = right_square;
-    }, |_, _| OperatorPostfix::Slice { index })
+    }, |_, _| OperatorPostfix::Slice { index: Box::new(index) })
 }
 
-fn expression_atom<'s>(pm: &mut Master<'s>, pt: Point<'s>) -> Progress<'s, Expression> {
+fn expression_atom<'s>(pm: &mut Master<'s>, pt: Point<'s>) -> Progress<'s, Box<Expression>> {
     pm.alternate(pt)
-        .one(map(expr_if, Expression::If))
-        .one(map(expr_if_let, Expression::IfLet))
-        .one(map(expr_for_loop, Expression::ForLoop))
-        .one(map(expr_loop, Expression::Loop))
-        .one(map(expr_while, Expression::While))
-        .one(map(expr_while_let, Expression::WhileLet))
-        .one(map(expr_match, Expression::Match))
-        .one(map(expr_unsafe_block, Expression::UnsafeBlock))
-        .one(map(expr_block, Expression::Block))
-        .one(map(expr_macro_call, Expression::MacroCall))
-        .one(map(expr_let, Expression::Let))
-        .one(expr_tuple_or_parenthetical)
-        .one(map(expr_array, Expression::Array))
-        .one(map(character_literal, Expression::Character))
-        .one(map(string_literal, Expression::String))
-        .one(map(expr_closure, Expression::Closure))
-        .one(map(expr_return, Expression::Return))
-        .one(map(expr_continue, Expression::Continue))
-        .one(map(expr_break, Expression::Break))
-        .one(map(number_literal, Expression::Number))
-        .one(map(expr_byte, Expression::Byte))
-        .one(map(expr_byte_string, Expression::ByteString))
-        .one(map(expr_disambiguation, Expression::Disambiguation))
-        .one(map(expr_value, Expression::Value))
+        .one(boxed(map(expr_if, Expression::If)))
+        .one(boxed(map(expr_if_let, Expression::IfLet)))
+        .one(boxed(map(expr_for_loop, Expression::ForLoop)))
+        .one(boxed(map(expr_loop, Expression::Loop)))
+        .one(boxed(map(expr_while, Expression::While)))
+        .one(boxed(map(expr_while_let, Expression::WhileLet)))
+        .one(boxed(map(expr_match, Expression::Match)))
+        .one(boxed(map(expr_unsafe_block, Expression::UnsafeBlock)))
+        .one(boxed(map(expr_async_block, Expression::AsyncBlock)))
+        .one(boxed(map(expr_block, Expression::Block)))
+        .one(boxed(map(expr_macro_call, Expression::MacroCall)))
+        .one(boxed(map(expr_let, Expression::Let)))
+        .one(boxed(expr_tuple_or_parenthetical))
+        .one(boxed(map(expr_array, Expression::Array)))
+        .one(boxed(map(character_literal, Expression::Character)))
+        .one(boxed(map(string_literal, Expression::String)))
+        .one(boxed(map(expr_closure, Expression::Closure)))
+        .one(boxed(map(expr_return, Expression::Return)))
+        .one(boxed(map(expr_continue, Expression::Continue)))
+        .one(boxed(map(expr_break, Expression::Break)))
+        .one(boxed(map(number_literal, Expression::Number)))
+        .one(boxed(map(expr_byte, Expression::Byte)))
+        .one(boxed(map(expr_byte_string, Expression::ByteString)))
+        .one(boxed(map(expr_disambiguation, Expression::Disambiguation)))
+        .one(boxed(map(expr_value, Expression::Value)))
         .finish()
 }
 
@@ -529,6 +573,13 @@ impl<'s> ShuntingYard<'s> {
         Ok(())
     }
 
+    fn pending_prefixes(&self) -> usize {
+        self.operators.iter().filter(|op| match op.value {
+            OperatorKind::Prefix(..) => true,
+            _ => false,
+        }).count()
+    }
+
     fn finish(mut self, pm: &Master, failure_point: Point<'s>) ->
         ExprResult<'s, ShuntCar<'s, Attributed<Expression>>>
     {
@@ -567,19 +618,21 @@ impl<'s> ShuntingYard<'s> {
 
         match op {
             // TODO: Make into unary ?
-            Prefix(Attributed { extent, attributes, value: OperatorPrefix::Dereference(..) }) => {
+            Prefix(Attributed { extent, attributes, value: OperatorPrefix::Dereference(operator) }) => {
                 self.apply_prefix(pm, op_range, extent, attributes, |extent, expr| {
                     Expression::Dereference(Dereference {
                         extent,
+                        operator,
                         target: Box::new(expr),
                         whitespace: Vec::new(),
                     })
                 })
             },
-            Prefix(Attributed { extent, attributes, value: OperatorPrefix::Reference { is_mutable } }) => {
+            Prefix(Attributed { extent, attributes, value: OperatorPrefix::Reference { operator, is_mutable } }) => {
                 self.apply_prefix(pm, op_range, extent, attributes, |extent, expr| {
                     Expression::Reference(Reference {
                         extent,
+                        operator,
                         is_mutable,
                         target: Box::new(expr),
                     })
@@ -612,42 +665,42 @@ impl<'s> ShuntingYard<'s> {
                     })
                 })
             },
-            Prefix(Attributed { extent, attributes, value: OperatorPrefix::Negate(..) }) => {
-                self.apply_unary(pm, op_range, extent, attributes, UnaryOp::Negate)
+            Prefix(Attributed { extent, attributes, value: OperatorPrefix::Negate(op) }) => {
+                self.apply_unary(pm, op_range, extent, attributes, UnaryOp::Negate(op))
             },
-            Prefix(Attributed { extent, attributes, value: OperatorPrefix::Not(..) }) => {
-                self.apply_unary(pm, op_range, extent, attributes, UnaryOp::Not)
+            Prefix(Attributed { extent, attributes, value: OperatorPrefix::Not(op) }) => {
+                self.apply_unary(pm, op_range, extent, attributes, UnaryOp::Not(op))
             },
 
-            Infix(OperatorInfix::Add(..)) => self.apply_binary(pm, op_range, BinaryOp::Add),
-            Infix(OperatorInfix::AddAssign(..)) => self.apply_binary(pm, op_range, BinaryOp::AddAssign),
-            Infix(OperatorInfix::Assign(..)) => self.apply_binary(pm, op_range, BinaryOp::Assign),
-            Infix(OperatorInfix::BitwiseAnd(..)) => self.apply_binary(pm, op_range, BinaryOp::BitwiseAnd),
-            Infix(OperatorInfix::BitwiseAndAssign(..)) => self.apply_binary(pm, op_range, BinaryOp::BitwiseAndAssign),
-            Infix(OperatorInfix::BitwiseOr(..)) => self.apply_binary(pm, op_range, BinaryOp::BitwiseOr),
-            Infix(OperatorInfix::BitwiseOrAssign(..)) => self.apply_binary(pm, op_range, BinaryOp::BitwiseOrAssign),
-            Infix(OperatorInfix::BitwiseXor(..)) => self.apply_binary(pm, op_range, BinaryOp::BitwiseXor),
-            Infix(OperatorInfix::BitwiseXorAssign(..)) => self.apply_binary(pm, op_range, BinaryOp::BitwiseXorAssign),
-            Infix(OperatorInfix::BooleanAnd(..)) => self.apply_binary(pm, op_range, BinaryOp::BooleanAnd),
-            Infix(OperatorInfix::BooleanOr(..)) => self.apply_binary(pm, op_range, BinaryOp::BooleanOr),
-            Infix(OperatorInfix::Div(..)) => self.apply_binary(pm, op_range, BinaryOp::Div),
-            Infix(OperatorInfix::DivAssign(..)) => self.apply_binary(pm, op_range, BinaryOp::DivAssign),
-            Infix(OperatorInfix::Equal(..)) => self.apply_binary(pm, op_range, BinaryOp::Equal),
-            Infix(OperatorInfix::GreaterThan(..)) => self.apply_binary(pm, op_range, BinaryOp::GreaterThan),
-            Infix(OperatorInfix::GreaterThanOrEqual(..)) => self.apply_binary(pm, op_range, BinaryOp::GreaterThanOrEqual),
-            Infix(OperatorInfix::LessThan(..)) => self.apply_binary(pm, op_range, BinaryOp::LessThan),
-            Infix(OperatorInfix::LessThanOrEqual(..)) => self.apply_binary(pm, op_range, BinaryOp::LessThanOrEqual),
-            Infix(OperatorInfix::Mod(..)) => self.apply_binary(pm, op_range, BinaryOp::Mod),
-            Infix(OperatorInfix::ModAssign(..)) => self.apply_binary(pm, op_range, BinaryOp::ModAssign),
-            Infix(OperatorInfix::Mul(..)) => self.apply_binary(pm, op_range, BinaryOp::Mul),
-            Infix(OperatorInfix::MulAssign(..)) => self.apply_binary(pm, op_range, BinaryOp::MulAssign),
-            Infix(OperatorInfix::NotEqual(..)) => self.apply_binary(pm, op_range, BinaryOp::NotEqual),
-            Infix(OperatorInfix::ShiftLeft(..)) => self.apply_binary(pm, op_range, BinaryOp::ShiftLeft),
-            Infix(OperatorInfix::ShiftLeftAssign(..)) => self.apply_binary(pm, op_range, BinaryOp::ShiftLeftAssign),
-            Infix(OperatorInfix::ShiftRight(..)) => self.apply_binary(pm, op_range, BinaryOp::ShiftRight),
-            Infix(OperatorInfix::ShiftRightAssign(..)) => self.apply_binary(pm, op_range, BinaryOp::ShiftRightAssign),
-            Infix(OperatorInfix::Sub(..)) => self.apply_binary(pm, op_range, BinaryOp::Sub),
-            Infix(OperatorInfix::SubAssign(..)) => self.apply_binary(pm, op_range, BinaryOp::SubAssign),
+            Infix(OperatorInfix::Add(op)) => self.apply_binary(pm, op_range, BinaryOp::Add(op)),
+            Infix(OperatorInfix::AddAssign(op)) => self.apply_binary(pm, op_range, BinaryOp::AddAssign(op)),
+            Infix(OperatorInfix::Assign(op)) => self.apply_binary(pm, op_range, BinaryOp::Assign(op)),
+            Infix(OperatorInfix::BitwiseAnd(op)) => self.apply_binary(pm, op_range, BinaryOp::BitwiseAnd(op)),
+            Infix(OperatorInfix::BitwiseAndAssign(op)) => self.apply_binary(pm, op_range, BinaryOp::BitwiseAndAssign(op)),
+            Infix(OperatorInfix::BitwiseOr(op)) => self.apply_binary(pm, op_range, BinaryOp::BitwiseOr(op)),
+            Infix(OperatorInfix::BitwiseOrAssign(op)) => self.apply_binary(pm, op_range, BinaryOp::BitwiseOrAssign(op)),
+            Infix(OperatorInfix::BitwiseXor(op)) => self.apply_binary(pm, op_range, BinaryOp::BitwiseXor(op)),
+            Infix(OperatorInfix::BitwiseXorAssign(op)) => self.apply_binary(pm, op_range, BinaryOp::BitwiseXorAssign(op)),
+            Infix(OperatorInfix::BooleanAnd(op)) => self.apply_binary(pm, op_range, BinaryOp::BooleanAnd(op)),
+            Infix(OperatorInfix::BooleanOr(op)) => self.apply_binary(pm, op_range, BinaryOp::BooleanOr(op)),
+            Infix(OperatorInfix::Div(op)) => self.apply_binary(pm, op_range, BinaryOp::Div(op)),
+            Infix(OperatorInfix::DivAssign(op)) => self.apply_binary(pm, op_range, BinaryOp::DivAssign(op)),
+            Infix(OperatorInfix::Equal(op)) => self.apply_binary(pm, op_range, BinaryOp::Equal(op)),
+            Infix(OperatorInfix::GreaterThan(op)) => self.apply_binary(pm, op_range, BinaryOp::GreaterThan(op)),
+            Infix(OperatorInfix::GreaterThanOrEqual(op)) => self.apply_binary(pm, op_range, BinaryOp::GreaterThanOrEqual(op)),
+            Infix(OperatorInfix::LessThan(op)) => self.apply_binary(pm, op_range, BinaryOp::LessThan(op)),
+            Infix(OperatorInfix::LessThanOrEqual(op)) => self.apply_binary(pm, op_range, BinaryOp::LessThanOrEqual(op)),
+            Infix(OperatorInfix::Mod(op)) => self.apply_binary(pm, op_range, BinaryOp::Mod(op)),
+            Infix(OperatorInfix::ModAssign(op)) => self.apply_binary(pm, op_range, BinaryOp::ModAssign(op)),
+            Infix(OperatorInfix::Mul(op)) => self.apply_binary(pm, op_range, BinaryOp::Mul(op)),
+            Infix(OperatorInfix::MulAssign(op)) => self.apply_binary(pm, op_range, BinaryOp::MulAssign(op)),
+            Infix(OperatorInfix::NotEqual(op)) => self.apply_binary(pm, op_range, BinaryOp::NotEqual(op)),
+            Infix(OperatorInfix::ShiftLeft(op)) => self.apply_binary(pm, op_range, BinaryOp::ShiftLeft(op)),
+            Infix(OperatorInfix::ShiftLeftAssign(op)) => self.apply_binary(pm, op_range, BinaryOp::ShiftLeftAssign(op)),
+            Infix(OperatorInfix::ShiftRight(op)) => self.apply_binary(pm, op_range, BinaryOp::ShiftRight(op)),
+            Infix(OperatorInfix::ShiftRightAssign(op)) => self.apply_binary(pm, op_range, BinaryOp::ShiftRightAssign(op)),
+            Infix(OperatorInfix::Sub(op)) => self.apply_binary(pm, op_range, BinaryOp::Sub(op)),
+            Infix(OperatorInfix::SubAssign(op)) => self.apply_binary(pm, op_range, BinaryOp::SubAssign(op)),
 
             Infix(OperatorInfix::RangeInclusive(..)) => {
                 self.apply_maybe_infix(pm, op_range, |extent, lhs, rhs| {
@@ -691,14 +744,15 @@ impl<'s> ShuntingYard<'s> {
                     Expression::Slice(Slice {
                         extent,
                         target: Box::new(expr),
-                        index: Box::new(index),
+                        index,
                     }).into()
                 })
             },
-            Postfix(OperatorPostfix::AsType { typ }) => {
+            Postfix(OperatorPostfix::AsType { operator, typ }) => {
                 self.apply_postfix(pm, op_range, |extent, expr| {
                     Expression::AsType(AsType {
                         extent,
+                        operator,
                         target: Box::new(expr),
                         typ,
                     }).into()
@@ -713,10 +767,19 @@ impl<'s> ShuntingYard<'s> {
                     }).into()
                 })
             },
-            Postfix(OperatorPostfix::Try(..)) => {
+            Postfix(OperatorPostfix::Await) => {
+                self.apply_postfix(pm, op_range, |extent, expr| {
+                    Expression::Await(Await {
+                        extent,
+                        target: Box::new(expr),
+                    }).into()
+                })
+            },
+            Postfix(OperatorPostfix::Try(operator)) => {
                 self.apply_postfix(pm, op_range, |extent, expr| {
                     Expression::TryOperator(TryOperator {
                         extent,
+                        operator,
                         target: Box::new(expr),
                     }).into()
                 })
@@ -852,8 +915,13 @@ pub fn expr_macro_call<'s>(pm: &mut Master<'s>, pt: Point<'s>) -> Progress<'s, M
         name = ident;
         _    = bang;
         arg  = optional(ident);
+        apt  = point;
         args = expr_macro_call_args;
-    }, |pm: &mut Master, pt| MacroCall { extent: pm.state.ex(spt, pt), name, arg, args })
+    }, |pm: &mut Master, pt| {
+        let extent = pm.state.ex(spt, pt);
+        pm.state.record_macro_args(extent, apt);
+        MacroCall { extent, name, arg, args, expressions: None }
+    })
 }
 
 fn expr_macro_call_args<'s>(pm: &mut Master<'s>, pt: Point<'s>) -> Progress<'s, MacroCallArgs> {
@@ -1177,9 +1245,10 @@ fn expr_closure<'s>(pm: &mut Master<'s>, pt: Point<'s>) -> Progress<'s, Closure>
 
 fn expr_closure_arg<'s>(pm: &mut Master<'s>, pt: Point<'s>) -> Progress<'s, ClosureArg> {
     sequence!(pm, pt, {
+        spt  = point;
         name = pattern;
         typ  = optional(expr_closure_arg_type);
-    }, |_, _| ClosureArg { name, typ, whitespace: Vec::new() })
+    }, |pm: &mut Master, pt| ClosureArg { extent: pm.state.ex(spt, pt), name, typ, whitespace: Vec::new() })
 }
 
 fn expr_closure_arg_type<'s>(pm: &mut Master<'s>, pt: Point<'s>) -> Progress<'s, Type> {
@@ -1273,6 +1342,20 @@ fn expr_unsafe_block<'s>(pm: &mut Master<'s>, pt: Point<'s>) -> Progress<'s, Uns
     }, |pm: &mut Master, pt| UnsafeBlock { extent: pm.state.ex(spt, pt), body: Box::new(body), whitespace: Vec::new() })
 }
 
+fn expr_async_block<'s>(pm: &mut Master<'s>, pt: Point<'s>) -> Progress<'s, AsyncBlock> {
+    sequence!(pm, pt, {
+        spt  = point;
+        _    = kw_async_2018;
+        mov  = optional(kw_move);
+        body = block;
+    }, |pm: &mut Master, pt| AsyncBlock {
+        extent: pm.state.ex(spt, pt),
+        is_move: mov.is_some(),
+        body: Box::new(body),
+        whitespace: Vec::new(),
+    })
+}
+
 fn expr_value<'s>(pm: &mut Master<'s>, pt: Point<'s>) -> Progress<'s, Value> {
     if pm.state.expression_ambiguity.is_ambiguous() {
         sequence!(pm, pt, {
@@ -1309,13 +1392,13 @@ fn expr_value_struct_literal_field<'s>(pm: &mut Master<'s>, pt: Point<'s>) -> Pr
         name  = ident;
         mpt   = point;
         value = optional(expr_value_struct_literal_field_value);
-    }, |pm: &mut Master, _| {
+    }, |pm: &mut Master, pt| {
         let value = value.unwrap_or_else(|| Expression::Value(Value {
             extent: pm.state.ex(spt, mpt),
             name: name.into(),
             literal: None,
         }).into());
-        StructLiteralField { name, value, whitespace: Vec::new() }
+        StructLiteralField { extent: pm.state.ex(spt, pt), name, value, whitespace: Vec::new() }
     })
 }
 
@@ -1351,7 +1434,7 @@ fn expr_disambiguation<'s>(pm: &mut Master<'s>, pt: Point<'s>) -> Progress<'s, D
     })
 }
 
-#[derive(Debug, Copy, Clone, PartialEq)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub enum ExpressionAmbiguity {
     Unambiguous,
     OnlyStructLiterals,
@@ -1810,6 +1893,14 @@ mod test {
         assert_extent!(p, (0, 5))
     }
 
+    #[test]
+    fn expr_binary_op_has_operator_extent() {
+        let e = qp(expression, "a <= b");
+        let b = unwrap_as!(e.value, Expression::Binary);
+        assert!(b.op.as_less_than_or_equal().is_some());
+        assert_extent!(b.op, (2, 4));
+    }
+
     #[test]
     fn expr_binary_multiple() {
         let p = qp(expression, "1 + 2 + 3");
@@ -1972,6 +2063,18 @@ mod test {
         assert_extent!(p, (0, 23))
     }
 
+    #[test]
+    fn expr_value_struct_literal_field_with_value() {
+        let p = qp(expr_value_struct_literal_field, "a: 1");
+        assert_extent!(p, (0, 4))
+    }
+
+    #[test]
+    fn expr_value_struct_literal_field_shorthand() {
+        let p = qp(expr_value_struct_literal_field, "a");
+        assert_extent!(p, (0, 1))
+    }
+
     #[test]
     fn expr_value_starts_with_keyword() {
         let p = qp(expression, "continuez");
@@ -1990,6 +2093,12 @@ mod test {
         assert_extent!(p, (0, 5))
     }
 
+    #[test]
+    fn expr_closure_arg_with_type() {
+        let p = qp(expr_closure_arg, "a: u8");
+        assert_extent!(p, (0, 5))
+    }
+
     #[test]
     fn expr_closure_multiple() {
         let p = qp(expression, "|a, b| a + b");
@@ -2142,6 +2251,17 @@ mod test {
         assert_extent!(v3, (2, 5));
     }
 
+    #[test]
+    fn expr_reference_of_reference_has_operator_extents() {
+        let e = qp(expression, "&&foo");
+
+        let r1 = unwrap_as!(e.value, Expression::Reference);
+        assert_eq!(r1.operator, (0, 1));
+
+        let r2 = unwrap_as!(r1.target.value, Expression::Reference);
+        assert_eq!(r2.operator, (1, 2));
+    }
+
     #[test]
     fn expr_reference_mut() {
         let p = qp(expression, "&mut foo");
@@ -2154,6 +2274,13 @@ mod test {
         assert_extent!(p, (0, 4))
     }
 
+    #[test]
+    fn expr_dereference_has_operator_extent() {
+        let e = qp(expression, "* foo");
+        let d = unwrap_as!(e.value, Expression::Dereference);
+        assert_eq!(d.operator, (0, 1));
+    }
+
     #[test]
     fn expr_unary_not() {
         let p = qp(expression, "!foo");
@@ -2166,12 +2293,27 @@ mod test {
         assert_extent!(p, (0, 4))
     }
 
+    #[test]
+    fn expr_unary_has_operator_extent() {
+        let e = qp(expression, "- foo");
+        let u = unwrap_as!(e.value, Expression::Unary);
+        assert!(u.op.as_negate().is_some());
+        assert_extent!(u.op, (0, 1));
+    }
+
     #[test]
     fn expr_as_type() {
         let p = qp(expression, "42 as u8");
         assert_extent!(p, (0, 8))
     }
 
+    #[test]
+    fn expr_as_type_has_operator_extent() {
+        let e = qp(expression, "42 as u8");
+        let a = unwrap_as!(e.value, Expression::AsType);
+        assert_eq!(a.operator, (3, 5));
+    }
+
     #[test]
     fn expr_as_type_followed_by_addition() {
         let p = qp(expression, "42 as u8 + 1");
@@ -2266,6 +2408,13 @@ mod test {
         assert_extent!(p, (0, 4))
     }
 
+    #[test]
+    fn expr_try_operator_has_operator_extent() {
+        let e = qp(expression, "foo ?");
+        let t = unwrap_as!(e.value, Expression::TryOperator);
+        assert_eq!(t.operator, (4, 5));
+    }
+
     #[test]
     fn expr_box() {
         let p = qp(expression, "box foo");
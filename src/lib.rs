@@ -8,19 +8,35 @@ extern crate peresil;
 
 extern crate unicode_xid;
 
+#[cfg(feature = "serde")]
+#[macro_use]
+extern crate serde;
+
+#[cfg(all(test, feature = "serde"))]
+extern crate serde_json;
+
+#[cfg(test)]
+#[macro_use]
+extern crate quickcheck;
+
 #[cfg(test)]
 #[macro_use]
 mod test_utils;
 
 pub mod tokenizer;
+pub mod source;
+pub mod complexity;
+pub mod lint;
 mod expression;
 
-use std::collections::BTreeSet;
+use std::cell::Cell;
+use std::collections::{BTreeSet, HashMap};
 use std::fmt;
 
 use peresil::combinators::*;
 
-use tokenizer::{Token, Tokens};
+use tokenizer::{Token, TokenBuffer, Tokens, Trivia};
+use source::{ColumnUnit, LineColumn, LineIndex, Origin};
 use expression::{expression, statement_expression, expr_byte, expr_byte_string, expr_macro_call};
 
 type Point<'s> = TokenPoint<'s, Token>;
@@ -117,14 +133,52 @@ impl<'s, T> Eq for TokenPoint<'s, T> {}
 
 // -----
 
-#[derive(Debug, Default)]
+#[derive(Debug)]
 pub struct State {
     expression_ambiguity: expression::ExpressionAmbiguity,
+    // Where an extent could not be calculated, if anywhere
+    invalid_extent: Cell<Option<usize>>,
+    depth: usize,
+    max_depth: usize,
+    edition: Edition,
+    parse_macro_args: bool,
+    // Where the arguments of each macro call start and how deeply the
+    // call is nested, by where the call starts
+    macro_args: HashMap<usize, (usize, usize)>,
+    memo: Option<Memo>,
 }
 
 impl State {
     fn new() -> Self {
-        State::default()
+        State::with_options(&ParseOptions::default())
+    }
+
+    fn with_options(options: &ParseOptions) -> Self {
+        State {
+            expression_ambiguity: Default::default(),
+            invalid_extent: Cell::new(None),
+            depth: 0,
+            max_depth: options.max_depth,
+            edition: options.edition,
+            parse_macro_args: options.parse_macro_args,
+            macro_args: HashMap::new(),
+            memo: if options.memoize { Some(Memo::default()) } else { None },
+        }
+    }
+
+    // The arguments are parsed after the item containing the call, so
+    // that calls which are backtracked over aren't parsed at all
+    fn record_macro_args(&mut self, call: Extent, args: Point) {
+        if self.parse_macro_args {
+            self.macro_args.insert(call.0, (args.offset, self.depth));
+        }
+    }
+
+    // Nothing before the point will be parsed again
+    fn forget_memo(&mut self) {
+        if let Some(ref mut memo) = self.memo {
+            *memo = Memo::default();
+        }
     }
 
     fn ex(&self, start: Point, end: Point) -> Extent {
@@ -135,52 +189,89 @@ impl State {
         // use the initial point.
         let relative_tokens = start.s;
 
-        let start_offset = |pt: Point| -> usize {
-            let (a, _) = relative_tokens[0].extent();
+        let start_offset = |pt: Point| -> Option<usize> {
+            let (a, _) = relative_tokens.first()?.extent();
             let a_x = pt.sub_offset.map_or(0, |x| x + 1) as usize;
-            a + a_x
+            Some(a + a_x)
         };
 
-        let end_offset = |pt: Point| -> usize {
+        let end_offset = |pt: Point| -> Option<usize> {
             let offset = pt.offset - start.offset - 1;
-            let (_, b) = relative_tokens[offset].extent();
+            let (_, b) = relative_tokens.get(offset)?.extent();
             let b_x = pt.sub_offset.map_or(0, |x| x + 1) as usize;
-            b + b_x
+            Some(b + b_x)
         };
 
-        match start.offset.cmp(&end.offset) {
+        let extent = match start.offset.cmp(&end.offset) {
             Ordering::Less => {
-                let a = start_offset(start);
-                let b = end_offset(end);
-                (a, b)
+                start_offset(start).and_then(|a| end_offset(end).map(|b| (a, b)))
             }
             Ordering::Equal => {
                 match start.sub_offset.cmp(&end.sub_offset) {
                     Ordering::Less => {
-                        let a = start_offset(start);
-                        let b = start_offset(end);
-                        (a, b)
-                    }
-                    Ordering::Equal => {
-                        let a = start_offset(start);
-                        (a, a)
+                        start_offset(start).and_then(|a| start_offset(end).map(|b| (a, b)))
                     }
-                    Ordering::Greater => panic!("points are backwards ({:?}, {:?})", start, end),
+                    Ordering::Equal => start_offset(start).map(|a| (a, a)),
+                    Ordering::Greater => None,
                 }
             }
-            Ordering::Greater => panic!("points are backwards ({:?}, {:?})", start, end),
-        }
+            Ordering::Greater => None,
+        };
+
+        // Points that are backwards or past the end of the tokens
+        // are a bug in the parser. Rather than panic, remember the
+        // first occurrence so that the parse can report an error.
+        extent.unwrap_or_else(|| {
+            let location = relative_tokens.first().map_or(0, |t| t.extent().0);
+            if self.invalid_extent.get().is_none() {
+                self.invalid_extent.set(Some(location));
+            }
+            (location, location)
+        })
+    }
+
+    fn invalid_extent_error(&self) -> Option<ErrorDetail> {
+        self.invalid_extent.get().map(|location| {
+            ErrorDetail::Parser(ParserErrorDetail {
+                location,
+                errors: Some(Error::InvalidExtent).into_iter().collect(),
+            })
+        })
     }
 }
 
+// Where a rule started parsing, and anything in the state that could
+// change what it parses
+type MemoKey = (usize, Option<u8>, usize, expression::ExpressionAmbiguity);
+
+#[derive(Debug)]
+struct MemoEntry<T> {
+    end: (usize, Option<u8>),
+    result: Result<T, Error>,
+}
+
+type MemoTable<T> = HashMap<MemoKey, MemoEntry<T>>;
+
+/// The results of rules that are parsed again when an enclosing
+/// alternative fails, such as the path at the start of many patterns
+/// and expressions.
+#[derive(Debug, Default)]
+struct Memo {
+    attributes: MemoTable<Vec<Attribute>>,
+    pathed_ident: MemoTable<PathedIdent>,
+    typ: MemoTable<Type>,
+}
+
 // define an error type - emphasis on errors. Need to implement Recoverable (more to discuss.
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub enum Error {
     ExpectedAmpersand,
     ExpectedAmpersandEquals,
     ExpectedAs,
     ExpectedAsterisk,
+    ExpectedAsync,
     ExpectedAt,
+    ExpectedAwait,
     ExpectedBackslash,
     ExpectedBang,
     ExpectedBox,
@@ -206,7 +297,9 @@ pub enum Error {
     ExpectedDoublePeriod,
     ExpectedDoublePipe,
     ExpectedDoubleRightAngle,
+    ExpectedDyn,
     ExpectedElse,
+    ExpectedEndOfFile,
     ExpectedEnum,
     ExpectedEquals,
     ExpectedExtern,
@@ -264,6 +357,7 @@ pub enum Error {
     ExpectedTimesEquals,
     ExpectedTrait,
     ExpectedTriplePeriod,
+    ExpectedTry,
     ExpectedType,
     ExpectedUnion,
     ExpectedUnsafe,
@@ -274,10 +368,22 @@ pub enum Error {
     ExpectedExpression,
 
     BlockNotAllowedHere,
+
+    // The parser got confused; these are bugs in the parser
+    UnableToMakeProgress,
+    CannotResumeSplitToken,
+    InvalidExtent,
+
+    NestingTooDeep,
+
+    // An identifier that is a keyword in the requested edition
+    ReservedKeyword,
 }
 
 impl peresil::Recoverable for Error {
-    fn recoverable(&self) -> bool { true }
+    // Nothing else can succeed once the nesting limit is hit, and
+    // trying would only bury the error among other expectations.
+    fn recoverable(&self) -> bool { *self != Error::NestingTooDeep }
 }
 
 #[derive(Debug, PartialEq)]
@@ -288,7 +394,20 @@ pub enum ErrorDetail {
 
 impl ErrorDetail {
     pub fn with_text<'a>(&'a self, text: &'a str) -> ErrorDetailText<'a> {
-        ErrorDetailText { detail: self, text }
+        ErrorDetailText { detail: self, text, index: None, origin: Origin::default() }
+    }
+
+    /// Like `with_text`, but reuses an existing index of the text.
+    pub fn with_text_and_index<'a>(&'a self, text: &'a str, index: &'a LineIndex) -> ErrorDetailText<'a> {
+        self.with_text(text).with_index(index)
+    }
+
+    /// The byte offset where the error was detected.
+    pub fn location(&self) -> usize {
+        match *self {
+            ErrorDetail::Tokenizer(ref d) => d.location(),
+            ErrorDetail::Parser(ref d) => d.location(),
+        }
     }
 }
 
@@ -308,13 +427,37 @@ impl From<ParserErrorDetail> for ErrorDetail {
 pub struct ErrorDetailText<'a> {
     detail: &'a ErrorDetail,
     text: &'a str,
+    index: Option<&'a LineIndex>,
+    origin: Origin,
+}
+
+impl<'a> ErrorDetailText<'a> {
+    /// Reuse an existing index of the text instead of building one.
+    pub fn with_index(self, index: &'a LineIndex) -> Self {
+        ErrorDetailText { index: Some(index), ..self }
+    }
+
+    /// Treat the text as a fragment of a larger document.
+    pub fn with_origin(self, origin: Origin) -> Self {
+        ErrorDetailText { origin, ..self }
+    }
 }
 
 impl<'a> fmt::Display for ErrorDetailText<'a> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let built;
+        let index = match self.index {
+            Some(index) => index,
+            None => { built = LineIndex::new(self.text); &built }
+        };
+
         match *self.detail {
-            ErrorDetail::Tokenizer(ref t) => t.with_text(self.text).fmt(f),
-            ErrorDetail::Parser(ref p) => p.with_text(self.text).fmt(f),
+            ErrorDetail::Tokenizer(ref t) => {
+                t.with_text(self.text).with_index(index).with_origin(self.origin).fmt(f)
+            }
+            ErrorDetail::Parser(ref p) => {
+                p.with_text(self.text).with_index(index).with_origin(self.origin).fmt(f)
+            }
         }
     }
 }
@@ -327,23 +470,56 @@ pub struct ParserErrorDetail {
 
 impl ParserErrorDetail {
     pub fn with_text<'a>(&'a self, text: &'a str) -> ParserErrorDetailText<'a> {
-        ParserErrorDetailText { detail: self, text }
+        ParserErrorDetailText { detail: self, text, index: None, origin: Origin::default() }
+    }
+
+    /// Like `with_text`, but reuses an existing index of the text.
+    pub fn with_text_and_index<'a>(&'a self, text: &'a str, index: &'a LineIndex) -> ParserErrorDetailText<'a> {
+        self.with_text(text).with_index(index)
     }
+
+    /// The byte offset of the token that could not be parsed.
+    pub fn location(&self) -> usize { self.location }
+
+    /// What was expected at the location.
+    pub fn errors(&self) -> &BTreeSet<Error> { &self.errors }
 }
 
 #[derive(Debug)]
 pub struct ParserErrorDetailText<'a> {
     detail: &'a ParserErrorDetail,
     text: &'a str,
+    index: Option<&'a LineIndex>,
+    origin: Origin,
+}
+
+impl<'a> ParserErrorDetailText<'a> {
+    /// Reuse an existing index of the text instead of building one.
+    pub fn with_index(self, index: &'a LineIndex) -> Self {
+        ParserErrorDetailText { index: Some(index), ..self }
+    }
+
+    /// Treat the text as a fragment of a larger document.
+    pub fn with_origin(self, origin: Origin) -> Self {
+        ParserErrorDetailText { origin, ..self }
+    }
 }
 
 impl<'a> fmt::Display for ParserErrorDetailText<'a> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let human = HumanTextError::new(self.text, self.detail.location);
-
-        writeln!(f, "Unable to parse text (line {}, column {})", human.line, human.column)?;
-        writeln!(f, "{}{}", human.head_of_line, human.tail_of_line)?;
-        writeln!(f, "{:>width$}", "^", width = human.column)?;
+        let built;
+        let index = match self.index {
+            Some(index) => index,
+            None => { built = LineIndex::new(self.text); &built }
+        };
+        match HumanTextError::new(self.text, index, self.origin, self.detail.location) {
+            Some(human) => {
+                writeln!(f, "Unable to parse text (line {}, column {})", human.line, human.column)?;
+                writeln!(f, "{}{}", human.head_of_line, human.tail_of_line)?;
+                writeln!(f, "{:>width$}", "^", width = human.caret_column)?;
+            }
+            None => writeln!(f, "Unable to parse text (byte {})", self.detail.location)?,
+        }
         writeln!(f, "Expected:")?;
         for e in &self.detail.errors {
             writeln!(f, "  {:?}", e)?; // TODO: should be Display
@@ -357,21 +533,29 @@ struct HumanTextError<'a> {
     tail_of_line: &'a str,
     line: usize,
     column: usize,
+    caret_column: usize,
 }
 
 impl<'a> HumanTextError<'a> {
-    fn new(text: &'a str, location: usize) -> HumanTextError<'a> {
-        let (head, tail) = text.split_at(location);
-        let start_of_line = head.rfind("\n").unwrap_or(0);
-        let end_of_line = tail.find("\n").unwrap_or_else(|| tail.len());
+    // `None` when the location isn't inside of the text, such as when
+    // the error is rendered with the wrong text or origin
+    fn new(text: &'a str, index: &LineIndex, origin: Origin, location: usize) -> Option<HumanTextError<'a>> {
+        let location = location.checked_sub(origin.offset)?;
+        let position = index.to_line_column(location, ColumnUnit::Char)?;
+        let (start_of_line, end_of_line) = index.line_extent(position.line)?;
+        let location = index.to_offset(position, ColumnUnit::Char)?;
+
+        let head_of_line = text.get(start_of_line..location)?;
+        let tail_of_line = text.get(location..end_of_line)?.trim_right_matches('\r');
 
-        let head_of_line = &head[start_of_line..];
-        let tail_of_line = &tail[..end_of_line];
+        let in_document = origin.to_line_column(index, origin.offset + location, ColumnUnit::Char)?;
 
-        let line = head.matches("\n").count() + 1; // Normally the first line is #1, so add one
-        let column = head_of_line.len();
+        // Normally the first line and column are #1, so add one
+        let line = in_document.line + 1;
+        let column = in_document.column + 1;
+        let caret_column = position.column + 1;
 
-        HumanTextError { head_of_line, tail_of_line, line, column }
+        Some(HumanTextError { head_of_line, tail_of_line, line, column, caret_column })
     }
 }
 
@@ -379,45 +563,399 @@ impl<'a> HumanTextError<'a> {
 // todo: rename?
 
 pub fn parse_rust_file(file: &str) -> Result<File, ErrorDetail> {
-    let tokens: Vec<_> = Tokens::new(file).collect::<Result<_, _>>()?;
-    let (_ws, tokens): (Vec<_>, Vec<_>) = tokens.into_iter().partition(|t| {
-        t.is_whitespace() || t.is_comment() || t.is_doc_comment() || t.is_comment_block() || t.is_doc_comment_block()
-    });
+    ParseOptions::default().parse(file).map(|p| p.file)
+}
 
-    let mut pt = Point::new(&tokens);
-    let mut pm = Master::with_state(State::new());
-    let mut items = Vec::new();
+/// Like `parse_rust_file`, but with control over how the parse
+/// happens. When recovering from errors, the first error is
+/// returned; use `ParseOptions::parse` to see all of them.
+pub fn parse_rust_file_with_options(file: &str, options: &ParseOptions) -> Result<File, ErrorDetail> {
+    let parsed = options.parse(file)?;
+    match parsed.errors.into_iter().next() {
+        Some(e) => Err(e),
+        None => Ok(parsed.file),
+    }
+}
 
-    loop {
-        if pt.s.first().map(Token::is_end_of_file).unwrap_or(true) { break }
+/// The default limit on how deeply modules, blocks, expressions, types
+/// and patterns may nest. Each visible level of code uses a few levels,
+/// so closures in method chains inside of `match` arms and `if`s can
+/// be nested several times over.
+///
+/// Reaching the limit needs about 4 MiB of stack in a release build
+/// and 16 MiB in a debug build. Parse untrusted text on a thread with
+/// that much stack, or lower the limit.
+pub const DEFAULT_MAX_DEPTH: usize = 128;
 
-        let item = attributed(item)(&mut pm, pt);
-        let item = pm.finish(item);
+/// The edition of Rust that the text is written in.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Edition {
+    Rust2015,
+    Rust2018,
+}
 
-        let next_pt = match item.status {
-            peresil::Status::Success(s) => {
-                items.push(s);
-                item.point
-            },
-            peresil::Status::Failure(e) => {
-                return Err(ErrorDetail::Parser(ParserErrorDetail {
-                    location: tokens[item.point.offset].extent().0,
-                    errors: e.into_iter().collect(),
-                }))
-            },
-        };
+impl Default for Edition {
+    fn default() -> Self { Edition::Rust2015 }
+}
 
-        if next_pt.offset <= pt.offset {
-            panic!("Unable to make progress");
+/// Options that control how text is parsed.
+///
+/// ```
+/// use fuzzy_pickles::{Edition, ParseOptions};
+///
+/// let parsed = ParseOptions::default()
+///     .with_edition(Edition::Rust2018)
+///     .with_trivia(true)
+///     .parse("// Hello\nfn main() {}")
+///     .expect("Unable to parse");
+///
+/// assert_eq!(parsed.file.items().len(), 1);
+/// let trivia = parsed.trivia.expect("Trivia was not kept");
+/// assert!(trivia.tokens()[0].is_comment());
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ParseOptions {
+    max_depth: usize,
+    edition: Edition,
+    keep_trivia: bool,
+    recover: bool,
+    parse_macro_args: bool,
+    base_offset: usize,
+    memoize: bool,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        ParseOptions {
+            max_depth: DEFAULT_MAX_DEPTH,
+            edition: Edition::default(),
+            keep_trivia: false,
+            recover: false,
+            parse_macro_args: false,
+            base_offset: 0,
+            memoize: false,
         }
-        pt = next_pt;
     }
+}
+
+impl ParseOptions {
+    /// Limit how deeply modules, blocks, expressions, types and
+    /// patterns may nest. Prefix operators such as `&` and `!` each
+    /// count as a level while their operand is parsed. Exceeding the
+    /// limit fails with `Error::NestingTooDeep` instead of overflowing
+    /// the stack. See `DEFAULT_MAX_DEPTH` for how much stack is needed.
+    pub fn with_max_depth(self, max_depth: usize) -> Self {
+        ParseOptions { max_depth, ..self }
+    }
+
+    /// Rust 2018 adds `dyn` trait objects, `async` functions and
+    /// blocks and `.await`. Its new keywords fail with
+    /// `Error::ReservedKeyword` when used as identifiers; raw
+    /// identifiers such as `r#async` are allowed in both editions.
+    pub fn with_edition(self, edition: Edition) -> Self {
+        ParseOptions { edition, ..self }
+    }
+
+    /// Keep the whitespace and comments in `ParsedFile::trivia`
+    /// instead of discarding them.
+    pub fn with_trivia(self, keep_trivia: bool) -> Self {
+        ParseOptions { keep_trivia, ..self }
+    }
+
+    /// Continue parsing after an item fails to parse. The rest of the
+    /// item is skipped and the error is added to `ParsedFile::errors`.
+    pub fn with_recovery(self, recover: bool) -> Self {
+        ParseOptions { recover, ..self }
+    }
+
+    /// Try to parse the arguments of macro calls as expressions
+    /// separated by commas, as used by `println!` and `vec!`. See
+    /// `MacroCall::expressions`.
+    pub fn with_macro_args(self, parse_macro_args: bool) -> Self {
+        ParseOptions { parse_macro_args, ..self }
+    }
+
+    /// The byte offset of the text in a larger document. Extents and
+    /// error locations are relative to the start of the document.
+    pub fn with_base_offset(self, base_offset: usize) -> Self {
+        ParseOptions { base_offset, ..self }
+    }
+
+    /// Remember the results of rules that are commonly parsed more
+    /// than once at the same token, such as paths, types and
+    /// attributes, when an enclosing alternative fails. Results are
+    /// kept until the end of each top-level item.
+    ///
+    /// Little of typical code is parsed more than once and most of
+    /// the time is spent tokenizing, so the bookkeeping can cost more
+    /// than it saves; measure before enabling.
+    pub fn with_memoization(self, memoize: bool) -> Self {
+        ParseOptions { memoize, ..self }
+    }
+
+    pub fn max_depth(&self) -> usize { self.max_depth }
+    pub fn edition(&self) -> Edition { self.edition }
+    pub fn keeps_trivia(&self) -> bool { self.keep_trivia }
+    pub fn recovers(&self) -> bool { self.recover }
+    pub fn parses_macro_args(&self) -> bool { self.parse_macro_args }
+    pub fn base_offset(&self) -> usize { self.base_offset }
+    pub fn memoizes(&self) -> bool { self.memoize }
+
+    /// Parses an entire file.
+    pub fn parse(&self, text: &str) -> Result<ParsedFile, ErrorDetail> {
+        parse_file(text, self)
+    }
+}
+
+/// A file parsed by `ParseOptions::parse`.
+#[derive(Debug)]
+pub struct ParsedFile {
+    pub file: File,
+    /// The whitespace and comments of the text, along with where
+    /// they are between the other tokens. Only kept when
+    /// `ParseOptions::with_trivia` is enabled.
+    pub trivia: Option<Trivia>,
+    /// The errors that were recovered from, in order. Empty unless
+    /// `ParseOptions::with_recovery` is enabled, as otherwise the
+    /// first error fails the parse.
+    pub errors: Vec<ErrorDetail>,
+}
+
+fn parse_file(text: &str, options: &ParseOptions) -> Result<ParsedFile, ErrorDetail> {
+    let tokens = Tokens::with_offset(text, options.base_offset);
+    let buffer = if options.keep_trivia {
+        TokenBuffer::with_trivia(tokens)?
+    } else {
+        TokenBuffer::new(tokens)?
+    };
+
+    // The file spans all of the text, including leading and trailing
+    // whitespace and comments.
+    let extent = buffer.extent();
+    let (items, errors) = {
+        let tokens = buffer.tokens();
+
+        let mut errors = Vec::new();
+
+        let mut pt = Point::new(tokens);
+        let mut pm = Master::with_state(State::with_options(options));
+        let mut items = Vec::new();
+
+        loop {
+            if pt.s.first().map(Token::is_end_of_file).unwrap_or(true) { break }
+
+            let item = attributed(item)(&mut pm, pt);
+            let item = pm.finish(item);
+            pm.state.forget_memo();
+
+            let next_pt = match item.status {
+                peresil::Status::Success(mut s) => {
+                    if options.parse_macro_args {
+                        s.visit_mut(&mut MacroArgs { pm: &mut pm, tokens });
+                        pm.state.macro_args.clear();
+                        pm.state.forget_memo();
+                    }
+                    items.push(s);
+                    item.point
+                },
+                peresil::Status::Failure(e) => {
+                    let error = parser_error(tokens, item.point, e);
+                    if !options.recover {
+                        return Err(error);
+                    }
+                    errors.push(error);
+                    skip_item(pt)
+                },
+            };
+
+            if next_pt.offset <= pt.offset {
+                return Err(parser_error(tokens, pt, vec![Error::UnableToMakeProgress]));
+            }
+            pt = next_pt;
+        }
 
-    Ok(File { items: items })
+        if let Some(e) = pm.state.invalid_extent_error() {
+            return Err(e);
+        }
+
+        errors.sort_by_key(ErrorDetail::location);
+        (items, errors)
+    };
+
+    // The other tokens are only needed while parsing.
+    let trivia = if options.keep_trivia { Some(buffer.into_trivia()) } else { None };
+
+    Ok(ParsedFile { file: File { extent, items }, trivia, errors })
 
     // TODO: add `expect` to progress?
 }
 
+// Skips past the item that starts at the point: up to a semicolon or
+// a closing curly brace that isn't nested inside of anything else.
+fn skip_item(pt: Point) -> Point {
+    let mut skipped = 0;
+    let mut depth: usize = 0;
+
+    for token in pt.s {
+        if token.is_end_of_file() {
+            break;
+        }
+        skipped += 1;
+
+        if token.is_left_curly() || token.is_left_paren() || token.is_left_square() {
+            depth += 1;
+        } else if token.is_right_curly() || token.is_right_paren() || token.is_right_square() {
+            depth = depth.saturating_sub(1);
+            if depth == 0 && token.is_right_curly() {
+                break;
+            }
+        } else if depth == 0 && token.is_semicolon() {
+            break;
+        }
+    }
+
+    pt.advance_by(skipped)
+}
+
+/// Parses a single expression, such as `a + b * c`.
+pub fn parse_expression(text: &str) -> Result<Attributed<Expression>, ErrorDetail> {
+    parse_complete(Tokens::new(text), expression)
+}
+
+/// Parses a single type, such as `Vec<&'a str>`.
+pub fn parse_type(text: &str) -> Result<Type, ErrorDetail> {
+    parse_complete(Tokens::new(text), typ)
+}
+
+/// Parses a single pattern, such as `Some(ref x)`.
+pub fn parse_pattern(text: &str) -> Result<Pattern, ErrorDetail> {
+    parse_complete(Tokens::new(text), pattern)
+}
+
+/// Parses a single item, such as a function or a struct, along
+/// with any attributes.
+pub fn parse_item(text: &str) -> Result<Attributed<Item>, ErrorDetail> {
+    parse_complete(Tokens::new(text), |pm, pt| attributed(item)(pm, pt))
+}
+
+/// Parses a single statement, without the trailing semicolon.
+pub fn parse_statement(text: &str) -> Result<Statement, ErrorDetail> {
+    parse_complete(Tokens::new(text), statement)
+}
+
+/// Parses a block, including the surrounding curly braces.
+pub fn parse_block(text: &str) -> Result<Block, ErrorDetail> {
+    parse_complete(Tokens::new(text), block)
+}
+
+/// Rust source embedded inside of a larger document, such as a code
+/// block in Markdown or a doc comment.
+///
+/// Extents and error locations produced by parsing a fragment are
+/// relative to the start of the document, not the fragment.
+#[derive(Debug, Copy, Clone)]
+pub struct Fragment<'a> {
+    text: &'a str,
+    origin: Origin,
+}
+
+impl<'a> Fragment<'a> {
+    /// `offset` is the byte offset of `text` in the document.
+    pub fn new(text: &'a str, offset: usize) -> Self {
+        Fragment { text, origin: Origin { offset, line_column: LineColumn::default() } }
+    }
+
+    /// The line and column of the start of the fragment in the
+    /// document, used when rendering errors against the fragment's
+    /// text alone.
+    pub fn with_line_column(self, line_column: LineColumn) -> Self {
+        Fragment { origin: Origin { line_column, ..self.origin }, ..self }
+    }
+
+    pub fn text(&self) -> &'a str { self.text }
+    pub fn origin(&self) -> Origin { self.origin }
+
+    pub fn parse_file(&self) -> Result<File, ErrorDetail> {
+        ParseOptions::default()
+            .with_base_offset(self.origin.offset)
+            .parse(self.text)
+            .map(|p| p.file)
+    }
+
+    pub fn parse_expression(&self) -> Result<Attributed<Expression>, ErrorDetail> {
+        parse_complete(self.tokens(), expression)
+    }
+
+    pub fn parse_type(&self) -> Result<Type, ErrorDetail> {
+        parse_complete(self.tokens(), typ)
+    }
+
+    pub fn parse_pattern(&self) -> Result<Pattern, ErrorDetail> {
+        parse_complete(self.tokens(), pattern)
+    }
+
+    pub fn parse_item(&self) -> Result<Attributed<Item>, ErrorDetail> {
+        parse_complete(self.tokens(), |pm, pt| attributed(item)(pm, pt))
+    }
+
+    pub fn parse_statement(&self) -> Result<Statement, ErrorDetail> {
+        parse_complete(self.tokens(), statement)
+    }
+
+    pub fn parse_block(&self) -> Result<Block, ErrorDetail> {
+        parse_complete(self.tokens(), block)
+    }
+
+    /// Render an error using only the text of the fragment. Use
+    /// `ErrorDetail::with_text` when the document is available.
+    pub fn error_text(&self, detail: &'a ErrorDetail) -> ErrorDetailText<'a> {
+        detail.with_text(self.text).with_origin(self.origin)
+    }
+
+    fn tokens(&self) -> Tokens<'a> {
+        Tokens::with_offset(self.text, self.origin.offset)
+    }
+}
+
+// Runs the parser over the entire text; anything left over is an error
+fn parse_complete<F, T>(tokens: Tokens, f: F) -> Result<T, ErrorDetail>
+    where F: for<'s> FnOnce(&mut Master<'s>, Point<'s>) -> Progress<'s, T>
+{
+    let buffer = TokenBuffer::new(tokens)?;
+
+    let pt = Point::new(buffer.tokens());
+    let mut pm = Master::with_state(State::new());
+
+    let r = complete(f)(&mut pm, pt);
+    let r = pm.finish(r);
+
+    match r.status {
+        peresil::Status::Success(v) => match pm.state.invalid_extent_error() {
+            Some(e) => Err(e),
+            None => Ok(v),
+        },
+        peresil::Status::Failure(e) => Err(parser_error(buffer.tokens(), r.point, e)),
+    }
+}
+
+fn complete<'s, F, T>(f: F) -> impl FnOnce(&mut Master<'s>, Point<'s>) -> Progress<'s, T>
+    where F: FnOnce(&mut Master<'s>, Point<'s>) -> Progress<'s, T>
+{
+    move |pm, pt| {
+        sequence!(pm, pt, {
+            value = f;
+            _     = end_of_file;
+        }, |_, _| value)
+    }
+}
+
+fn parser_error(tokens: &[Token], pt: Point, errors: Vec<Error>) -> ErrorDetail {
+    ErrorDetail::Parser(ParserErrorDetail {
+        location: tokens[pt.offset].extent().0,
+        errors: errors.into_iter().collect(),
+    })
+}
+
 // TODO: enum variants track whole extent, enum delegates
 
 pub type Extent = (usize, usize);
@@ -438,12 +976,15 @@ impl HasExtent for Extent {
     fn extent(&self) -> Extent { *self }
 }
 
-#[derive(Debug, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct File {
+    extent: Extent,
     items: Vec<Attributed<Item>>,
 }
 
-#[derive(Debug, HasExtent, Visit, Decompose)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Decompose)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Item {
     AttributeContaining(AttributeContaining),
     Const(Const),
@@ -462,37 +1003,43 @@ pub enum Item {
     Union(Union),
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Attribute {
     extent: Extent,
     text: Extent,
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct AttributeContaining {
     extent: Extent,
     text: Extent,
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Lifetime {
     extent: Extent,
     name: Ident,
 }
 
-#[derive(Debug, HasExtent, Visit, Decompose)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Decompose)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Whitespace {
     Comment(Comment),
     Whitespace(Extent),
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Comment {
     extent: Extent,
     text: Extent,
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Use {
     extent: Extent,
     visibility: Option<Visibility>,
@@ -501,32 +1048,37 @@ pub struct Use {
     whitespace: Vec<Whitespace>,
 }
 
-#[derive(Debug, HasExtent, Visit, Decompose)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Decompose)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum UseTail {
     Ident(UseTailIdent),
     Glob(UseTailGlob),
     Multi(UseTailMulti),
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct UseTailIdent {
     extent: Extent,
     name: Ident,
     rename: Option<Ident>,
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct UseTailGlob {
     extent: Extent,
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct UseTailMulti {
     extent: Extent,
     names: Vec<UseTailIdent>,
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Function {
     pub extent: Extent,
     pub header: FunctionHeader,
@@ -534,7 +1086,8 @@ pub struct Function {
     whitespace: Vec<Whitespace>,
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct FunctionHeader {
     pub extent: Extent,
     visibility: Option<Visibility>,
@@ -547,17 +1100,20 @@ pub struct FunctionHeader {
     whitespace: Vec<Whitespace>,
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct FunctionQualifiers {
     pub extent: Extent,
     is_default: Option<Extent>,
     is_const: Option<Extent>,
+    is_async: Option<Extent>,
     is_unsafe: Option<Extent>,
     is_extern: Option<Extent>,
     abi: Option<String>,
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct TraitImplFunctionHeader {
     extent: Extent,
     visibility: Option<Visibility>,
@@ -570,21 +1126,24 @@ pub struct TraitImplFunctionHeader {
     whitespace: Vec<Whitespace>,
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct GenericDeclarations {
     pub extent: Extent,
     lifetimes: Vec<Attributed<GenericDeclarationLifetime>>,
     types: Vec<Attributed<GenericDeclarationType>>,
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct GenericDeclarationLifetime {
     extent: Extent,
     name: Lifetime,
     bounds: Vec<Lifetime>,
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct GenericDeclarationType {
     extent: Extent,
     name: Ident,
@@ -592,17 +1151,20 @@ pub struct GenericDeclarationType {
     default: Option<Type>,
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Type {
     extent: Extent,
     kind: TypeKind,
     additional: Vec<TypeAdditional>,
 }
 
-#[derive(Debug, HasExtent, Visit, Decompose)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Decompose)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum TypeKind {
     Array(TypeArray),
     Disambiguation(TypeDisambiguation),
+    DynTrait(TypeDynTrait),
     Function(TypeFunction),
     HigherRankedTraitBounds(TypeHigherRankedTraitBounds),
     ImplTrait(TypeImplTrait),
@@ -614,14 +1176,16 @@ pub enum TypeKind {
     Uninhabited(Extent),
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct TypeReference {
     extent: Extent,
     kind: TypeReferenceKind,
     typ: Box<Type>,
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct TypeReferenceKind {
     extent: Extent,
     lifetime: Option<Lifetime>,
@@ -629,7 +1193,8 @@ pub struct TypeReferenceKind {
     whitespace: Vec<Whitespace>,
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct TypePointer {
     extent: Extent,
     kind: TypePointerKind,
@@ -637,13 +1202,15 @@ pub struct TypePointer {
     whitespace: Vec<Whitespace>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum TypePointerKind {
     Const,
     Mutable,
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct TypeArray {
     extent: Extent,
     typ: Box<Type>,
@@ -651,7 +1218,8 @@ pub struct TypeArray {
     whitespace: Vec<Whitespace>,
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct TypeHigherRankedTraitBounds {
     extent: Extent,
     lifetimes: Vec<Lifetime>,
@@ -659,40 +1227,54 @@ pub struct TypeHigherRankedTraitBounds {
     whitespace: Vec<Whitespace>,
 }
 
-#[derive(Debug, HasExtent, Visit, Decompose)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Decompose)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum TypeHigherRankedTraitBoundsChild {
     Named(TypeNamed),
     Function(TypeFunction),
     Reference(TypeReference),
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct TypeImplTrait {
     extent: Extent,
     name: TypeNamed,
     whitespace: Vec<Whitespace>,
 }
 
-#[derive(Debug, HasExtent, Visit, Decompose)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TypeDynTrait {
+    extent: Extent,
+    name: TypeNamed,
+    whitespace: Vec<Whitespace>,
+}
+
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Decompose)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum TypeAdditional {
     Named(TypeNamed),
     Lifetime(Lifetime),
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct TypeNamed {
     extent: Extent,
     path: Vec<TypeNamedComponent>,
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct TypeNamedComponent {
     extent: Extent,
     ident: Ident,
     generics: Option<TypeGenerics>,
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct TypeDisambiguation {
     extent: Extent,
     from_type: Box<Type>,
@@ -701,26 +1283,30 @@ pub struct TypeDisambiguation {
     whitespace: Vec<Whitespace>,
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct TypeSlice {
     extent: Extent,
     typ: Box<Type>,
     whitespace: Vec<Whitespace>,
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct TypeTuple {
     extent: Extent,
     types: Vec<Type>,
 }
 
-#[derive(Debug, HasExtent, Visit, Decompose)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Decompose)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum TypeGenerics {
     Function(TypeGenericsFunction),
     Angle(TypeGenericsAngle),
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct TypeGenericsFunction {
     extent: Extent,
     types: Vec<Type>,
@@ -728,21 +1314,24 @@ pub struct TypeGenericsFunction {
     whitespace: Vec<Whitespace>,
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct TypeGenericsAngle {
     extent: Extent,
     members: Vec<TypeGenericsAngleMember>,
     whitespace: Vec<Whitespace>,
 }
 
-#[derive(Debug, HasExtent, Visit, Decompose)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Decompose)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum TypeGenericsAngleMember {
     Lifetime(Lifetime),
     Type(Type),
     AssociatedType(AssociatedType)
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct AssociatedType {
     extent: Extent,
     name: Ident,
@@ -750,7 +1339,8 @@ pub struct AssociatedType {
     whitespace: Vec<Whitespace>,
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct TypeFunction {
     extent: Extent,
     qualifiers: FunctionQualifiers,
@@ -759,46 +1349,53 @@ pub struct TypeFunction {
     whitespace: Vec<Whitespace>,
 }
 
-#[derive(Debug, HasExtent, Visit, Decompose)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Decompose)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum TypeFunctionArgument {
     Named(TypeFunctionArgumentNamed),
     Variadic(Extent),
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct TypeFunctionArgumentNamed {
     extent: Extent,
     name: Option<Ident>,
     typ: Type
 }
 
-#[derive(Debug, Copy, Clone, HasExtent, Visit)]
+#[derive(Debug, Copy, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Ident {
     pub extent: Extent,
 }
 
 // TODO: Can we reuse the path from the `use` statement?
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Path {
     extent: Extent,
     components: Vec<Ident>,
 }
 
 // TODO: Can we reuse the path from the `use` statement?
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct PathedIdent {
     extent: Extent,
     components: Vec<PathComponent>,
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct PathComponent {
     extent: Extent,
     ident: Ident,
     turbofish: Option<Turbofish>,
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Turbofish {
     extent: Extent,
     lifetimes: Vec<Lifetime>,
@@ -813,7 +1410,8 @@ impl From<Ident> for PathedIdent {
     }
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Const {
     extent: Extent,
     visibility: Option<Visibility>,
@@ -823,7 +1421,8 @@ pub struct Const {
     whitespace: Vec<Whitespace>,
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Static {
     extent: Extent,
     visibility: Option<Visibility>,
@@ -834,7 +1433,8 @@ pub struct Static {
     whitespace: Vec<Whitespace>,
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Struct {
     pub extent: Extent,
     visibility: Option<Visibility>,
@@ -845,21 +1445,24 @@ pub struct Struct {
     whitespace: Vec<Whitespace>,
 }
 
-#[derive(Debug, HasExtent, Visit, Decompose)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Decompose)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum StructDefinitionBody {
     Brace(StructDefinitionBodyBrace),
     Tuple(StructDefinitionBodyTuple),
     Empty(Extent),
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct StructDefinitionBodyBrace {
     pub extent: Extent,
     fields: Vec<Attributed<StructDefinitionFieldNamed>>,
     whitespace: Vec<Whitespace>,
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct StructDefinitionFieldNamed {
     extent: Extent,
     visibility: Option<Visibility>,
@@ -868,21 +1471,24 @@ pub struct StructDefinitionFieldNamed {
     whitespace: Vec<Whitespace>,
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct StructDefinitionBodyTuple {
     pub extent: Extent,
     fields: Vec<Attributed<StructDefinitionFieldUnnamed>>,
     whitespace: Vec<Whitespace>,
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct StructDefinitionFieldUnnamed {
     extent: Extent,
     visibility: Option<Visibility>,
     typ: Type,
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Union {
     pub extent: Extent,
     visibility: Option<Visibility>,
@@ -893,7 +1499,8 @@ pub struct Union {
     whitespace: Vec<Whitespace>,
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Enum {
     pub extent: Extent,
     visibility: Option<Visibility>,
@@ -904,7 +1511,8 @@ pub struct Enum {
     whitespace: Vec<Whitespace>,
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct EnumVariant {
     extent: Extent,
     name: Ident,
@@ -912,26 +1520,37 @@ pub struct EnumVariant {
     whitespace: Vec<Whitespace>,
 }
 
-#[derive(Debug, Visit, Decompose)] // HasExtent?
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Decompose)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum EnumVariantBody {
-    Tuple(Vec<Attributed<StructDefinitionFieldUnnamed>>),
+    Tuple(StructDefinitionBodyTuple),
     Struct(StructDefinitionBodyBrace),
-    Unit(Option<Attributed<Expression>>),
+    Unit(EnumVariantBodyUnit),
+}
+
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct EnumVariantBodyUnit {
+    extent: Extent,
+    discriminant: Option<Attributed<Expression>>,
 }
 
-#[derive(Debug, Visit, Decompose)] // HasExtent?
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Decompose)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Argument {
     SelfArgument(SelfArgument),
     Named(NamedArgument),
 }
 
-#[derive(Debug, HasExtent, Visit, Decompose)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Decompose)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum SelfArgument {
     Longhand(SelfArgumentLonghand),
     Shorthand(SelfArgumentShorthand),
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct SelfArgumentLonghand {
     extent: Extent,
     is_mut: Option<Extent>,
@@ -940,7 +1559,8 @@ pub struct SelfArgumentLonghand {
     whitespace: Vec<Whitespace>,
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct SelfArgumentShorthand {
     extent: Extent,
     qualifier: Option<SelfArgumentShorthandQualifier>,
@@ -948,92 +1568,108 @@ pub struct SelfArgumentShorthand {
     whitespace: Vec<Whitespace>,
 }
 
-#[derive(Debug, HasExtent, Visit, Decompose)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Decompose)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum SelfArgumentShorthandQualifier {
     Reference(TypeReferenceKind),
     Mut(Extent),
 }
 
-#[derive(Debug, Visit)] // HasExtent?
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct NamedArgument {
+    extent: Extent,
     name: Pattern,
     typ: Type,
     whitespace: Vec<Whitespace>,
 }
 
-#[derive(Debug, Visit, Decompose)] // HasExtent?
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Decompose)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum TraitImplArgument {
     SelfArgument(SelfArgument),
     Named(TraitImplArgumentNamed),
 }
 
-#[derive(Debug, Visit)] // HasExtent?
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct TraitImplArgumentNamed {
+    extent: Extent,
     name: Option<Pattern>,
     typ: Type,
     whitespace: Vec<Whitespace>,
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Where {
     extent: Extent,
     higher_ranked_trait_bounds: Vec<Lifetime>,
     kind: WhereKind,
 }
 
-#[derive(Debug, HasExtent, Visit, Decompose)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Decompose)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum WhereKind {
     Lifetime(WhereLifetime),
     Type(WhereType),
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct WhereLifetime {
     pub extent: Extent,
     name: Lifetime,
     bounds: Vec<Lifetime>,
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct WhereType {
     pub extent: Extent,
     name: Type,
     bounds: TraitBounds,
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct TraitBounds {
     pub extent: Extent,
     types: Vec<TraitBound>,
 }
 
-#[derive(Debug, HasExtent, Visit, Decompose)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Decompose)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum TraitBound {
     Lifetime(TraitBoundLifetime),
     Normal(TraitBoundNormal),
     Relaxed(TraitBoundRelaxed),
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct TraitBoundLifetime {
     pub extent: Extent,
     lifetime: Lifetime,
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct TraitBoundNormal {
     pub extent: Extent,
     typ: TraitBoundType,
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct TraitBoundRelaxed {
     pub extent: Extent,
     typ: TraitBoundType,
     whitespace: Vec<Whitespace>,
 }
 
-#[derive(Debug, HasExtent, Visit, Decompose)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Decompose)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum TraitBoundType {
     Named(TypeNamed),
     // TODO: HRTB Trait bounds don't really allow references or fn types, just named
@@ -1041,7 +1677,8 @@ pub enum TraitBoundType {
     HigherRankedTraitBounds(TypeHigherRankedTraitBounds),
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Block {
     extent: Extent,
     statements: Vec<Statement>,
@@ -1049,27 +1686,41 @@ pub struct Block {
     whitespace: Vec<Whitespace>,
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct UnsafeBlock {
     extent: Extent,
     body: Box<Block>,
     whitespace: Vec<Whitespace>,
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AsyncBlock {
+    extent: Extent,
+    #[visit(ignore)]
+    is_move: bool,
+    body: Box<Block>,
+    whitespace: Vec<Whitespace>,
+}
+
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Parenthetical {
     extent: Extent,
     expression: Box<Attributed<Expression>>,
 }
 
-#[derive(Debug, HasExtent, Visit, Decompose)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Decompose)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Statement {
     Expression(Attributed<Expression>),
     Item(Attributed<Item>),
     Empty(Extent),
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Attributed<T> {
     extent: Extent,
     attributes: Vec<Attribute>,
@@ -1088,30 +1739,92 @@ impl<T> std::ops::Deref for Attributed<T> {
 }
 
 macro_rules! visit_attributed {
-    ($typ:ty, $visit:ident, $exit:ident) => {
+    ($typ:ty, $visit:ident, $exit:ident, $visit_mut:ident, $exit_mut:ident) => {
         impl Visit for Attributed<$typ> {
-            fn visit<V>(&self, v: &mut V)
+            fn visit<V>(&self, v: &mut V) -> Control
                 where V: Visitor
             {
-                v.$visit(self);
-                self.attributes.visit(v);
-                self.value.visit(v);
+                match v.$visit(self) {
+                    Control::Continue => {
+                        if self.attributes.visit(v) == Control::Stop ||
+                            self.value.visit(v) == Control::Stop
+                        {
+                            return Control::Stop;
+                        }
+                    }
+                    Control::Break => {}
+                    Control::Stop => return Control::Stop,
+                }
                 v.$exit(self);
+                Control::Continue
+            }
+        }
+
+        impl VisitMut for Attributed<$typ> {
+            fn visit_mut<V>(&mut self, v: &mut V) -> Control
+                where V: VisitorMut
+            {
+                match v.$visit_mut(self) {
+                    Control::Continue => {
+                        if self.attributes.visit_mut(v) == Control::Stop ||
+                            self.value.visit_mut(v) == Control::Stop
+                        {
+                            return Control::Stop;
+                        }
+                    }
+                    Control::Break => {}
+                    Control::Stop => return Control::Stop,
+                }
+                v.$exit_mut(self);
+                Control::Continue
+            }
+        }
+    };
+}
+
+macro_rules! fold_attributed {
+    ($typ:ty, $fold:ident) => {
+        impl Foldable for Attributed<$typ> {
+            fn fold_with<F>(self, f: &mut F) -> Self
+                where F: Fold
+            {
+                f.$fold(self)
+            }
+
+            fn fold_children<F>(self, f: &mut F) -> Self
+                where F: Fold
+            {
+                Attributed {
+                    extent: self.extent,
+                    attributes: self.attributes.fold_with(f),
+                    value: self.value.fold_with(f),
+                }
             }
         }
     };
 }
 
-visit_attributed!(EnumVariant, visit_attributed_enum_variant, exit_attributed_enum_variant);
-visit_attributed!(Expression, visit_attributed_expression, exit_attributed_expression);
-visit_attributed!(ExternBlockMember, visit_attributed_extern_block_member, exit_attributed_extern_block_member);
-visit_attributed!(GenericDeclarationLifetime, visit_attributed_generic_declaration_lifetime, exit_attributed_generic_declaration_lifetime);
-visit_attributed!(GenericDeclarationType, visit_attributed_generic_declaration_type, exit_attributed_generic_declaration_type);
-visit_attributed!(ImplMember, visit_attributed_impl_member, exit_attributed_impl_member);
-visit_attributed!(Item, visit_attributed_item, exit_attributed_item);
-visit_attributed!(StructDefinitionFieldNamed, visit_attributed_struct_definition_field_named, exit_attributed_struct_definition_field_named);
-visit_attributed!(StructDefinitionFieldUnnamed, visit_attributed_struct_definition_field_unnamed, exit_attributed_struct_definition_field_unnamed);
-visit_attributed!(TraitMember, visit_attributed_trait_member, exit_attributed_trait_member);
+visit_attributed!(EnumVariant, visit_attributed_enum_variant, exit_attributed_enum_variant, visit_attributed_enum_variant_mut, exit_attributed_enum_variant_mut);
+visit_attributed!(Expression, visit_attributed_expression, exit_attributed_expression, visit_attributed_expression_mut, exit_attributed_expression_mut);
+visit_attributed!(ExternBlockMember, visit_attributed_extern_block_member, exit_attributed_extern_block_member, visit_attributed_extern_block_member_mut, exit_attributed_extern_block_member_mut);
+visit_attributed!(GenericDeclarationLifetime, visit_attributed_generic_declaration_lifetime, exit_attributed_generic_declaration_lifetime, visit_attributed_generic_declaration_lifetime_mut, exit_attributed_generic_declaration_lifetime_mut);
+visit_attributed!(GenericDeclarationType, visit_attributed_generic_declaration_type, exit_attributed_generic_declaration_type, visit_attributed_generic_declaration_type_mut, exit_attributed_generic_declaration_type_mut);
+visit_attributed!(ImplMember, visit_attributed_impl_member, exit_attributed_impl_member, visit_attributed_impl_member_mut, exit_attributed_impl_member_mut);
+visit_attributed!(Item, visit_attributed_item, exit_attributed_item, visit_attributed_item_mut, exit_attributed_item_mut);
+visit_attributed!(StructDefinitionFieldNamed, visit_attributed_struct_definition_field_named, exit_attributed_struct_definition_field_named, visit_attributed_struct_definition_field_named_mut, exit_attributed_struct_definition_field_named_mut);
+visit_attributed!(StructDefinitionFieldUnnamed, visit_attributed_struct_definition_field_unnamed, exit_attributed_struct_definition_field_unnamed, visit_attributed_struct_definition_field_unnamed_mut, exit_attributed_struct_definition_field_unnamed_mut);
+visit_attributed!(TraitMember, visit_attributed_trait_member, exit_attributed_trait_member, visit_attributed_trait_member_mut, exit_attributed_trait_member_mut);
+
+fold_attributed!(EnumVariant, fold_attributed_enum_variant);
+fold_attributed!(Expression, fold_attributed_expression);
+fold_attributed!(ExternBlockMember, fold_attributed_extern_block_member);
+fold_attributed!(GenericDeclarationLifetime, fold_attributed_generic_declaration_lifetime);
+fold_attributed!(GenericDeclarationType, fold_attributed_generic_declaration_type);
+fold_attributed!(ImplMember, fold_attributed_impl_member);
+fold_attributed!(Item, fold_attributed_item);
+fold_attributed!(StructDefinitionFieldNamed, fold_attributed_struct_definition_field_named);
+fold_attributed!(StructDefinitionFieldUnnamed, fold_attributed_struct_definition_field_unnamed);
+fold_attributed!(TraitMember, fold_attributed_trait_member);
 
 // Assumes that there are no attributes
 impl From<Expression> for Attributed<Expression> {
@@ -1124,11 +1837,14 @@ impl From<Expression> for Attributed<Expression> {
     }
 }
 
-#[derive(Debug, HasExtent, Visit, Decompose)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Decompose)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Expression {
     Array(Array),
     AsType(AsType),
     Ascription(Ascription),
+    AsyncBlock(AsyncBlock),
+    Await(Await),
     Binary(Binary),
     Block(Box<Block>),
     Box(ExpressionBox),
@@ -1169,6 +1885,7 @@ pub enum Expression {
 impl Expression {
     fn may_terminate_statement(&self) -> bool {
         match *self {
+            Expression::AsyncBlock(_)  |
             Expression::Block(_)       |
             Expression::ForLoop(_)     |
             Expression::If(_)          |
@@ -1184,22 +1901,28 @@ impl Expression {
     }
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct MacroCall {
     extent: Extent,
     name: Ident,
     arg: Option<Ident>,
     args: MacroCallArgs,
+    // Only when requested by `ParseOptions::with_macro_args` and the
+    // arguments are expressions separated by commas
+    expressions: Option<Vec<Attributed<Expression>>>,
 }
 
-#[derive(Debug, HasExtent, Visit, Decompose)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Decompose)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum MacroCallArgs {
     Paren(Extent),
     Curly(Extent),
     Square(Extent),
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Let {
     extent: Extent,
     pattern: Pattern,
@@ -1208,32 +1931,45 @@ pub struct Let {
     whitespace: Vec<Whitespace>,
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Tuple {
     extent: Extent,
     members: Vec<Attributed<Expression>>,
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct TryOperator {
+    extent: Extent,
+    operator: Extent,
+    target: Box<Attributed<Expression>>,
+}
+
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Await {
     extent: Extent,
     target: Box<Attributed<Expression>>,
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct FieldAccess {
     extent: Extent,
     target: Box<Attributed<Expression>>,
     field: FieldName,
 }
 
-#[derive(Debug, HasExtent, Decompose)]
+#[derive(Debug, Clone, HasExtent, Decompose)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum FieldName {
     Path(PathComponent),
     Number(Extent),
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Number {
     extent: Extent,
     is_negative: Option<Extent>,
@@ -1241,7 +1977,8 @@ pub struct Number {
     whitespace: Vec<Whitespace>,
 }
 
-#[derive(Debug, HasExtent, Visit, Decompose)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Decompose)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum NumberValue {
     Binary(NumberBinary),
     Decimal(NumberDecimal),
@@ -1249,7 +1986,8 @@ pub enum NumberValue {
     Octal(NumberOctal),
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct NumberBinary {
     extent: Extent,
     decimal: Extent,
@@ -1258,7 +1996,8 @@ pub struct NumberBinary {
     suffix: Option<Extent>,
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct NumberDecimal {
     extent: Extent,
     decimal: Extent,
@@ -1267,7 +2006,8 @@ pub struct NumberDecimal {
     suffix: Option<Extent>,
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct NumberHexadecimal {
     extent: Extent,
     decimal: Extent,
@@ -1276,7 +2016,8 @@ pub struct NumberHexadecimal {
     suffix: Option<Extent>,
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct NumberOctal {
     extent: Extent,
     decimal: Extent,
@@ -1285,14 +2026,16 @@ pub struct NumberOctal {
     suffix: Option<Extent>,
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Value {
     extent: Extent,
     name: PathedIdent,
     literal: Option<StructLiteral>,
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct StructLiteral {
     extent: Extent,
     fields: Vec<StructLiteralField>,
@@ -1300,21 +2043,25 @@ pub struct StructLiteral {
     whitespace: Vec<Whitespace>,
 }
 
-#[derive(Debug, Visit)] // HasExtent?
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct StructLiteralField {
+    extent: Extent,
     name: Ident,
     value: Attributed<Expression>,
     whitespace: Vec<Whitespace>,
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Call {
     extent: Extent,
     target: Box<Attributed<Expression>>,
     args: Vec<Attributed<Expression>>,
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct ForLoop {
     extent: Extent,
     label: Option<Lifetime>,
@@ -1324,7 +2071,8 @@ pub struct ForLoop {
     whitespace: Vec<Whitespace>,
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Loop {
     extent: Extent,
     label: Option<Lifetime>,
@@ -1332,7 +2080,8 @@ pub struct Loop {
     whitespace: Vec<Whitespace>,
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct IfLet {
     extent: Extent,
     pattern: Pattern,
@@ -1341,7 +2090,8 @@ pub struct IfLet {
     whitespace: Vec<Whitespace>,
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct While {
     extent: Extent,
     label: Option<Lifetime>,
@@ -1350,7 +2100,8 @@ pub struct While {
     whitespace: Vec<Whitespace>,
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct WhileLet {
     extent: Extent,
     label: Option<Lifetime>,
@@ -1361,7 +2112,8 @@ pub struct WhileLet {
 }
 
 // TODO: Should this be the same as dereference? What about reference?
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Unary {
     extent: Extent,
     op: UnaryOp,
@@ -1369,55 +2121,59 @@ pub struct Unary {
     whitespace: Vec<Whitespace>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Decompose)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum UnaryOp {
-    Negate,
-    Not,
+    Negate(Extent),
+    Not(Extent),
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Binary {
     extent: Extent,
-    op: BinaryOp,
     lhs: Box<Attributed<Expression>>,
+    op: BinaryOp,
     rhs: Box<Attributed<Expression>>,
     whitespace: Vec<Whitespace>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Decompose)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum BinaryOp {
-    Add,
-    AddAssign,
-    Assign,
-    BitwiseAnd,
-    BitwiseAndAssign,
-    BitwiseOr,
-    BitwiseOrAssign,
-    BitwiseXor,
-    BitwiseXorAssign,
-    BooleanAnd,
-    BooleanOr,
-    Div,
-    DivAssign,
-    Equal,
-    GreaterThan,
-    GreaterThanOrEqual,
-    LessThan,
-    LessThanOrEqual,
-    Mod,
-    ModAssign,
-    Mul,
-    MulAssign,
-    NotEqual,
-    ShiftLeft,
-    ShiftLeftAssign,
-    ShiftRight,
-    ShiftRightAssign,
-    Sub,
-    SubAssign,
-}
-
-#[derive(Debug, HasExtent, Visit)]
+    Add(Extent),
+    AddAssign(Extent),
+    Assign(Extent),
+    BitwiseAnd(Extent),
+    BitwiseAndAssign(Extent),
+    BitwiseOr(Extent),
+    BitwiseOrAssign(Extent),
+    BitwiseXor(Extent),
+    BitwiseXorAssign(Extent),
+    BooleanAnd(Extent),
+    BooleanOr(Extent),
+    Div(Extent),
+    DivAssign(Extent),
+    Equal(Extent),
+    GreaterThan(Extent),
+    GreaterThanOrEqual(Extent),
+    LessThan(Extent),
+    LessThanOrEqual(Extent),
+    Mod(Extent),
+    ModAssign(Extent),
+    Mul(Extent),
+    MulAssign(Extent),
+    NotEqual(Extent),
+    ShiftLeft(Extent),
+    ShiftLeftAssign(Extent),
+    ShiftRight(Extent),
+    ShiftRightAssign(Extent),
+    Sub(Extent),
+    SubAssign(Extent),
+}
+
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct If {
     extent: Extent,
     condition: Box<Attributed<Expression>>,
@@ -1427,7 +2183,8 @@ pub struct If {
     whitespace: Vec<Whitespace>,
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Match {
     extent: Extent,
     head: Box<Attributed<Expression>>,
@@ -1435,7 +2192,8 @@ pub struct Match {
     whitespace: Vec<Whitespace>,
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct MatchArm {
     extent: Extent,
     attributes: Vec<Attribute>,
@@ -1445,39 +2203,45 @@ pub struct MatchArm {
     whitespace: Vec<Whitespace>,
 }
 
-#[derive(Debug, HasExtent, Visit, Decompose)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Decompose)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum MatchHand {
     Brace(Attributed<Expression>),
     Expression(Attributed<Expression>),
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Range {
     extent: Extent,
     lhs: Option<Box<Attributed<Expression>>>,
     rhs: Option<Box<Attributed<Expression>>>,
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct RangeInclusive {
     extent: Extent,
     lhs: Option<Box<Attributed<Expression>>>,
     rhs: Option<Box<Attributed<Expression>>>,
 }
 
-#[derive(Debug, HasExtent, Visit, Decompose)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Decompose)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Array {
     Explicit(ArrayExplicit),
     Repeated(ArrayRepeated),
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct ArrayExplicit {
     extent: Extent,
     values: Vec<Attributed<Expression>>,
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct ArrayRepeated {
     extent: Extent,
     value: Box<Attributed<Expression>>,
@@ -1486,58 +2250,68 @@ pub struct ArrayRepeated {
 }
 
 // TODO: Rename this visitor function?
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct ExpressionBox {
     extent: Extent,
     target: Box<Attributed<Expression>>,
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct AsType {
     extent: Extent,
+    operator: Extent,
     target: Box<Attributed<Expression>>,
     typ: Type,
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Ascription {
     extent: Extent,
     target: Box<Attributed<Expression>>,
     typ: Type,
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Character {
     extent: Extent,
     value: Extent,
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct String {
     extent: Extent,
     value: Extent,
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Byte {
     extent: Extent,
     value: Character,
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct ByteString {
     extent: Extent,
     value: String,
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Slice {
     extent: Extent,
     target: Box<Attributed<Expression>>,
     index: Box<Attributed<Expression>>,
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Closure {
     extent: Extent,
     #[visit(ignore)]
@@ -1548,28 +2322,35 @@ pub struct Closure {
     whitespace: Vec<Whitespace>,
 }
 
-#[derive(Debug, Visit)] // HasExtent?
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct ClosureArg {
+    extent: Extent,
     name: Pattern,
     typ: Option<Type>,
     whitespace: Vec<Whitespace>,
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Reference {
     extent: Extent,
+    operator: Extent,
     is_mutable: Option<Extent>,
     target: Box<Attributed<Expression>>,
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Dereference {
     extent: Extent,
+    operator: Extent,
     target: Box<Attributed<Expression>>,
     whitespace: Vec<Whitespace>,
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Disambiguation {
     extent: Extent,
     from_type: Type,
@@ -1578,21 +2359,24 @@ pub struct Disambiguation {
     whitespace: Vec<Whitespace>,
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Return {
     extent: Extent,
     value: Option<Box<Attributed<Expression>>>,
     whitespace: Vec<Whitespace>,
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Continue {
     extent: Extent,
     label: Option<Lifetime>,
     whitespace: Vec<Whitespace>,
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Break {
     extent: Extent,
     label: Option<Lifetime>,
@@ -1600,14 +2384,16 @@ pub struct Break {
     whitespace: Vec<Whitespace>,
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Pattern {
     extent: Extent,
     name: Option<PatternName>,
     kind: PatternKind,
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct PatternName {
     extent: Extent,
     is_ref: Option<Extent>,
@@ -1616,7 +2402,8 @@ pub struct PatternName {
     whitespace: Vec<Whitespace>,
 }
 
-#[derive(Debug, HasExtent, Visit, Decompose)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Decompose)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum PatternKind {
     Box(PatternBox),
     Byte(PatternByte),
@@ -1634,7 +2421,8 @@ pub enum PatternKind {
     Tuple(PatternTuple),
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct PatternIdent {
     extent: Extent,
     is_ref: Option<Extent>,
@@ -1643,7 +2431,8 @@ pub struct PatternIdent {
     tuple: Option<PatternTuple>,
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct PatternStruct {
     extent: Extent,
     name: PathedIdent,
@@ -1653,13 +2442,15 @@ pub struct PatternStruct {
     whitespace: Vec<Whitespace>,
 }
 
-#[derive(Debug, Visit, Decompose)] // HasExtent?
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Decompose)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum PatternStructField {
     Long(PatternStructFieldLong),
     Short(PatternStructFieldShort),
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct PatternStructFieldLong {
     extent: Extent,
     name: Ident,
@@ -1667,72 +2458,85 @@ pub struct PatternStructFieldLong {
     whitespace: Vec<Whitespace>,
 }
 
-#[derive(Debug, Visit)] // HasExtent?
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct PatternStructFieldShort {
-    ident: PatternIdent
+    extent: Extent,
+    ident: PatternIdent,
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct PatternTuple {
     extent: Extent,
     members: Vec<PatternBundleMember>,
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct PatternSlice {
     extent: Extent,
     members: Vec<PatternBundleMember>,
 }
 
-#[derive(Debug, HasExtent, Visit, Decompose)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Decompose)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum PatternBundleMember {
     Pattern(Pattern),
     Wildcard(Extent),
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct PatternWildcard {
     extent: Extent,
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct PatternByte {
     extent: Extent,
     value: Byte,
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct PatternCharacter {
     extent: Extent,
     value: Character,
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct PatternByteString {
     extent: Extent,
     value: ByteString,
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct PatternString {
     extent: Extent,
     value: String,
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct PatternNumber {
     extent: Extent,
     is_negative: Option<Extent>,
     value: Number,
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct PatternMacroCall {
     extent: Extent,
     value: MacroCall,
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct PatternRangeExclusive {
     extent: Extent,
     start: PatternRangeComponent,
@@ -1740,7 +2544,8 @@ pub struct PatternRangeExclusive {
     whitespace: Vec<Whitespace>,
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct PatternRangeInclusive {
     extent: Extent,
     start: PatternRangeComponent,
@@ -1748,7 +2553,8 @@ pub struct PatternRangeInclusive {
     whitespace: Vec<Whitespace>,
 }
 
-#[derive(Debug, Decompose)]
+#[derive(Debug, Clone, Decompose)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum PatternRangeComponent {
     Ident(PathedIdent),
     Byte(Byte),
@@ -1756,7 +2562,8 @@ pub enum PatternRangeComponent {
     Number(PatternNumber),
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct PatternReference {
     extent: Extent,
     is_mut: Option<Extent>,
@@ -1764,14 +2571,16 @@ pub struct PatternReference {
     whitespace: Vec<Whitespace>,
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct PatternBox {
     extent: Extent,
     pattern: Box<Pattern>,
     whitespace: Vec<Whitespace>,
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Trait {
     extent: Extent,
     visibility: Option<Visibility>,
@@ -1784,7 +2593,8 @@ pub struct Trait {
     whitespace: Vec<Whitespace>,
 }
 
-#[derive(Debug, HasExtent, Visit, Decompose)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Decompose)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum TraitMember {
     Const(TraitMemberConst),
     Function(TraitMemberFunction),
@@ -1792,14 +2602,16 @@ pub enum TraitMember {
     MacroCall(MacroCall),
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct TraitMemberFunction {
     extent: Extent,
     header: TraitImplFunctionHeader,
     body: Option<Block>,
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct TraitMemberType {
     extent: Extent,
     name: Ident,
@@ -1808,7 +2620,8 @@ pub struct TraitMemberType {
     whitespace: Vec<Whitespace>,
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct TraitMemberConst {
     extent: Extent,
     name: Ident,
@@ -1817,7 +2630,8 @@ pub struct TraitMemberConst {
     whitespace: Vec<Whitespace>,
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Impl {
     extent: Extent,
     is_unsafe: Option<Extent>,
@@ -1828,13 +2642,15 @@ pub struct Impl {
     whitespace: Vec<Whitespace>,
 }
 
-#[derive(Debug, HasExtent, Visit, Decompose)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Decompose)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum ImplKind {
     Trait(ImplOfTrait),
     Inherent(ImplOfInherent),
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct ImplOfTrait {
     extent: Extent,
     is_negative: Option<Extent>,
@@ -1843,20 +2659,23 @@ pub struct ImplOfTrait {
     whitespace: Vec<Whitespace>,
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct ImplOfInherent {
     extent: Extent,
     type_name: Type,
     whitespace: Vec<Whitespace>,
 }
 
-#[derive(Debug, HasExtent, Visit, Decompose)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Decompose)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum ImplOfTraitType {
     Type(Type),
     Wildcard(Extent),
 }
 
-#[derive(Debug, HasExtent, Visit, Decompose)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Decompose)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum ImplMember {
     Const(ImplConst),
     Function(ImplFunction),
@@ -1864,14 +2683,16 @@ pub enum ImplMember {
     MacroCall(MacroCall),
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct ImplFunction {
     extent: Extent,
     header: FunctionHeader,
     body: Block,
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct ImplType {
     extent: Extent,
     name: Ident,
@@ -1879,7 +2700,8 @@ pub struct ImplType {
     whitespace: Vec<Whitespace>,
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct ImplConst {
     extent: Extent,
     visibility: Option<Visibility>,
@@ -1889,7 +2711,8 @@ pub struct ImplConst {
     whitespace: Vec<Whitespace>,
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Crate {
     extent: Extent,
     visibility: Option<Visibility>,
@@ -1898,7 +2721,8 @@ pub struct Crate {
     whitespace: Vec<Whitespace>,
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct ExternBlock {
     extent: Extent,
     abi: Option<String>,
@@ -1906,13 +2730,15 @@ pub struct ExternBlock {
     whitespace: Vec<Whitespace>,
 }
 
-#[derive(Debug, HasExtent, Visit, Decompose)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Decompose)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum ExternBlockMember {
     Function(ExternBlockMemberFunction),
     Static(ExternBlockMemberStatic),
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct ExternBlockMemberStatic {
     extent: Extent,
     visibility: Option<Visibility>,
@@ -1922,7 +2748,8 @@ pub struct ExternBlockMemberStatic {
     whitespace: Vec<Whitespace>,
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct ExternBlockMemberFunction {
     extent: Extent,
     visibility: Option<Visibility>,
@@ -1934,13 +2761,15 @@ pub struct ExternBlockMemberFunction {
     whitespace: Vec<Whitespace>,
 }
 
-#[derive(Debug, HasExtent, Visit, Decompose)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Decompose)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum ExternBlockMemberFunctionArgument {
     Named(ExternBlockMemberFunctionArgumentNamed),
     Variadic(ExternBlockMemberFunctionArgumentVariadic),
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct ExternBlockMemberFunctionArgumentNamed {
     extent: Extent,
     name: Pattern,
@@ -1948,12 +2777,14 @@ pub struct ExternBlockMemberFunctionArgumentNamed {
     whitespace: Vec<Whitespace>,
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct ExternBlockMemberFunctionArgumentVariadic {
     extent: Extent,
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct TypeAlias {
     extent: Extent,
     visibility: Option<Visibility>,
@@ -1964,7 +2795,8 @@ pub struct TypeAlias {
     whitespace: Vec<Whitespace>,
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Module {
     extent: Extent,
     visibility: Option<Visibility>,
@@ -1973,7 +2805,8 @@ pub struct Module {
     whitespace: Vec<Whitespace>,
 }
 
-#[derive(Debug, HasExtent, Visit)]
+#[derive(Debug, Clone, HasExtent, Visit, Fold, Accessors)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Visibility {
     extent: Extent,
     #[visit(ignore)]
@@ -1981,7 +2814,8 @@ pub struct Visibility {
     whitespace: Vec<Whitespace>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum VisibilityQualifier {
     Crate,
     SelfIdent,
@@ -1991,94 +2825,258 @@ pub enum VisibilityQualifier {
 // --------------------------------------------------
 
 pub trait Visit {
-    fn visit<V>(&self, &mut V)
+    /// Returns `Control::Stop` if the visitor halted the traversal.
+    fn visit<V>(&self, &mut V) -> Control
         where V: Visitor;
 }
 
+pub trait VisitMut {
+    /// Returns `Control::Stop` if the visitor halted the traversal.
+    fn visit_mut<V>(&mut self, &mut V) -> Control
+        where V: VisitorMut;
+}
+
 impl<T> Visit for Box<T>
     where T: Visit
 {
-    fn visit<V>(&self, v: &mut V)
+    fn visit<V>(&self, v: &mut V) -> Control
         where V: Visitor
     {
         (**self).visit(v)
     }
 }
 
+impl<T> VisitMut for Box<T>
+    where T: VisitMut
+{
+    fn visit_mut<V>(&mut self, v: &mut V) -> Control
+        where V: VisitorMut
+    {
+        (**self).visit_mut(v)
+    }
+}
+
 impl<T> Visit for Option<T>
     where T: Visit
 {
-    fn visit<V>(&self, v: &mut V)
+    fn visit<V>(&self, v: &mut V) -> Control
         where V: Visitor
     {
         for i in self {
-            i.visit(v)
+            if i.visit(v) == Control::Stop {
+                return Control::Stop;
+            }
+        }
+        Control::Continue
+    }
+}
+
+impl<T> VisitMut for Option<T>
+    where T: VisitMut
+{
+    fn visit_mut<V>(&mut self, v: &mut V) -> Control
+        where V: VisitorMut
+    {
+        for i in self {
+            if i.visit_mut(v) == Control::Stop {
+                return Control::Stop;
+            }
         }
+        Control::Continue
     }
 }
 
 impl<T> Visit for Vec<T>
     where T: Visit
 {
-    fn visit<V>(&self, v: &mut V)
+    fn visit<V>(&self, v: &mut V) -> Control
         where V: Visitor
     {
         for i in self {
-            i.visit(v)
+            if i.visit(v) == Control::Stop {
+                return Control::Stop;
+            }
         }
+        Control::Continue
     }
 }
 
-// Cheap hacks to avoid having to annotate every terminal `Extent` and
+impl<T> VisitMut for Vec<T>
+    where T: VisitMut
+{
+    fn visit_mut<V>(&mut self, v: &mut V) -> Control
+        where V: VisitorMut
+    {
+        for i in self {
+            if i.visit_mut(v) == Control::Stop {
+                return Control::Stop;
+            }
+        }
+        Control::Continue
+    }
+}
+
+// Cheap hacks to avoid having to annotate every terminal `Extent` and
 // enum; just visit them and don't do anything.
 
 // An extent without any context is pretty useless.
 impl Visit for Extent {
-    fn visit<V>(&self, _v: &mut V)
-        where V: Visitor
-    {}
-}
-
-// Can't imagine we'd ever want to count the number of additions;
-// without the lhs/rhs there's not much benefit.
-impl Visit for UnaryOp {
-    fn visit<V>(&self, _v: &mut V)
+    fn visit<V>(&self, _v: &mut V) -> Control
         where V: Visitor
-    {}
+    {
+        Control::Continue
+    }
 }
-impl Visit for BinaryOp {
-    fn visit<V>(&self, _v: &mut V)
-        where V: Visitor
-    {}
+impl VisitMut for Extent {
+    fn visit_mut<V>(&mut self, _v: &mut V) -> Control
+        where V: VisitorMut
+    {
+        Control::Continue
+    }
 }
 
+
 // We *might* want to visit this, to enable checking for "large" tuple
 // indexes or poor variable names?
 impl Visit for FieldName {
-    fn visit<V>(&self, _v: &mut V)
+    fn visit<V>(&self, _v: &mut V) -> Control
         where V: Visitor
-    {}
+    {
+        Control::Continue
+    }
+}
+impl VisitMut for FieldName {
+    fn visit_mut<V>(&mut self, _v: &mut V) -> Control
+        where V: VisitorMut
+    {
+        Control::Continue
+    }
 }
 
 // We *might* want to continue visiting the children to be able to
 // inspect the character / number?
 impl Visit for PatternRangeComponent {
-    fn visit<V>(&self, _v: &mut V)
+    fn visit<V>(&self, _v: &mut V) -> Control
         where V: Visitor
-    {}
+    {
+        Control::Continue
+    }
+}
+impl VisitMut for PatternRangeComponent {
+    fn visit_mut<V>(&mut self, _v: &mut V) -> Control
+        where V: VisitorMut
+    {
+        Control::Continue
+    }
 }
 
 // Knowing if an unknown pointer is mutable has no benefit.
 impl Visit for TypePointerKind {
-    fn visit<V>(&self, _v: &mut V)
+    fn visit<V>(&self, _v: &mut V) -> Control
         where V: Visitor
-    {}
+    {
+        Control::Continue
+    }
+}
+impl VisitMut for TypePointerKind {
+    fn visit_mut<V>(&mut self, _v: &mut V) -> Control
+        where V: VisitorMut
+    {
+        Control::Continue
+    }
+}
+
+/// A node that can be rebuilt by a `Fold`.
+pub trait Foldable: Sized {
+    /// Hand this node to the matching `Fold` method.
+    fn fold_with<F>(self, &mut F) -> Self
+        where F: Fold;
+
+    /// Fold each child and rebuild this node from the results.
+    fn fold_children<F>(self, &mut F) -> Self
+        where F: Fold;
+}
+
+impl<T> Foldable for Box<T>
+    where T: Foldable
+{
+    fn fold_with<F>(self, f: &mut F) -> Self
+        where F: Fold
+    {
+        Box::new((*self).fold_with(f))
+    }
+
+    fn fold_children<F>(self, f: &mut F) -> Self
+        where F: Fold
+    {
+        self.fold_with(f)
+    }
+}
+
+impl<T> Foldable for Option<T>
+    where T: Foldable
+{
+    fn fold_with<F>(self, f: &mut F) -> Self
+        where F: Fold
+    {
+        self.map(|i| i.fold_with(f))
+    }
+
+    fn fold_children<F>(self, f: &mut F) -> Self
+        where F: Fold
+    {
+        self.fold_with(f)
+    }
+}
+
+impl<T> Foldable for Vec<T>
+    where T: Foldable
+{
+    fn fold_with<F>(self, f: &mut F) -> Self
+        where F: Fold
+    {
+        self.into_iter().map(|i| i.fold_with(f)).collect()
+    }
+
+    fn fold_children<F>(self, f: &mut F) -> Self
+        where F: Fold
+    {
+        self.fold_with(f)
+    }
 }
 
+// The same terminals that aren't visited are returned unchanged.
+macro_rules! fold_terminal {
+    ($($typ:ty),*) => {
+        $(
+            impl Foldable for $typ {
+                fn fold_with<F>(self, _f: &mut F) -> Self
+                    where F: Fold
+                {
+                    self
+                }
+
+                fn fold_children<F>(self, _f: &mut F) -> Self
+                    where F: Fold
+                {
+                    self
+                }
+            }
+        )*
+    };
+}
+
+fold_terminal!(Extent, FieldName, PatternRangeComponent, TypePointerKind);
+
 #[derive(Debug, PartialEq)]
 pub enum Control {
+    /// Visit the children of this node
     Continue,
-    Break
+    /// Skip the children of this node, but continue with its siblings
+    Break,
+    /// Halt the entire traversal; no further `visit_*` or `exit_*`
+    /// methods will be called
+    Stop,
 }
 
 pub trait Visitor {
@@ -2089,6 +3087,7 @@ pub trait Visitor {
     fn visit_as_type(&mut self, &AsType) -> Control { Control::Continue }
     fn visit_ascription(&mut self, &Ascription) -> Control { Control::Continue }
     fn visit_associated_type(&mut self, &AssociatedType) -> Control { Control::Continue }
+    fn visit_async_block(&mut self, &AsyncBlock) -> Control { Control::Continue }
     fn visit_attribute(&mut self, &Attribute) -> Control { Control::Continue }
     fn visit_attribute_containing(&mut self, &AttributeContaining) -> Control { Control::Continue }
     fn visit_attributed_enum_variant(&mut self, &Attributed<EnumVariant>) -> Control { Control::Continue }
@@ -2101,7 +3100,9 @@ pub trait Visitor {
     fn visit_attributed_struct_definition_field_named(&mut self, &Attributed<StructDefinitionFieldNamed>) -> Control { Control::Continue }
     fn visit_attributed_struct_definition_field_unnamed(&mut self, &Attributed<StructDefinitionFieldUnnamed>) -> Control { Control::Continue }
     fn visit_attributed_trait_member(&mut self, &Attributed<TraitMember>) -> Control { Control::Continue }
+    fn visit_await(&mut self, &Await) -> Control { Control::Continue }
     fn visit_binary(&mut self, &Binary) -> Control { Control::Continue }
+    fn visit_binary_op(&mut self, &BinaryOp) -> Control { Control::Continue }
     fn visit_block(&mut self, &Block) -> Control { Control::Continue }
     fn visit_break(&mut self, &Break) -> Control { Control::Continue }
     fn visit_byte(&mut self, &Byte) -> Control { Control::Continue }
@@ -2119,6 +3120,7 @@ pub trait Visitor {
     fn visit_enum(&mut self, &Enum) -> Control { Control::Continue }
     fn visit_enum_variant(&mut self, &EnumVariant) -> Control { Control::Continue }
     fn visit_enum_variant_body(&mut self, &EnumVariantBody) -> Control { Control::Continue }
+    fn visit_enum_variant_body_unit(&mut self, &EnumVariantBodyUnit) -> Control { Control::Continue }
     fn visit_expression(&mut self, &Expression) -> Control { Control::Continue }
     fn visit_expression_box(&mut self, &ExpressionBox) -> Control { Control::Continue }
     fn visit_extern_block(&mut self, &ExternBlock) -> Control { Control::Continue }
@@ -2234,6 +3236,7 @@ pub trait Visitor {
     fn visit_type_alias(&mut self, &TypeAlias) -> Control { Control::Continue }
     fn visit_type_array(&mut self, &TypeArray) -> Control { Control::Continue }
     fn visit_type_disambiguation(&mut self, &TypeDisambiguation) -> Control { Control::Continue }
+    fn visit_type_dyn_trait(&mut self, &TypeDynTrait) -> Control { Control::Continue }
     fn visit_type_function(&mut self, &TypeFunction) -> Control { Control::Continue }
     fn visit_type_function_argument(&mut self, &TypeFunctionArgument) -> Control { Control::Continue }
     fn visit_type_function_argument_named(&mut self, &TypeFunctionArgumentNamed) -> Control { Control::Continue }
@@ -2253,6 +3256,7 @@ pub trait Visitor {
     fn visit_type_slice(&mut self, &TypeSlice) -> Control { Control::Continue }
     fn visit_type_tuple(&mut self, &TypeTuple) -> Control { Control::Continue }
     fn visit_unary(&mut self, &Unary) -> Control { Control::Continue }
+    fn visit_unary_op(&mut self, &UnaryOp) -> Control { Control::Continue }
     fn visit_union(&mut self, &Union) -> Control { Control::Continue }
     fn visit_unsafe_block(&mut self, &UnsafeBlock) -> Control { Control::Continue }
     fn visit_use(&mut self, &Use) -> Control { Control::Continue }
@@ -2277,6 +3281,7 @@ pub trait Visitor {
     fn exit_as_type(&mut self, &AsType) {}
     fn exit_ascription(&mut self, &Ascription) {}
     fn exit_associated_type(&mut self, &AssociatedType) {}
+    fn exit_async_block(&mut self, &AsyncBlock) {}
     fn exit_attribute(&mut self, &Attribute) {}
     fn exit_attribute_containing(&mut self, &AttributeContaining) {}
     fn exit_attributed_enum_variant(&mut self, &Attributed<EnumVariant>) {}
@@ -2289,7 +3294,9 @@ pub trait Visitor {
     fn exit_attributed_struct_definition_field_named(&mut self, &Attributed<StructDefinitionFieldNamed>) {}
     fn exit_attributed_struct_definition_field_unnamed(&mut self, &Attributed<StructDefinitionFieldUnnamed>) {}
     fn exit_attributed_trait_member(&mut self, &Attributed<TraitMember>) {}
+    fn exit_await(&mut self, &Await) {}
     fn exit_binary(&mut self, &Binary) {}
+    fn exit_binary_op(&mut self, &BinaryOp) {}
     fn exit_block(&mut self, &Block) {}
     fn exit_break(&mut self, &Break) {}
     fn exit_byte(&mut self, &Byte) {}
@@ -2307,6 +3314,7 @@ pub trait Visitor {
     fn exit_enum(&mut self, &Enum) {}
     fn exit_enum_variant(&mut self, &EnumVariant) {}
     fn exit_enum_variant_body(&mut self, &EnumVariantBody) {}
+    fn exit_enum_variant_body_unit(&mut self, &EnumVariantBodyUnit) {}
     fn exit_expression(&mut self, &Expression) {}
     fn exit_expression_box(&mut self, &ExpressionBox) {}
     fn exit_extern_block(&mut self, &ExternBlock) {}
@@ -2422,6 +3430,7 @@ pub trait Visitor {
     fn exit_type_alias(&mut self, &TypeAlias) {}
     fn exit_type_array(&mut self, &TypeArray) {}
     fn exit_type_disambiguation(&mut self, &TypeDisambiguation) {}
+    fn exit_type_dyn_trait(&mut self, &TypeDynTrait) {}
     fn exit_type_function(&mut self, &TypeFunction) {}
     fn exit_type_function_argument(&mut self, &TypeFunctionArgument) {}
     fn exit_type_function_argument_named(&mut self, &TypeFunctionArgumentNamed) {}
@@ -2441,6 +3450,7 @@ pub trait Visitor {
     fn exit_type_slice(&mut self, &TypeSlice) {}
     fn exit_type_tuple(&mut self, &TypeTuple) {}
     fn exit_unary(&mut self, &Unary) {}
+    fn exit_unary_op(&mut self, &UnaryOp) {}
     fn exit_union(&mut self, &Union) {}
     fn exit_unsafe_block(&mut self, &UnsafeBlock) {}
     fn exit_use(&mut self, &Use) {}
@@ -2459,8 +3469,1038 @@ pub trait Visitor {
     fn exit_whitespace(&mut self, &Whitespace) {}
 }
 
+pub trait VisitorMut {
+    fn visit_argument_mut(&mut self, &mut Argument) -> Control { Control::Continue }
+    fn visit_array_mut(&mut self, &mut Array) -> Control { Control::Continue }
+    fn visit_array_explicit_mut(&mut self, &mut ArrayExplicit) -> Control { Control::Continue }
+    fn visit_array_repeated_mut(&mut self, &mut ArrayRepeated) -> Control { Control::Continue }
+    fn visit_as_type_mut(&mut self, &mut AsType) -> Control { Control::Continue }
+    fn visit_ascription_mut(&mut self, &mut Ascription) -> Control { Control::Continue }
+    fn visit_associated_type_mut(&mut self, &mut AssociatedType) -> Control { Control::Continue }
+    fn visit_async_block_mut(&mut self, &mut AsyncBlock) -> Control { Control::Continue }
+    fn visit_attribute_mut(&mut self, &mut Attribute) -> Control { Control::Continue }
+    fn visit_attribute_containing_mut(&mut self, &mut AttributeContaining) -> Control { Control::Continue }
+    fn visit_attributed_enum_variant_mut(&mut self, &mut Attributed<EnumVariant>) -> Control { Control::Continue }
+    fn visit_attributed_expression_mut(&mut self, &mut Attributed<Expression>) -> Control { Control::Continue }
+    fn visit_attributed_extern_block_member_mut(&mut self, &mut Attributed<ExternBlockMember>) -> Control { Control::Continue }
+    fn visit_attributed_generic_declaration_lifetime_mut(&mut self, &mut Attributed<GenericDeclarationLifetime>) -> Control { Control::Continue }
+    fn visit_attributed_generic_declaration_type_mut(&mut self, &mut Attributed<GenericDeclarationType>) -> Control { Control::Continue }
+    fn visit_attributed_impl_member_mut(&mut self, &mut Attributed<ImplMember>) -> Control { Control::Continue }
+    fn visit_attributed_item_mut(&mut self, &mut Attributed<Item>) -> Control { Control::Continue }
+    fn visit_attributed_struct_definition_field_named_mut(&mut self, &mut Attributed<StructDefinitionFieldNamed>) -> Control { Control::Continue }
+    fn visit_attributed_struct_definition_field_unnamed_mut(&mut self, &mut Attributed<StructDefinitionFieldUnnamed>) -> Control { Control::Continue }
+    fn visit_attributed_trait_member_mut(&mut self, &mut Attributed<TraitMember>) -> Control { Control::Continue }
+    fn visit_await_mut(&mut self, &mut Await) -> Control { Control::Continue }
+    fn visit_binary_mut(&mut self, &mut Binary) -> Control { Control::Continue }
+    fn visit_binary_op_mut(&mut self, &mut BinaryOp) -> Control { Control::Continue }
+    fn visit_block_mut(&mut self, &mut Block) -> Control { Control::Continue }
+    fn visit_break_mut(&mut self, &mut Break) -> Control { Control::Continue }
+    fn visit_byte_mut(&mut self, &mut Byte) -> Control { Control::Continue }
+    fn visit_byte_string_mut(&mut self, &mut ByteString) -> Control { Control::Continue }
+    fn visit_call_mut(&mut self, &mut Call) -> Control { Control::Continue }
+    fn visit_character_mut(&mut self, &mut Character) -> Control { Control::Continue }
+    fn visit_closure_mut(&mut self, &mut Closure) -> Control { Control::Continue }
+    fn visit_closure_arg_mut(&mut self, &mut ClosureArg) -> Control { Control::Continue }
+    fn visit_comment_mut(&mut self, &mut Comment) -> Control { Control::Continue }
+    fn visit_const_mut(&mut self, &mut Const) -> Control { Control::Continue }
+    fn visit_continue_mut(&mut self, &mut Continue) -> Control { Control::Continue }
+    fn visit_crate_mut(&mut self, &mut Crate) -> Control { Control::Continue }
+    fn visit_dereference_mut(&mut self, &mut Dereference) -> Control { Control::Continue }
+    fn visit_disambiguation_mut(&mut self, &mut Disambiguation) -> Control { Control::Continue }
+    fn visit_enum_mut(&mut self, &mut Enum) -> Control { Control::Continue }
+    fn visit_enum_variant_mut(&mut self, &mut EnumVariant) -> Control { Control::Continue }
+    fn visit_enum_variant_body_mut(&mut self, &mut EnumVariantBody) -> Control { Control::Continue }
+    fn visit_enum_variant_body_unit_mut(&mut self, &mut EnumVariantBodyUnit) -> Control { Control::Continue }
+    fn visit_expression_mut(&mut self, &mut Expression) -> Control { Control::Continue }
+    fn visit_expression_box_mut(&mut self, &mut ExpressionBox) -> Control { Control::Continue }
+    fn visit_extern_block_mut(&mut self, &mut ExternBlock) -> Control { Control::Continue }
+    fn visit_extern_block_member_mut(&mut self, &mut ExternBlockMember) -> Control { Control::Continue }
+    fn visit_extern_block_member_function_mut(&mut self, &mut ExternBlockMemberFunction) -> Control { Control::Continue }
+    fn visit_extern_block_member_function_argument_mut(&mut self, &mut ExternBlockMemberFunctionArgument) -> Control { Control::Continue }
+    fn visit_extern_block_member_function_argument_named_mut(&mut self, &mut ExternBlockMemberFunctionArgumentNamed) -> Control { Control::Continue }
+    fn visit_extern_block_member_function_argument_variadic_mut(&mut self, &mut ExternBlockMemberFunctionArgumentVariadic) -> Control { Control::Continue }
+    fn visit_extern_block_member_static_mut(&mut self, &mut ExternBlockMemberStatic) -> Control { Control::Continue }
+    fn visit_field_access_mut(&mut self, &mut FieldAccess) -> Control { Control::Continue }
+    fn visit_file_mut(&mut self, &mut File) -> Control { Control::Continue }
+    fn visit_for_loop_mut(&mut self, &mut ForLoop) -> Control { Control::Continue }
+    fn visit_function_mut(&mut self, &mut Function) -> Control { Control::Continue }
+    fn visit_function_header_mut(&mut self, &mut FunctionHeader) -> Control { Control::Continue }
+    fn visit_function_qualifiers_mut(&mut self, &mut FunctionQualifiers) -> Control { Control::Continue }
+    fn visit_generic_declaration_lifetime_mut(&mut self, &mut GenericDeclarationLifetime) -> Control { Control::Continue }
+    fn visit_generic_declaration_type_mut(&mut self, &mut GenericDeclarationType) -> Control { Control::Continue }
+    fn visit_generic_declarations_mut(&mut self, &mut GenericDeclarations) -> Control { Control::Continue }
+    fn visit_ident_mut(&mut self, &mut Ident) -> Control { Control::Continue }
+    fn visit_if_mut(&mut self, &mut If) -> Control { Control::Continue }
+    fn visit_if_let_mut(&mut self, &mut IfLet) -> Control { Control::Continue }
+    fn visit_impl_mut(&mut self, &mut Impl) -> Control { Control::Continue }
+    fn visit_impl_const_mut(&mut self, &mut ImplConst) -> Control { Control::Continue }
+    fn visit_impl_function_mut(&mut self, &mut ImplFunction) -> Control { Control::Continue }
+    fn visit_impl_kind_mut(&mut self, &mut ImplKind) -> Control { Control::Continue }
+    fn visit_impl_member_mut(&mut self, &mut ImplMember) -> Control { Control::Continue }
+    fn visit_impl_of_inherent_mut(&mut self, &mut ImplOfInherent) -> Control { Control::Continue }
+    fn visit_impl_of_trait_mut(&mut self, &mut ImplOfTrait) -> Control { Control::Continue }
+    fn visit_impl_of_trait_type_mut(&mut self, &mut ImplOfTraitType) -> Control { Control::Continue }
+    fn visit_impl_type_mut(&mut self, &mut ImplType) -> Control { Control::Continue }
+    fn visit_item_mut(&mut self, &mut Item) -> Control { Control::Continue }
+    fn visit_let_mut(&mut self, &mut Let) -> Control { Control::Continue }
+    fn visit_lifetime_mut(&mut self, &mut Lifetime) -> Control { Control::Continue }
+    fn visit_loop_mut(&mut self, &mut Loop) -> Control { Control::Continue }
+    fn visit_macro_call_mut(&mut self, &mut MacroCall) -> Control { Control::Continue }
+    fn visit_macro_call_args_mut(&mut self, &mut MacroCallArgs) -> Control { Control::Continue }
+    fn visit_match_mut(&mut self, &mut Match) -> Control { Control::Continue }
+    fn visit_match_arm_mut(&mut self, &mut MatchArm) -> Control { Control::Continue }
+    fn visit_match_hand_mut(&mut self, &mut MatchHand) -> Control { Control::Continue }
+    fn visit_module_mut(&mut self, &mut Module) -> Control { Control::Continue }
+    fn visit_named_argument_mut(&mut self, &mut NamedArgument) -> Control { Control::Continue }
+    fn visit_number_mut(&mut self, &mut Number) -> Control { Control::Continue }
+    fn visit_number_value_mut(&mut self, &mut NumberValue) -> Control { Control::Continue }
+    fn visit_number_binary_mut(&mut self, &mut NumberBinary) -> Control { Control::Continue }
+    fn visit_number_decimal_mut(&mut self, &mut NumberDecimal) -> Control { Control::Continue }
+    fn visit_number_hexadecimal_mut(&mut self, &mut NumberHexadecimal) -> Control { Control::Continue }
+    fn visit_number_octal_mut(&mut self, &mut NumberOctal) -> Control { Control::Continue }
+    fn visit_parenthetical_mut(&mut self, &mut Parenthetical) -> Control { Control::Continue }
+    fn visit_path_mut(&mut self, &mut Path) -> Control { Control::Continue }
+    fn visit_path_component_mut(&mut self, &mut PathComponent) -> Control { Control::Continue }
+    fn visit_pathed_ident_mut(&mut self, &mut PathedIdent) -> Control { Control::Continue }
+    fn visit_pattern_mut(&mut self, &mut Pattern) -> Control { Control::Continue }
+    fn visit_pattern_name_mut(&mut self, &mut PatternName) -> Control { Control::Continue }
+    fn visit_pattern_bundle_member_mut(&mut self, &mut PatternBundleMember) -> Control { Control::Continue }
+    fn visit_pattern_byte_mut(&mut self, &mut PatternByte) -> Control { Control::Continue }
+    fn visit_pattern_byte_string_mut(&mut self, &mut PatternByteString) -> Control { Control::Continue }
+    fn visit_pattern_character_mut(&mut self, &mut PatternCharacter) -> Control { Control::Continue }
+    fn visit_pattern_ident_mut(&mut self, &mut PatternIdent) -> Control { Control::Continue }
+    fn visit_pattern_kind_mut(&mut self, &mut PatternKind) -> Control { Control::Continue }
+    fn visit_pattern_macro_call_mut(&mut self, &mut PatternMacroCall) -> Control { Control::Continue }
+    fn visit_pattern_number_mut(&mut self, &mut PatternNumber) -> Control { Control::Continue }
+    fn visit_pattern_range_exclusive_mut(&mut self, &mut PatternRangeExclusive) -> Control { Control::Continue }
+    fn visit_pattern_range_inclusive_mut(&mut self, &mut PatternRangeInclusive) -> Control { Control::Continue }
+    fn visit_pattern_reference_mut(&mut self, &mut PatternReference) -> Control { Control::Continue }
+    fn visit_pattern_slice_mut(&mut self, &mut PatternSlice) -> Control { Control::Continue }
+    fn visit_pattern_string_mut(&mut self, &mut PatternString) -> Control { Control::Continue }
+    fn visit_pattern_struct_mut(&mut self, &mut PatternStruct) -> Control { Control::Continue }
+    fn visit_pattern_struct_field_mut(&mut self, &mut PatternStructField) -> Control { Control::Continue }
+    fn visit_pattern_struct_field_long_mut(&mut self, &mut PatternStructFieldLong) -> Control { Control::Continue }
+    fn visit_pattern_struct_field_short_mut(&mut self, &mut PatternStructFieldShort) -> Control { Control::Continue }
+    fn visit_pattern_tuple_mut(&mut self, &mut PatternTuple) -> Control { Control::Continue }
+    fn visit_pattern_wildcard_mut(&mut self, &mut PatternWildcard) -> Control { Control::Continue }
+    fn visit_pattern_box_mut(&mut self, &mut PatternBox) -> Control { Control::Continue }
+    fn visit_range_mut(&mut self, &mut Range) -> Control { Control::Continue }
+    fn visit_range_inclusive_mut(&mut self, &mut RangeInclusive) -> Control { Control::Continue }
+    fn visit_reference_mut(&mut self, &mut Reference) -> Control { Control::Continue }
+    fn visit_return_mut(&mut self, &mut Return) -> Control { Control::Continue }
+    fn visit_self_argument_mut(&mut self, &mut SelfArgument) -> Control { Control::Continue }
+    fn visit_self_argument_longhand_mut(&mut self, &mut SelfArgumentLonghand) -> Control { Control::Continue }
+    fn visit_self_argument_shorthand_mut(&mut self, &mut SelfArgumentShorthand) -> Control { Control::Continue }
+    fn visit_self_argument_shorthand_qualifier_mut(&mut self, &mut SelfArgumentShorthandQualifier) -> Control { Control::Continue }
+    fn visit_slice_mut(&mut self, &mut Slice) -> Control { Control::Continue }
+    fn visit_statement_mut(&mut self, &mut Statement) -> Control { Control::Continue }
+    fn visit_static_mut(&mut self, &mut Static) -> Control { Control::Continue }
+    fn visit_string_mut(&mut self, &mut String) -> Control { Control::Continue }
+    fn visit_struct_mut(&mut self, &mut Struct) -> Control { Control::Continue }
+    fn visit_struct_definition_body_mut(&mut self, &mut StructDefinitionBody) -> Control { Control::Continue }
+    fn visit_struct_definition_body_brace_mut(&mut self, &mut StructDefinitionBodyBrace) -> Control { Control::Continue }
+    fn visit_struct_definition_body_tuple_mut(&mut self, &mut StructDefinitionBodyTuple) -> Control { Control::Continue }
+    fn visit_struct_definition_field_named_mut(&mut self, &mut StructDefinitionFieldNamed) -> Control { Control::Continue }
+    fn visit_struct_definition_field_unnamed_mut(&mut self, &mut StructDefinitionFieldUnnamed) -> Control { Control::Continue }
+    fn visit_struct_literal_mut(&mut self, &mut StructLiteral) -> Control { Control::Continue }
+    fn visit_struct_literal_field_mut(&mut self, &mut StructLiteralField) -> Control { Control::Continue }
+    fn visit_trait_mut(&mut self, &mut Trait) -> Control { Control::Continue }
+    fn visit_trait_bound_mut(&mut self, &mut TraitBound) -> Control { Control::Continue }
+    fn visit_trait_bound_lifetime_mut(&mut self, &mut TraitBoundLifetime) -> Control { Control::Continue }
+    fn visit_trait_bound_normal_mut(&mut self, &mut TraitBoundNormal) -> Control { Control::Continue }
+    fn visit_trait_bound_relaxed_mut(&mut self, &mut TraitBoundRelaxed) -> Control { Control::Continue }
+    fn visit_trait_bound_type_mut(&mut self, &mut TraitBoundType) -> Control { Control::Continue }
+    fn visit_trait_bounds_mut(&mut self, &mut TraitBounds) -> Control { Control::Continue }
+    fn visit_trait_impl_argument_mut(&mut self, &mut TraitImplArgument) -> Control { Control::Continue }
+    fn visit_trait_impl_argument_named_mut(&mut self, &mut TraitImplArgumentNamed) -> Control { Control::Continue }
+    fn visit_trait_impl_function_header_mut(&mut self, &mut TraitImplFunctionHeader) -> Control { Control::Continue }
+    fn visit_trait_member_mut(&mut self, &mut TraitMember) -> Control { Control::Continue }
+    fn visit_trait_member_const_mut(&mut self, &mut TraitMemberConst) -> Control { Control::Continue }
+    fn visit_trait_member_function_mut(&mut self, &mut TraitMemberFunction) -> Control { Control::Continue }
+    fn visit_trait_member_type_mut(&mut self, &mut TraitMemberType) -> Control { Control::Continue }
+    fn visit_try_operator_mut(&mut self, &mut TryOperator) -> Control { Control::Continue }
+    fn visit_tuple_mut(&mut self, &mut Tuple) -> Control { Control::Continue }
+    fn visit_turbofish_mut(&mut self, &mut Turbofish) -> Control { Control::Continue }
+    fn visit_type_mut(&mut self, &mut Type) -> Control { Control::Continue }
+    fn visit_type_additional_mut(&mut self, &mut TypeAdditional) -> Control { Control::Continue }
+    fn visit_type_alias_mut(&mut self, &mut TypeAlias) -> Control { Control::Continue }
+    fn visit_type_array_mut(&mut self, &mut TypeArray) -> Control { Control::Continue }
+    fn visit_type_disambiguation_mut(&mut self, &mut TypeDisambiguation) -> Control { Control::Continue }
+    fn visit_type_dyn_trait_mut(&mut self, &mut TypeDynTrait) -> Control { Control::Continue }
+    fn visit_type_function_mut(&mut self, &mut TypeFunction) -> Control { Control::Continue }
+    fn visit_type_function_argument_mut(&mut self, &mut TypeFunctionArgument) -> Control { Control::Continue }
+    fn visit_type_function_argument_named_mut(&mut self, &mut TypeFunctionArgumentNamed) -> Control { Control::Continue }
+    fn visit_type_generics_mut(&mut self, &mut TypeGenerics) -> Control { Control::Continue }
+    fn visit_type_generics_angle_mut(&mut self, &mut TypeGenericsAngle) -> Control { Control::Continue }
+    fn visit_type_generics_angle_member_mut(&mut self, &mut TypeGenericsAngleMember) -> Control { Control::Continue }
+    fn visit_type_generics_function_mut(&mut self, &mut TypeGenericsFunction) -> Control { Control::Continue }
+    fn visit_type_higher_ranked_trait_bounds_mut(&mut self, &mut TypeHigherRankedTraitBounds) -> Control { Control::Continue }
+    fn visit_type_higher_ranked_trait_bounds_child_mut(&mut self, &mut TypeHigherRankedTraitBoundsChild) -> Control { Control::Continue }
+    fn visit_type_impl_trait_mut(&mut self, &mut TypeImplTrait) -> Control { Control::Continue }
+    fn visit_type_kind_mut(&mut self, &mut TypeKind) -> Control { Control::Continue }
+    fn visit_type_named_mut(&mut self, &mut TypeNamed) -> Control { Control::Continue }
+    fn visit_type_named_component_mut(&mut self, &mut TypeNamedComponent) -> Control { Control::Continue }
+    fn visit_type_pointer_mut(&mut self, &mut TypePointer) -> Control { Control::Continue }
+    fn visit_type_reference_mut(&mut self, &mut TypeReference) -> Control { Control::Continue }
+    fn visit_type_reference_kind_mut(&mut self, &mut TypeReferenceKind) -> Control { Control::Continue }
+    fn visit_type_slice_mut(&mut self, &mut TypeSlice) -> Control { Control::Continue }
+    fn visit_type_tuple_mut(&mut self, &mut TypeTuple) -> Control { Control::Continue }
+    fn visit_unary_mut(&mut self, &mut Unary) -> Control { Control::Continue }
+    fn visit_unary_op_mut(&mut self, &mut UnaryOp) -> Control { Control::Continue }
+    fn visit_union_mut(&mut self, &mut Union) -> Control { Control::Continue }
+    fn visit_unsafe_block_mut(&mut self, &mut UnsafeBlock) -> Control { Control::Continue }
+    fn visit_use_mut(&mut self, &mut Use) -> Control { Control::Continue }
+    fn visit_use_tail_mut(&mut self, &mut UseTail) -> Control { Control::Continue }
+    fn visit_use_tail_glob_mut(&mut self, &mut UseTailGlob) -> Control { Control::Continue }
+    fn visit_use_tail_ident_mut(&mut self, &mut UseTailIdent) -> Control { Control::Continue }
+    fn visit_use_tail_multi_mut(&mut self, &mut UseTailMulti) -> Control { Control::Continue }
+    fn visit_value_mut(&mut self, &mut Value) -> Control { Control::Continue }
+    fn visit_visibility_mut(&mut self, &mut Visibility) -> Control { Control::Continue }
+    fn visit_where_mut(&mut self, &mut Where) -> Control { Control::Continue }
+    fn visit_where_kind_mut(&mut self, &mut WhereKind) -> Control { Control::Continue }
+    fn visit_where_lifetime_mut(&mut self, &mut WhereLifetime) -> Control { Control::Continue }
+    fn visit_where_type_mut(&mut self, &mut WhereType) -> Control { Control::Continue }
+    fn visit_while_mut(&mut self, &mut While) -> Control { Control::Continue }
+    fn visit_while_let_mut(&mut self, &mut WhileLet) -> Control { Control::Continue }
+    fn visit_whitespace_mut(&mut self, &mut Whitespace) -> Control { Control::Continue }
+
+    fn exit_argument_mut(&mut self, &mut Argument) {}
+    fn exit_array_mut(&mut self, &mut Array) {}
+    fn exit_array_explicit_mut(&mut self, &mut ArrayExplicit) {}
+    fn exit_array_repeated_mut(&mut self, &mut ArrayRepeated) {}
+    fn exit_as_type_mut(&mut self, &mut AsType) {}
+    fn exit_ascription_mut(&mut self, &mut Ascription) {}
+    fn exit_associated_type_mut(&mut self, &mut AssociatedType) {}
+    fn exit_async_block_mut(&mut self, &mut AsyncBlock) {}
+    fn exit_attribute_mut(&mut self, &mut Attribute) {}
+    fn exit_attribute_containing_mut(&mut self, &mut AttributeContaining) {}
+    fn exit_attributed_enum_variant_mut(&mut self, &mut Attributed<EnumVariant>) {}
+    fn exit_attributed_expression_mut(&mut self, &mut Attributed<Expression>) {}
+    fn exit_attributed_extern_block_member_mut(&mut self, &mut Attributed<ExternBlockMember>) {}
+    fn exit_attributed_generic_declaration_lifetime_mut(&mut self, &mut Attributed<GenericDeclarationLifetime>) {}
+    fn exit_attributed_generic_declaration_type_mut(&mut self, &mut Attributed<GenericDeclarationType>) {}
+    fn exit_attributed_impl_member_mut(&mut self, &mut Attributed<ImplMember>) {}
+    fn exit_attributed_item_mut(&mut self, &mut Attributed<Item>) {}
+    fn exit_attributed_struct_definition_field_named_mut(&mut self, &mut Attributed<StructDefinitionFieldNamed>) {}
+    fn exit_attributed_struct_definition_field_unnamed_mut(&mut self, &mut Attributed<StructDefinitionFieldUnnamed>) {}
+    fn exit_attributed_trait_member_mut(&mut self, &mut Attributed<TraitMember>) {}
+    fn exit_await_mut(&mut self, &mut Await) {}
+    fn exit_binary_mut(&mut self, &mut Binary) {}
+    fn exit_binary_op_mut(&mut self, &mut BinaryOp) {}
+    fn exit_block_mut(&mut self, &mut Block) {}
+    fn exit_break_mut(&mut self, &mut Break) {}
+    fn exit_byte_mut(&mut self, &mut Byte) {}
+    fn exit_byte_string_mut(&mut self, &mut ByteString) {}
+    fn exit_call_mut(&mut self, &mut Call) {}
+    fn exit_character_mut(&mut self, &mut Character) {}
+    fn exit_closure_mut(&mut self, &mut Closure) {}
+    fn exit_closure_arg_mut(&mut self, &mut ClosureArg) {}
+    fn exit_comment_mut(&mut self, &mut Comment) {}
+    fn exit_const_mut(&mut self, &mut Const) {}
+    fn exit_continue_mut(&mut self, &mut Continue) {}
+    fn exit_crate_mut(&mut self, &mut Crate) {}
+    fn exit_dereference_mut(&mut self, &mut Dereference) {}
+    fn exit_disambiguation_mut(&mut self, &mut Disambiguation) {}
+    fn exit_enum_mut(&mut self, &mut Enum) {}
+    fn exit_enum_variant_mut(&mut self, &mut EnumVariant) {}
+    fn exit_enum_variant_body_mut(&mut self, &mut EnumVariantBody) {}
+    fn exit_enum_variant_body_unit_mut(&mut self, &mut EnumVariantBodyUnit) {}
+    fn exit_expression_mut(&mut self, &mut Expression) {}
+    fn exit_expression_box_mut(&mut self, &mut ExpressionBox) {}
+    fn exit_extern_block_mut(&mut self, &mut ExternBlock) {}
+    fn exit_extern_block_member_mut(&mut self, &mut ExternBlockMember) {}
+    fn exit_extern_block_member_function_mut(&mut self, &mut ExternBlockMemberFunction) {}
+    fn exit_extern_block_member_function_argument_mut(&mut self, &mut ExternBlockMemberFunctionArgument) {}
+    fn exit_extern_block_member_function_argument_named_mut(&mut self, &mut ExternBlockMemberFunctionArgumentNamed) {}
+    fn exit_extern_block_member_function_argument_variadic_mut(&mut self, &mut ExternBlockMemberFunctionArgumentVariadic) {}
+    fn exit_extern_block_member_static_mut(&mut self, &mut ExternBlockMemberStatic) {}
+    fn exit_field_access_mut(&mut self, &mut FieldAccess) {}
+    fn exit_file_mut(&mut self, &mut File) {}
+    fn exit_for_loop_mut(&mut self, &mut ForLoop) {}
+    fn exit_function_mut(&mut self, &mut Function) {}
+    fn exit_function_header_mut(&mut self, &mut FunctionHeader) {}
+    fn exit_function_qualifiers_mut(&mut self, &mut FunctionQualifiers) {}
+    fn exit_generic_declaration_lifetime_mut(&mut self, &mut GenericDeclarationLifetime) {}
+    fn exit_generic_declaration_type_mut(&mut self, &mut GenericDeclarationType) {}
+    fn exit_generic_declarations_mut(&mut self, &mut GenericDeclarations) {}
+    fn exit_ident_mut(&mut self, &mut Ident) {}
+    fn exit_if_mut(&mut self, &mut If) {}
+    fn exit_if_let_mut(&mut self, &mut IfLet) {}
+    fn exit_impl_mut(&mut self, &mut Impl) {}
+    fn exit_impl_const_mut(&mut self, &mut ImplConst) {}
+    fn exit_impl_function_mut(&mut self, &mut ImplFunction) {}
+    fn exit_impl_kind_mut(&mut self, &mut ImplKind) {}
+    fn exit_impl_member_mut(&mut self, &mut ImplMember) {}
+    fn exit_impl_of_inherent_mut(&mut self, &mut ImplOfInherent) {}
+    fn exit_impl_of_trait_mut(&mut self, &mut ImplOfTrait) {}
+    fn exit_impl_of_trait_type_mut(&mut self, &mut ImplOfTraitType) {}
+    fn exit_impl_type_mut(&mut self, &mut ImplType) {}
+    fn exit_item_mut(&mut self, &mut Item) {}
+    fn exit_let_mut(&mut self, &mut Let) {}
+    fn exit_lifetime_mut(&mut self, &mut Lifetime) {}
+    fn exit_loop_mut(&mut self, &mut Loop) {}
+    fn exit_macro_call_mut(&mut self, &mut MacroCall) {}
+    fn exit_macro_call_args_mut(&mut self, &mut MacroCallArgs) {}
+    fn exit_match_mut(&mut self, &mut Match) {}
+    fn exit_match_arm_mut(&mut self, &mut MatchArm) {}
+    fn exit_match_hand_mut(&mut self, &mut MatchHand) {}
+    fn exit_module_mut(&mut self, &mut Module) {}
+    fn exit_named_argument_mut(&mut self, &mut NamedArgument) {}
+    fn exit_number_mut(&mut self, &mut Number) {}
+    fn exit_number_value_mut(&mut self, &mut NumberValue) {}
+    fn exit_number_binary_mut(&mut self, &mut NumberBinary) {}
+    fn exit_number_decimal_mut(&mut self, &mut NumberDecimal) {}
+    fn exit_number_hexadecimal_mut(&mut self, &mut NumberHexadecimal) {}
+    fn exit_number_octal_mut(&mut self, &mut NumberOctal) {}
+    fn exit_parenthetical_mut(&mut self, &mut Parenthetical) {}
+    fn exit_path_mut(&mut self, &mut Path) {}
+    fn exit_path_component_mut(&mut self, &mut PathComponent) {}
+    fn exit_pathed_ident_mut(&mut self, &mut PathedIdent) {}
+    fn exit_pattern_mut(&mut self, &mut Pattern) {}
+    fn exit_pattern_bundle_member_mut(&mut self, &mut PatternBundleMember) {}
+    fn exit_pattern_byte_mut(&mut self, &mut PatternByte) {}
+    fn exit_pattern_byte_string_mut(&mut self, &mut PatternByteString) {}
+    fn exit_pattern_character_mut(&mut self, &mut PatternCharacter) {}
+    fn exit_pattern_ident_mut(&mut self, &mut PatternIdent) {}
+    fn exit_pattern_kind_mut(&mut self, &mut PatternKind) {}
+    fn exit_pattern_macro_call_mut(&mut self, &mut PatternMacroCall) {}
+    fn exit_pattern_name_mut(&mut self, &mut PatternName) {}
+    fn exit_pattern_number_mut(&mut self, &mut PatternNumber) {}
+    fn exit_pattern_range_exclusive_mut(&mut self, &mut PatternRangeExclusive) {}
+    fn exit_pattern_range_inclusive_mut(&mut self, &mut PatternRangeInclusive) {}
+    fn exit_pattern_reference_mut(&mut self, &mut PatternReference) {}
+    fn exit_pattern_slice_mut(&mut self, &mut PatternSlice) {}
+    fn exit_pattern_string_mut(&mut self, &mut PatternString) {}
+    fn exit_pattern_struct_mut(&mut self, &mut PatternStruct) {}
+    fn exit_pattern_struct_field_mut(&mut self, &mut PatternStructField) {}
+    fn exit_pattern_struct_field_long_mut(&mut self, &mut PatternStructFieldLong) {}
+    fn exit_pattern_struct_field_short_mut(&mut self, &mut PatternStructFieldShort) {}
+    fn exit_pattern_tuple_mut(&mut self, &mut PatternTuple) {}
+    fn exit_pattern_wildcard_mut(&mut self, &mut PatternWildcard) {}
+    fn exit_pattern_box_mut(&mut self, &mut PatternBox) {}
+    fn exit_range_mut(&mut self, &mut Range) {}
+    fn exit_range_inclusive_mut(&mut self, &mut RangeInclusive) {}
+    fn exit_reference_mut(&mut self, &mut Reference) {}
+    fn exit_return_mut(&mut self, &mut Return) {}
+    fn exit_self_argument_mut(&mut self, &mut SelfArgument) {}
+    fn exit_self_argument_longhand_mut(&mut self, &mut SelfArgumentLonghand) {}
+    fn exit_self_argument_shorthand_mut(&mut self, &mut SelfArgumentShorthand) {}
+    fn exit_self_argument_shorthand_qualifier_mut(&mut self, &mut SelfArgumentShorthandQualifier) {}
+    fn exit_slice_mut(&mut self, &mut Slice) {}
+    fn exit_statement_mut(&mut self, &mut Statement) {}
+    fn exit_static_mut(&mut self, &mut Static) {}
+    fn exit_string_mut(&mut self, &mut String) {}
+    fn exit_struct_mut(&mut self, &mut Struct) {}
+    fn exit_struct_definition_body_mut(&mut self, &mut StructDefinitionBody) {}
+    fn exit_struct_definition_body_brace_mut(&mut self, &mut StructDefinitionBodyBrace) {}
+    fn exit_struct_definition_body_tuple_mut(&mut self, &mut StructDefinitionBodyTuple) {}
+    fn exit_struct_definition_field_named_mut(&mut self, &mut StructDefinitionFieldNamed) {}
+    fn exit_struct_definition_field_unnamed_mut(&mut self, &mut StructDefinitionFieldUnnamed) {}
+    fn exit_struct_literal_mut(&mut self, &mut StructLiteral) {}
+    fn exit_struct_literal_field_mut(&mut self, &mut StructLiteralField) {}
+    fn exit_trait_mut(&mut self, &mut Trait) {}
+    fn exit_trait_bound_mut(&mut self, &mut TraitBound) {}
+    fn exit_trait_bound_lifetime_mut(&mut self, &mut TraitBoundLifetime) {}
+    fn exit_trait_bound_normal_mut(&mut self, &mut TraitBoundNormal) {}
+    fn exit_trait_bound_relaxed_mut(&mut self, &mut TraitBoundRelaxed) {}
+    fn exit_trait_bound_type_mut(&mut self, &mut TraitBoundType) {}
+    fn exit_trait_bounds_mut(&mut self, &mut TraitBounds) {}
+    fn exit_trait_impl_argument_mut(&mut self, &mut TraitImplArgument) {}
+    fn exit_trait_impl_argument_named_mut(&mut self, &mut TraitImplArgumentNamed) {}
+    fn exit_trait_impl_function_header_mut(&mut self, &mut TraitImplFunctionHeader) {}
+    fn exit_trait_member_mut(&mut self, &mut TraitMember) {}
+    fn exit_trait_member_const_mut(&mut self, &mut TraitMemberConst) {}
+    fn exit_trait_member_function_mut(&mut self, &mut TraitMemberFunction) {}
+    fn exit_trait_member_type_mut(&mut self, &mut TraitMemberType) {}
+    fn exit_try_operator_mut(&mut self, &mut TryOperator) {}
+    fn exit_tuple_mut(&mut self, &mut Tuple) {}
+    fn exit_turbofish_mut(&mut self, &mut Turbofish) {}
+    fn exit_type_mut(&mut self, &mut Type) {}
+    fn exit_type_additional_mut(&mut self, &mut TypeAdditional) {}
+    fn exit_type_alias_mut(&mut self, &mut TypeAlias) {}
+    fn exit_type_array_mut(&mut self, &mut TypeArray) {}
+    fn exit_type_disambiguation_mut(&mut self, &mut TypeDisambiguation) {}
+    fn exit_type_dyn_trait_mut(&mut self, &mut TypeDynTrait) {}
+    fn exit_type_function_mut(&mut self, &mut TypeFunction) {}
+    fn exit_type_function_argument_mut(&mut self, &mut TypeFunctionArgument) {}
+    fn exit_type_function_argument_named_mut(&mut self, &mut TypeFunctionArgumentNamed) {}
+    fn exit_type_generics_mut(&mut self, &mut TypeGenerics) {}
+    fn exit_type_generics_angle_mut(&mut self, &mut TypeGenericsAngle) {}
+    fn exit_type_generics_angle_member_mut(&mut self, &mut TypeGenericsAngleMember) {}
+    fn exit_type_generics_function_mut(&mut self, &mut TypeGenericsFunction) {}
+    fn exit_type_higher_ranked_trait_bounds_mut(&mut self, &mut TypeHigherRankedTraitBounds) {}
+    fn exit_type_higher_ranked_trait_bounds_child_mut(&mut self, &mut TypeHigherRankedTraitBoundsChild) {}
+    fn exit_type_impl_trait_mut(&mut self, &mut TypeImplTrait) {}
+    fn exit_type_kind_mut(&mut self, &mut TypeKind) {}
+    fn exit_type_named_mut(&mut self, &mut TypeNamed) {}
+    fn exit_type_named_component_mut(&mut self, &mut TypeNamedComponent) {}
+    fn exit_type_pointer_mut(&mut self, &mut TypePointer) {}
+    fn exit_type_reference_mut(&mut self, &mut TypeReference) {}
+    fn exit_type_reference_kind_mut(&mut self, &mut TypeReferenceKind) {}
+    fn exit_type_slice_mut(&mut self, &mut TypeSlice) {}
+    fn exit_type_tuple_mut(&mut self, &mut TypeTuple) {}
+    fn exit_unary_mut(&mut self, &mut Unary) {}
+    fn exit_unary_op_mut(&mut self, &mut UnaryOp) {}
+    fn exit_union_mut(&mut self, &mut Union) {}
+    fn exit_unsafe_block_mut(&mut self, &mut UnsafeBlock) {}
+    fn exit_use_mut(&mut self, &mut Use) {}
+    fn exit_use_tail_mut(&mut self, &mut UseTail) {}
+    fn exit_use_tail_glob_mut(&mut self, &mut UseTailGlob) {}
+    fn exit_use_tail_ident_mut(&mut self, &mut UseTailIdent) {}
+    fn exit_use_tail_multi_mut(&mut self, &mut UseTailMulti) {}
+    fn exit_value_mut(&mut self, &mut Value) {}
+    fn exit_visibility_mut(&mut self, &mut Visibility) {}
+    fn exit_where_mut(&mut self, &mut Where) {}
+    fn exit_where_kind_mut(&mut self, &mut WhereKind) {}
+    fn exit_where_lifetime_mut(&mut self, &mut WhereLifetime) {}
+    fn exit_where_type_mut(&mut self, &mut WhereType) {}
+    fn exit_while_mut(&mut self, &mut While) {}
+    fn exit_while_let_mut(&mut self, &mut WhileLet) {}
+    fn exit_whitespace_mut(&mut self, &mut Whitespace) {}
+}
+
+pub trait Fold: Sized {
+    fn fold_argument(&mut self, node: Argument) -> Argument { node.fold_children(self) }
+    fn fold_array(&mut self, node: Array) -> Array { node.fold_children(self) }
+    fn fold_array_explicit(&mut self, node: ArrayExplicit) -> ArrayExplicit { node.fold_children(self) }
+    fn fold_array_repeated(&mut self, node: ArrayRepeated) -> ArrayRepeated { node.fold_children(self) }
+    fn fold_as_type(&mut self, node: AsType) -> AsType { node.fold_children(self) }
+    fn fold_ascription(&mut self, node: Ascription) -> Ascription { node.fold_children(self) }
+    fn fold_associated_type(&mut self, node: AssociatedType) -> AssociatedType { node.fold_children(self) }
+    fn fold_async_block(&mut self, node: AsyncBlock) -> AsyncBlock { node.fold_children(self) }
+    fn fold_attribute(&mut self, node: Attribute) -> Attribute { node.fold_children(self) }
+    fn fold_attribute_containing(&mut self, node: AttributeContaining) -> AttributeContaining { node.fold_children(self) }
+    fn fold_attributed_enum_variant(&mut self, node: Attributed<EnumVariant>) -> Attributed<EnumVariant> { node.fold_children(self) }
+    fn fold_attributed_expression(&mut self, node: Attributed<Expression>) -> Attributed<Expression> { node.fold_children(self) }
+    fn fold_attributed_extern_block_member(&mut self, node: Attributed<ExternBlockMember>) -> Attributed<ExternBlockMember> { node.fold_children(self) }
+    fn fold_attributed_generic_declaration_lifetime(&mut self, node: Attributed<GenericDeclarationLifetime>) -> Attributed<GenericDeclarationLifetime> { node.fold_children(self) }
+    fn fold_attributed_generic_declaration_type(&mut self, node: Attributed<GenericDeclarationType>) -> Attributed<GenericDeclarationType> { node.fold_children(self) }
+    fn fold_attributed_impl_member(&mut self, node: Attributed<ImplMember>) -> Attributed<ImplMember> { node.fold_children(self) }
+    fn fold_attributed_item(&mut self, node: Attributed<Item>) -> Attributed<Item> { node.fold_children(self) }
+    fn fold_attributed_struct_definition_field_named(&mut self, node: Attributed<StructDefinitionFieldNamed>) -> Attributed<StructDefinitionFieldNamed> { node.fold_children(self) }
+    fn fold_attributed_struct_definition_field_unnamed(&mut self, node: Attributed<StructDefinitionFieldUnnamed>) -> Attributed<StructDefinitionFieldUnnamed> { node.fold_children(self) }
+    fn fold_attributed_trait_member(&mut self, node: Attributed<TraitMember>) -> Attributed<TraitMember> { node.fold_children(self) }
+    fn fold_await(&mut self, node: Await) -> Await { node.fold_children(self) }
+    fn fold_binary(&mut self, node: Binary) -> Binary { node.fold_children(self) }
+    fn fold_binary_op(&mut self, node: BinaryOp) -> BinaryOp { node.fold_children(self) }
+    fn fold_block(&mut self, node: Block) -> Block { node.fold_children(self) }
+    fn fold_break(&mut self, node: Break) -> Break { node.fold_children(self) }
+    fn fold_byte(&mut self, node: Byte) -> Byte { node.fold_children(self) }
+    fn fold_byte_string(&mut self, node: ByteString) -> ByteString { node.fold_children(self) }
+    fn fold_call(&mut self, node: Call) -> Call { node.fold_children(self) }
+    fn fold_character(&mut self, node: Character) -> Character { node.fold_children(self) }
+    fn fold_closure(&mut self, node: Closure) -> Closure { node.fold_children(self) }
+    fn fold_closure_arg(&mut self, node: ClosureArg) -> ClosureArg { node.fold_children(self) }
+    fn fold_comment(&mut self, node: Comment) -> Comment { node.fold_children(self) }
+    fn fold_const(&mut self, node: Const) -> Const { node.fold_children(self) }
+    fn fold_continue(&mut self, node: Continue) -> Continue { node.fold_children(self) }
+    fn fold_crate(&mut self, node: Crate) -> Crate { node.fold_children(self) }
+    fn fold_dereference(&mut self, node: Dereference) -> Dereference { node.fold_children(self) }
+    fn fold_disambiguation(&mut self, node: Disambiguation) -> Disambiguation { node.fold_children(self) }
+    fn fold_enum(&mut self, node: Enum) -> Enum { node.fold_children(self) }
+    fn fold_enum_variant(&mut self, node: EnumVariant) -> EnumVariant { node.fold_children(self) }
+    fn fold_enum_variant_body(&mut self, node: EnumVariantBody) -> EnumVariantBody { node.fold_children(self) }
+    fn fold_enum_variant_body_unit(&mut self, node: EnumVariantBodyUnit) -> EnumVariantBodyUnit { node.fold_children(self) }
+    fn fold_expression(&mut self, node: Expression) -> Expression { node.fold_children(self) }
+    fn fold_expression_box(&mut self, node: ExpressionBox) -> ExpressionBox { node.fold_children(self) }
+    fn fold_extern_block(&mut self, node: ExternBlock) -> ExternBlock { node.fold_children(self) }
+    fn fold_extern_block_member(&mut self, node: ExternBlockMember) -> ExternBlockMember { node.fold_children(self) }
+    fn fold_extern_block_member_function(&mut self, node: ExternBlockMemberFunction) -> ExternBlockMemberFunction { node.fold_children(self) }
+    fn fold_extern_block_member_function_argument(&mut self, node: ExternBlockMemberFunctionArgument) -> ExternBlockMemberFunctionArgument { node.fold_children(self) }
+    fn fold_extern_block_member_function_argument_named(&mut self, node: ExternBlockMemberFunctionArgumentNamed) -> ExternBlockMemberFunctionArgumentNamed { node.fold_children(self) }
+    fn fold_extern_block_member_function_argument_variadic(&mut self, node: ExternBlockMemberFunctionArgumentVariadic) -> ExternBlockMemberFunctionArgumentVariadic { node.fold_children(self) }
+    fn fold_extern_block_member_static(&mut self, node: ExternBlockMemberStatic) -> ExternBlockMemberStatic { node.fold_children(self) }
+    fn fold_field_access(&mut self, node: FieldAccess) -> FieldAccess { node.fold_children(self) }
+    fn fold_file(&mut self, node: File) -> File { node.fold_children(self) }
+    fn fold_for_loop(&mut self, node: ForLoop) -> ForLoop { node.fold_children(self) }
+    fn fold_function(&mut self, node: Function) -> Function { node.fold_children(self) }
+    fn fold_function_header(&mut self, node: FunctionHeader) -> FunctionHeader { node.fold_children(self) }
+    fn fold_function_qualifiers(&mut self, node: FunctionQualifiers) -> FunctionQualifiers { node.fold_children(self) }
+    fn fold_generic_declaration_lifetime(&mut self, node: GenericDeclarationLifetime) -> GenericDeclarationLifetime { node.fold_children(self) }
+    fn fold_generic_declaration_type(&mut self, node: GenericDeclarationType) -> GenericDeclarationType { node.fold_children(self) }
+    fn fold_generic_declarations(&mut self, node: GenericDeclarations) -> GenericDeclarations { node.fold_children(self) }
+    fn fold_ident(&mut self, node: Ident) -> Ident { node.fold_children(self) }
+    fn fold_if(&mut self, node: If) -> If { node.fold_children(self) }
+    fn fold_if_let(&mut self, node: IfLet) -> IfLet { node.fold_children(self) }
+    fn fold_impl(&mut self, node: Impl) -> Impl { node.fold_children(self) }
+    fn fold_impl_const(&mut self, node: ImplConst) -> ImplConst { node.fold_children(self) }
+    fn fold_impl_function(&mut self, node: ImplFunction) -> ImplFunction { node.fold_children(self) }
+    fn fold_impl_kind(&mut self, node: ImplKind) -> ImplKind { node.fold_children(self) }
+    fn fold_impl_member(&mut self, node: ImplMember) -> ImplMember { node.fold_children(self) }
+    fn fold_impl_of_inherent(&mut self, node: ImplOfInherent) -> ImplOfInherent { node.fold_children(self) }
+    fn fold_impl_of_trait(&mut self, node: ImplOfTrait) -> ImplOfTrait { node.fold_children(self) }
+    fn fold_impl_of_trait_type(&mut self, node: ImplOfTraitType) -> ImplOfTraitType { node.fold_children(self) }
+    fn fold_impl_type(&mut self, node: ImplType) -> ImplType { node.fold_children(self) }
+    fn fold_item(&mut self, node: Item) -> Item { node.fold_children(self) }
+    fn fold_let(&mut self, node: Let) -> Let { node.fold_children(self) }
+    fn fold_lifetime(&mut self, node: Lifetime) -> Lifetime { node.fold_children(self) }
+    fn fold_loop(&mut self, node: Loop) -> Loop { node.fold_children(self) }
+    fn fold_macro_call(&mut self, node: MacroCall) -> MacroCall { node.fold_children(self) }
+    fn fold_macro_call_args(&mut self, node: MacroCallArgs) -> MacroCallArgs { node.fold_children(self) }
+    fn fold_match(&mut self, node: Match) -> Match { node.fold_children(self) }
+    fn fold_match_arm(&mut self, node: MatchArm) -> MatchArm { node.fold_children(self) }
+    fn fold_match_hand(&mut self, node: MatchHand) -> MatchHand { node.fold_children(self) }
+    fn fold_module(&mut self, node: Module) -> Module { node.fold_children(self) }
+    fn fold_named_argument(&mut self, node: NamedArgument) -> NamedArgument { node.fold_children(self) }
+    fn fold_number(&mut self, node: Number) -> Number { node.fold_children(self) }
+    fn fold_number_value(&mut self, node: NumberValue) -> NumberValue { node.fold_children(self) }
+    fn fold_number_binary(&mut self, node: NumberBinary) -> NumberBinary { node.fold_children(self) }
+    fn fold_number_decimal(&mut self, node: NumberDecimal) -> NumberDecimal { node.fold_children(self) }
+    fn fold_number_hexadecimal(&mut self, node: NumberHexadecimal) -> NumberHexadecimal { node.fold_children(self) }
+    fn fold_number_octal(&mut self, node: NumberOctal) -> NumberOctal { node.fold_children(self) }
+    fn fold_parenthetical(&mut self, node: Parenthetical) -> Parenthetical { node.fold_children(self) }
+    fn fold_path(&mut self, node: Path) -> Path { node.fold_children(self) }
+    fn fold_path_component(&mut self, node: PathComponent) -> PathComponent { node.fold_children(self) }
+    fn fold_pathed_ident(&mut self, node: PathedIdent) -> PathedIdent { node.fold_children(self) }
+    fn fold_pattern(&mut self, node: Pattern) -> Pattern { node.fold_children(self) }
+    fn fold_pattern_name(&mut self, node: PatternName) -> PatternName { node.fold_children(self) }
+    fn fold_pattern_bundle_member(&mut self, node: PatternBundleMember) -> PatternBundleMember { node.fold_children(self) }
+    fn fold_pattern_byte(&mut self, node: PatternByte) -> PatternByte { node.fold_children(self) }
+    fn fold_pattern_byte_string(&mut self, node: PatternByteString) -> PatternByteString { node.fold_children(self) }
+    fn fold_pattern_character(&mut self, node: PatternCharacter) -> PatternCharacter { node.fold_children(self) }
+    fn fold_pattern_ident(&mut self, node: PatternIdent) -> PatternIdent { node.fold_children(self) }
+    fn fold_pattern_kind(&mut self, node: PatternKind) -> PatternKind { node.fold_children(self) }
+    fn fold_pattern_macro_call(&mut self, node: PatternMacroCall) -> PatternMacroCall { node.fold_children(self) }
+    fn fold_pattern_number(&mut self, node: PatternNumber) -> PatternNumber { node.fold_children(self) }
+    fn fold_pattern_range_exclusive(&mut self, node: PatternRangeExclusive) -> PatternRangeExclusive { node.fold_children(self) }
+    fn fold_pattern_range_inclusive(&mut self, node: PatternRangeInclusive) -> PatternRangeInclusive { node.fold_children(self) }
+    fn fold_pattern_reference(&mut self, node: PatternReference) -> PatternReference { node.fold_children(self) }
+    fn fold_pattern_slice(&mut self, node: PatternSlice) -> PatternSlice { node.fold_children(self) }
+    fn fold_pattern_string(&mut self, node: PatternString) -> PatternString { node.fold_children(self) }
+    fn fold_pattern_struct(&mut self, node: PatternStruct) -> PatternStruct { node.fold_children(self) }
+    fn fold_pattern_struct_field(&mut self, node: PatternStructField) -> PatternStructField { node.fold_children(self) }
+    fn fold_pattern_struct_field_long(&mut self, node: PatternStructFieldLong) -> PatternStructFieldLong { node.fold_children(self) }
+    fn fold_pattern_struct_field_short(&mut self, node: PatternStructFieldShort) -> PatternStructFieldShort { node.fold_children(self) }
+    fn fold_pattern_tuple(&mut self, node: PatternTuple) -> PatternTuple { node.fold_children(self) }
+    fn fold_pattern_wildcard(&mut self, node: PatternWildcard) -> PatternWildcard { node.fold_children(self) }
+    fn fold_pattern_box(&mut self, node: PatternBox) -> PatternBox { node.fold_children(self) }
+    fn fold_range(&mut self, node: Range) -> Range { node.fold_children(self) }
+    fn fold_range_inclusive(&mut self, node: RangeInclusive) -> RangeInclusive { node.fold_children(self) }
+    fn fold_reference(&mut self, node: Reference) -> Reference { node.fold_children(self) }
+    fn fold_return(&mut self, node: Return) -> Return { node.fold_children(self) }
+    fn fold_self_argument(&mut self, node: SelfArgument) -> SelfArgument { node.fold_children(self) }
+    fn fold_self_argument_longhand(&mut self, node: SelfArgumentLonghand) -> SelfArgumentLonghand { node.fold_children(self) }
+    fn fold_self_argument_shorthand(&mut self, node: SelfArgumentShorthand) -> SelfArgumentShorthand { node.fold_children(self) }
+    fn fold_self_argument_shorthand_qualifier(&mut self, node: SelfArgumentShorthandQualifier) -> SelfArgumentShorthandQualifier { node.fold_children(self) }
+    fn fold_slice(&mut self, node: Slice) -> Slice { node.fold_children(self) }
+    fn fold_statement(&mut self, node: Statement) -> Statement { node.fold_children(self) }
+    fn fold_static(&mut self, node: Static) -> Static { node.fold_children(self) }
+    fn fold_string(&mut self, node: String) -> String { node.fold_children(self) }
+    fn fold_struct(&mut self, node: Struct) -> Struct { node.fold_children(self) }
+    fn fold_struct_definition_body(&mut self, node: StructDefinitionBody) -> StructDefinitionBody { node.fold_children(self) }
+    fn fold_struct_definition_body_brace(&mut self, node: StructDefinitionBodyBrace) -> StructDefinitionBodyBrace { node.fold_children(self) }
+    fn fold_struct_definition_body_tuple(&mut self, node: StructDefinitionBodyTuple) -> StructDefinitionBodyTuple { node.fold_children(self) }
+    fn fold_struct_definition_field_named(&mut self, node: StructDefinitionFieldNamed) -> StructDefinitionFieldNamed { node.fold_children(self) }
+    fn fold_struct_definition_field_unnamed(&mut self, node: StructDefinitionFieldUnnamed) -> StructDefinitionFieldUnnamed { node.fold_children(self) }
+    fn fold_struct_literal(&mut self, node: StructLiteral) -> StructLiteral { node.fold_children(self) }
+    fn fold_struct_literal_field(&mut self, node: StructLiteralField) -> StructLiteralField { node.fold_children(self) }
+    fn fold_trait(&mut self, node: Trait) -> Trait { node.fold_children(self) }
+    fn fold_trait_bound(&mut self, node: TraitBound) -> TraitBound { node.fold_children(self) }
+    fn fold_trait_bound_lifetime(&mut self, node: TraitBoundLifetime) -> TraitBoundLifetime { node.fold_children(self) }
+    fn fold_trait_bound_normal(&mut self, node: TraitBoundNormal) -> TraitBoundNormal { node.fold_children(self) }
+    fn fold_trait_bound_relaxed(&mut self, node: TraitBoundRelaxed) -> TraitBoundRelaxed { node.fold_children(self) }
+    fn fold_trait_bound_type(&mut self, node: TraitBoundType) -> TraitBoundType { node.fold_children(self) }
+    fn fold_trait_bounds(&mut self, node: TraitBounds) -> TraitBounds { node.fold_children(self) }
+    fn fold_trait_impl_argument(&mut self, node: TraitImplArgument) -> TraitImplArgument { node.fold_children(self) }
+    fn fold_trait_impl_argument_named(&mut self, node: TraitImplArgumentNamed) -> TraitImplArgumentNamed { node.fold_children(self) }
+    fn fold_trait_impl_function_header(&mut self, node: TraitImplFunctionHeader) -> TraitImplFunctionHeader { node.fold_children(self) }
+    fn fold_trait_member(&mut self, node: TraitMember) -> TraitMember { node.fold_children(self) }
+    fn fold_trait_member_const(&mut self, node: TraitMemberConst) -> TraitMemberConst { node.fold_children(self) }
+    fn fold_trait_member_function(&mut self, node: TraitMemberFunction) -> TraitMemberFunction { node.fold_children(self) }
+    fn fold_trait_member_type(&mut self, node: TraitMemberType) -> TraitMemberType { node.fold_children(self) }
+    fn fold_try_operator(&mut self, node: TryOperator) -> TryOperator { node.fold_children(self) }
+    fn fold_tuple(&mut self, node: Tuple) -> Tuple { node.fold_children(self) }
+    fn fold_turbofish(&mut self, node: Turbofish) -> Turbofish { node.fold_children(self) }
+    fn fold_type(&mut self, node: Type) -> Type { node.fold_children(self) }
+    fn fold_type_additional(&mut self, node: TypeAdditional) -> TypeAdditional { node.fold_children(self) }
+    fn fold_type_alias(&mut self, node: TypeAlias) -> TypeAlias { node.fold_children(self) }
+    fn fold_type_array(&mut self, node: TypeArray) -> TypeArray { node.fold_children(self) }
+    fn fold_type_disambiguation(&mut self, node: TypeDisambiguation) -> TypeDisambiguation { node.fold_children(self) }
+    fn fold_type_dyn_trait(&mut self, node: TypeDynTrait) -> TypeDynTrait { node.fold_children(self) }
+    fn fold_type_function(&mut self, node: TypeFunction) -> TypeFunction { node.fold_children(self) }
+    fn fold_type_function_argument(&mut self, node: TypeFunctionArgument) -> TypeFunctionArgument { node.fold_children(self) }
+    fn fold_type_function_argument_named(&mut self, node: TypeFunctionArgumentNamed) -> TypeFunctionArgumentNamed { node.fold_children(self) }
+    fn fold_type_generics(&mut self, node: TypeGenerics) -> TypeGenerics { node.fold_children(self) }
+    fn fold_type_generics_angle(&mut self, node: TypeGenericsAngle) -> TypeGenericsAngle { node.fold_children(self) }
+    fn fold_type_generics_angle_member(&mut self, node: TypeGenericsAngleMember) -> TypeGenericsAngleMember { node.fold_children(self) }
+    fn fold_type_generics_function(&mut self, node: TypeGenericsFunction) -> TypeGenericsFunction { node.fold_children(self) }
+    fn fold_type_higher_ranked_trait_bounds(&mut self, node: TypeHigherRankedTraitBounds) -> TypeHigherRankedTraitBounds { node.fold_children(self) }
+    fn fold_type_higher_ranked_trait_bounds_child(&mut self, node: TypeHigherRankedTraitBoundsChild) -> TypeHigherRankedTraitBoundsChild { node.fold_children(self) }
+    fn fold_type_impl_trait(&mut self, node: TypeImplTrait) -> TypeImplTrait { node.fold_children(self) }
+    fn fold_type_kind(&mut self, node: TypeKind) -> TypeKind { node.fold_children(self) }
+    fn fold_type_named(&mut self, node: TypeNamed) -> TypeNamed { node.fold_children(self) }
+    fn fold_type_named_component(&mut self, node: TypeNamedComponent) -> TypeNamedComponent { node.fold_children(self) }
+    fn fold_type_pointer(&mut self, node: TypePointer) -> TypePointer { node.fold_children(self) }
+    fn fold_type_reference(&mut self, node: TypeReference) -> TypeReference { node.fold_children(self) }
+    fn fold_type_reference_kind(&mut self, node: TypeReferenceKind) -> TypeReferenceKind { node.fold_children(self) }
+    fn fold_type_slice(&mut self, node: TypeSlice) -> TypeSlice { node.fold_children(self) }
+    fn fold_type_tuple(&mut self, node: TypeTuple) -> TypeTuple { node.fold_children(self) }
+    fn fold_unary(&mut self, node: Unary) -> Unary { node.fold_children(self) }
+    fn fold_unary_op(&mut self, node: UnaryOp) -> UnaryOp { node.fold_children(self) }
+    fn fold_union(&mut self, node: Union) -> Union { node.fold_children(self) }
+    fn fold_unsafe_block(&mut self, node: UnsafeBlock) -> UnsafeBlock { node.fold_children(self) }
+    fn fold_use(&mut self, node: Use) -> Use { node.fold_children(self) }
+    fn fold_use_tail(&mut self, node: UseTail) -> UseTail { node.fold_children(self) }
+    fn fold_use_tail_glob(&mut self, node: UseTailGlob) -> UseTailGlob { node.fold_children(self) }
+    fn fold_use_tail_ident(&mut self, node: UseTailIdent) -> UseTailIdent { node.fold_children(self) }
+    fn fold_use_tail_multi(&mut self, node: UseTailMulti) -> UseTailMulti { node.fold_children(self) }
+    fn fold_value(&mut self, node: Value) -> Value { node.fold_children(self) }
+    fn fold_visibility(&mut self, node: Visibility) -> Visibility { node.fold_children(self) }
+    fn fold_where(&mut self, node: Where) -> Where { node.fold_children(self) }
+    fn fold_where_kind(&mut self, node: WhereKind) -> WhereKind { node.fold_children(self) }
+    fn fold_where_lifetime(&mut self, node: WhereLifetime) -> WhereLifetime { node.fold_children(self) }
+    fn fold_where_type(&mut self, node: WhereType) -> WhereType { node.fold_children(self) }
+    fn fold_while(&mut self, node: While) -> While { node.fold_children(self) }
+    fn fold_while_let(&mut self, node: WhileLet) -> WhileLet { node.fold_children(self) }
+    fn fold_whitespace(&mut self, node: Whitespace) -> Whitespace { node.fold_children(self) }
+}
+
+// The derive creates `NodeRef`, which mirrors this enum but borrows
+// each node, and `NodeKind`.
+#[allow(dead_code)]
+#[derive(NodeRef)]
+enum Node {
+    Argument(Argument),
+    Array(Array),
+    ArrayExplicit(ArrayExplicit),
+    ArrayRepeated(ArrayRepeated),
+    AsType(AsType),
+    Ascription(Ascription),
+    AssociatedType(AssociatedType),
+    AsyncBlock(AsyncBlock),
+    Attribute(Attribute),
+    AttributeContaining(AttributeContaining),
+    AttributedEnumVariant(Attributed<EnumVariant>),
+    AttributedExpression(Attributed<Expression>),
+    AttributedExternBlockMember(Attributed<ExternBlockMember>),
+    AttributedGenericDeclarationLifetime(Attributed<GenericDeclarationLifetime>),
+    AttributedGenericDeclarationType(Attributed<GenericDeclarationType>),
+    AttributedImplMember(Attributed<ImplMember>),
+    AttributedItem(Attributed<Item>),
+    AttributedStructDefinitionFieldNamed(Attributed<StructDefinitionFieldNamed>),
+    AttributedStructDefinitionFieldUnnamed(Attributed<StructDefinitionFieldUnnamed>),
+    AttributedTraitMember(Attributed<TraitMember>),
+    Await(Await),
+    Binary(Binary),
+    BinaryOp(BinaryOp),
+    Block(Block),
+    Break(Break),
+    Byte(Byte),
+    ByteString(ByteString),
+    Call(Call),
+    Character(Character),
+    Closure(Closure),
+    ClosureArg(ClosureArg),
+    Comment(Comment),
+    Const(Const),
+    Continue(Continue),
+    Crate(Crate),
+    Dereference(Dereference),
+    Disambiguation(Disambiguation),
+    Enum(Enum),
+    EnumVariant(EnumVariant),
+    EnumVariantBody(EnumVariantBody),
+    EnumVariantBodyUnit(EnumVariantBodyUnit),
+    Expression(Expression),
+    ExpressionBox(ExpressionBox),
+    ExternBlock(ExternBlock),
+    ExternBlockMember(ExternBlockMember),
+    ExternBlockMemberFunction(ExternBlockMemberFunction),
+    ExternBlockMemberFunctionArgument(ExternBlockMemberFunctionArgument),
+    ExternBlockMemberFunctionArgumentNamed(ExternBlockMemberFunctionArgumentNamed),
+    ExternBlockMemberFunctionArgumentVariadic(ExternBlockMemberFunctionArgumentVariadic),
+    ExternBlockMemberStatic(ExternBlockMemberStatic),
+    FieldAccess(FieldAccess),
+    File(File),
+    ForLoop(ForLoop),
+    Function(Function),
+    FunctionHeader(FunctionHeader),
+    FunctionQualifiers(FunctionQualifiers),
+    GenericDeclarationLifetime(GenericDeclarationLifetime),
+    GenericDeclarationType(GenericDeclarationType),
+    GenericDeclarations(GenericDeclarations),
+    Ident(Ident),
+    If(If),
+    IfLet(IfLet),
+    Impl(Impl),
+    ImplConst(ImplConst),
+    ImplFunction(ImplFunction),
+    ImplKind(ImplKind),
+    ImplMember(ImplMember),
+    ImplOfInherent(ImplOfInherent),
+    ImplOfTrait(ImplOfTrait),
+    ImplOfTraitType(ImplOfTraitType),
+    ImplType(ImplType),
+    Item(Item),
+    Let(Let),
+    Lifetime(Lifetime),
+    Loop(Loop),
+    MacroCall(MacroCall),
+    MacroCallArgs(MacroCallArgs),
+    Match(Match),
+    MatchArm(MatchArm),
+    MatchHand(MatchHand),
+    Module(Module),
+    NamedArgument(NamedArgument),
+    Number(Number),
+    NumberValue(NumberValue),
+    NumberBinary(NumberBinary),
+    NumberDecimal(NumberDecimal),
+    NumberHexadecimal(NumberHexadecimal),
+    NumberOctal(NumberOctal),
+    Parenthetical(Parenthetical),
+    Path(Path),
+    PathComponent(PathComponent),
+    PathedIdent(PathedIdent),
+    Pattern(Pattern),
+    PatternName(PatternName),
+    PatternBundleMember(PatternBundleMember),
+    PatternByte(PatternByte),
+    PatternByteString(PatternByteString),
+    PatternCharacter(PatternCharacter),
+    PatternIdent(PatternIdent),
+    PatternKind(PatternKind),
+    PatternMacroCall(PatternMacroCall),
+    PatternNumber(PatternNumber),
+    PatternRangeExclusive(PatternRangeExclusive),
+    PatternRangeInclusive(PatternRangeInclusive),
+    PatternReference(PatternReference),
+    PatternSlice(PatternSlice),
+    PatternString(PatternString),
+    PatternStruct(PatternStruct),
+    PatternStructField(PatternStructField),
+    PatternStructFieldLong(PatternStructFieldLong),
+    PatternStructFieldShort(PatternStructFieldShort),
+    PatternTuple(PatternTuple),
+    PatternWildcard(PatternWildcard),
+    PatternBox(PatternBox),
+    Range(Range),
+    RangeInclusive(RangeInclusive),
+    Reference(Reference),
+    Return(Return),
+    SelfArgument(SelfArgument),
+    SelfArgumentLonghand(SelfArgumentLonghand),
+    SelfArgumentShorthand(SelfArgumentShorthand),
+    SelfArgumentShorthandQualifier(SelfArgumentShorthandQualifier),
+    Slice(Slice),
+    Statement(Statement),
+    Static(Static),
+    String(String),
+    Struct(Struct),
+    StructDefinitionBody(StructDefinitionBody),
+    StructDefinitionBodyBrace(StructDefinitionBodyBrace),
+    StructDefinitionBodyTuple(StructDefinitionBodyTuple),
+    StructDefinitionFieldNamed(StructDefinitionFieldNamed),
+    StructDefinitionFieldUnnamed(StructDefinitionFieldUnnamed),
+    StructLiteral(StructLiteral),
+    StructLiteralField(StructLiteralField),
+    Trait(Trait),
+    TraitBound(TraitBound),
+    TraitBoundLifetime(TraitBoundLifetime),
+    TraitBoundNormal(TraitBoundNormal),
+    TraitBoundRelaxed(TraitBoundRelaxed),
+    TraitBoundType(TraitBoundType),
+    TraitBounds(TraitBounds),
+    TraitImplArgument(TraitImplArgument),
+    TraitImplArgumentNamed(TraitImplArgumentNamed),
+    TraitImplFunctionHeader(TraitImplFunctionHeader),
+    TraitMember(TraitMember),
+    TraitMemberConst(TraitMemberConst),
+    TraitMemberFunction(TraitMemberFunction),
+    TraitMemberType(TraitMemberType),
+    TryOperator(TryOperator),
+    Tuple(Tuple),
+    Turbofish(Turbofish),
+    Type(Type),
+    TypeAdditional(TypeAdditional),
+    TypeAlias(TypeAlias),
+    TypeArray(TypeArray),
+    TypeDisambiguation(TypeDisambiguation),
+    TypeDynTrait(TypeDynTrait),
+    TypeFunction(TypeFunction),
+    TypeFunctionArgument(TypeFunctionArgument),
+    TypeFunctionArgumentNamed(TypeFunctionArgumentNamed),
+    TypeGenerics(TypeGenerics),
+    TypeGenericsAngle(TypeGenericsAngle),
+    TypeGenericsAngleMember(TypeGenericsAngleMember),
+    TypeGenericsFunction(TypeGenericsFunction),
+    TypeHigherRankedTraitBounds(TypeHigherRankedTraitBounds),
+    TypeHigherRankedTraitBoundsChild(TypeHigherRankedTraitBoundsChild),
+    TypeImplTrait(TypeImplTrait),
+    TypeKind(TypeKind),
+    TypeNamed(TypeNamed),
+    TypeNamedComponent(TypeNamedComponent),
+    TypePointer(TypePointer),
+    TypeReference(TypeReference),
+    TypeReferenceKind(TypeReferenceKind),
+    TypeSlice(TypeSlice),
+    TypeTuple(TypeTuple),
+    Unary(Unary),
+    UnaryOp(UnaryOp),
+    Union(Union),
+    UnsafeBlock(UnsafeBlock),
+    Use(Use),
+    UseTail(UseTail),
+    UseTailGlob(UseTailGlob),
+    UseTailIdent(UseTailIdent),
+    UseTailMulti(UseTailMulti),
+    Value(Value),
+    Visibility(Visibility),
+    Where(Where),
+    WhereKind(WhereKind),
+    WhereLifetime(WhereLifetime),
+    WhereType(WhereType),
+    While(While),
+    WhileLet(WhileLet),
+    Whitespace(Whitespace),
+}
+
+// Gathers the nodes that are the direct children of another node.
+trait ChildNodes {
+    // Add this value if it is a node, otherwise any nodes it contains
+    fn push_nodes<'a>(&'a self, &mut Vec<NodeRef<'a>>);
+
+    // Add the nodes that are direct children of this node
+    fn push_child_nodes<'a>(&'a self, &mut Vec<NodeRef<'a>>);
+}
+
+impl<T> ChildNodes for Box<T>
+    where T: ChildNodes
+{
+    fn push_nodes<'a>(&'a self, nodes: &mut Vec<NodeRef<'a>>) {
+        (**self).push_nodes(nodes)
+    }
+
+    fn push_child_nodes<'a>(&'a self, nodes: &mut Vec<NodeRef<'a>>) {
+        (**self).push_child_nodes(nodes)
+    }
+}
+
+impl<T> ChildNodes for Option<T>
+    where T: ChildNodes
+{
+    fn push_nodes<'a>(&'a self, nodes: &mut Vec<NodeRef<'a>>) {
+        for i in self {
+            i.push_nodes(nodes)
+        }
+    }
+
+    fn push_child_nodes<'a>(&'a self, nodes: &mut Vec<NodeRef<'a>>) {
+        for i in self {
+            i.push_child_nodes(nodes)
+        }
+    }
+}
+
+impl<T> ChildNodes for Vec<T>
+    where T: ChildNodes
+{
+    fn push_nodes<'a>(&'a self, nodes: &mut Vec<NodeRef<'a>>) {
+        for i in self {
+            i.push_nodes(nodes)
+        }
+    }
+
+    fn push_child_nodes<'a>(&'a self, nodes: &mut Vec<NodeRef<'a>>) {
+        for i in self {
+            i.push_child_nodes(nodes)
+        }
+    }
+}
+
+impl<T> ChildNodes for Attributed<T>
+    where T: ChildNodes,
+          for<'a> NodeRef<'a>: From<&'a Attributed<T>>,
+{
+    fn push_nodes<'a>(&'a self, nodes: &mut Vec<NodeRef<'a>>) {
+        nodes.push(NodeRef::from(self))
+    }
+
+    fn push_child_nodes<'a>(&'a self, nodes: &mut Vec<NodeRef<'a>>) {
+        self.attributes.push_nodes(nodes);
+        self.value.push_nodes(nodes);
+    }
+}
+
+macro_rules! child_nodes_terminal {
+    ($($typ:ty),*) => {
+        $(
+            impl ChildNodes for $typ {
+                fn push_nodes<'a>(&'a self, _nodes: &mut Vec<NodeRef<'a>>) {}
+                fn push_child_nodes<'a>(&'a self, _nodes: &mut Vec<NodeRef<'a>>) {}
+            }
+        )*
+    };
+}
+
+child_nodes_terminal!(Extent, FieldName, PatternRangeComponent, TypePointerKind);
+
+/// Like a `Visitor`, but every node is passed along with its
+/// ancestors, outermost first.
+pub trait AncestorVisitor<'a> {
+    fn visit(&mut self, NodeRef<'a>, &[NodeRef<'a>]) -> Control { Control::Continue }
+    fn exit(&mut self, NodeRef<'a>, &[NodeRef<'a>]) {}
+}
+
+impl<'a> NodeRef<'a> {
+    /// Iterate over this node and all of its descendants, parents
+    /// before children.
+    pub fn walk(&self) -> Walk<'a> {
+        Walk { stack: vec![*self] }
+    }
+
+    /// Visit this node and all of its descendants. The ancestors
+    /// start with this node, not with the `File`, if this isn't the
+    /// root.
+    pub fn visit_with_ancestors<V>(self, v: &mut V) -> Control
+        where V: AncestorVisitor<'a>
+    {
+        let mut ancestors = Vec::new();
+        visit_with_ancestors(self, &mut ancestors, v)
+    }
+
+}
+
+impl File {
+    /// The chain of nodes containing the byte at `offset`, from the
+    /// `File` down to the innermost node.
+    pub fn node_at(&self, offset: usize) -> Vec<NodeRef> {
+        self.nodes_covering((offset, offset + 1))
+    }
+
+    /// The chain of nodes whose extent contains all of `extent`, from
+    /// the `File` down to the innermost node.
+    ///
+    /// The `File` is always the first node, even when `extent` lies
+    /// outside of it.
+    pub fn nodes_covering(&self, extent: Extent) -> Vec<NodeRef> {
+        let mut chain = vec![NodeRef::from(self)];
+
+        loop {
+            let next = {
+                let innermost = chain[chain.len() - 1];
+                innermost.children().into_iter().find(|child| {
+                    let (start, end) = child.extent();
+                    start < end && start <= extent.0 && extent.1 <= end
+                })
+            };
+
+            match next {
+                Some(child) => chain.push(child),
+                None => return chain,
+            }
+        }
+    }
+}
+
+/// A pre-order iterator over a node and its descendants.
+#[derive(Debug)]
+pub struct Walk<'a> {
+    stack: Vec<NodeRef<'a>>,
+}
+
+impl<'a> Iterator for Walk<'a> {
+    type Item = NodeRef<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        self.stack.extend(node.children().into_iter().rev());
+        Some(node)
+    }
+}
+
+fn visit_with_ancestors<'a, V>(node: NodeRef<'a>, ancestors: &mut Vec<NodeRef<'a>>, v: &mut V) -> Control
+    where V: AncestorVisitor<'a>
+{
+    match v.visit(node, ancestors) {
+        Control::Continue => {
+            ancestors.push(node);
+            for child in node.children() {
+                if visit_with_ancestors(child, ancestors, v) == Control::Stop {
+                    return Control::Stop;
+                }
+            }
+            ancestors.pop();
+        }
+        Control::Break => {}
+        Control::Stop => return Control::Stop,
+    }
+    v.exit(node, ancestors);
+    Control::Continue
+}
+
 // --------------------------------------------------
 
+// Counts one level of nesting while the parser runs, failing if that
+// exceeds the limit
+fn nested<'s, F, T>(f: F) -> impl FnOnce(&mut Master<'s>, Point<'s>) -> Progress<'s, T>
+    where F: FnOnce(&mut Master<'s>, Point<'s>) -> Progress<'s, T>
+{
+    move |pm, pt| {
+        let depth = pm.state.depth;
+        if depth >= pm.state.max_depth {
+            return Progress::failure(pt, Error::NestingTooDeep);
+        }
+
+        pm.state.depth += 1;
+        let r = f(pm, pt);
+        pm.state.depth = depth;
+        r
+    }
+}
+
+// Moves the result to the heap. Alternatives of large types, such as
+// items and expressions, are boxed so that the stack frames on the
+// recursive path stay small.
+fn boxed<'s, F, T>(f: F) -> impl FnOnce(&mut Master<'s>, Point<'s>) -> Progress<'s, Box<T>>
+    where F: FnOnce(&mut Master<'s>, Point<'s>) -> Progress<'s, T>
+{
+    move |pm, pt| f(pm, pt).map(Box::new)
+}
+
+// Reuses the result of a previous attempt at parsing the rule at the
+// same point. Failures inside of the rule were recorded by the first
+// attempt and don't need to be recorded again.
+fn memoized<'s, F, T>(table: fn(&mut Memo) -> &mut MemoTable<T>, f: F) ->
+    impl FnOnce(&mut Master<'s>, Point<'s>) -> Progress<'s, T>
+    where F: FnOnce(&mut Master<'s>, Point<'s>) -> Progress<'s, T>,
+          T: Clone,
+{
+    move |pm, pt| {
+        let key = (pt.offset, pt.sub_offset, pm.state.depth, pm.state.expression_ambiguity);
+
+        match pm.state.memo {
+            None => return f(pm, pt),
+            Some(ref mut memo) => {
+                if let Some(entry) = table(memo).get(&key) {
+                    let (offset, sub_offset) = entry.end;
+                    let end = TokenPoint { offset, sub_offset, s: &pt.s[offset - pt.offset..] };
+                    return match entry.result {
+                        Ok(ref v) => Progress::success(end, v.clone()),
+                        Err(e) => Progress::failure(end, e),
+                    };
+                }
+            }
+        }
+
+        let r = f(pm, pt);
+
+        // Failures may be reported before the point where the rule
+        // started; those can't be replayed from here.
+        if r.point.offset >= pt.offset {
+            if let Some(ref mut memo) = pm.state.memo {
+                let result = match r.status {
+                    peresil::Status::Success(ref v) => Ok(v.clone()),
+                    peresil::Status::Failure(e) => Err(e),
+                };
+                table(memo).insert(key, MemoEntry { end: r.point.location(), result });
+            }
+        }
+
+        r
+    }
+}
+
 fn ext<'s, F, T>(f: F) -> impl FnOnce(&mut Master<'s>, Point<'s>) -> Progress<'s, Extent>
     where F: FnOnce(&mut Master<'s>, Point<'s>) -> Progress<'s, T>
 {
@@ -2563,6 +4603,9 @@ fn zero_or_more_tailed_append<'s, S, F, T, U>(append_to: Tailed<T>, sep: S, f: F
         let mut tailed = append_to;
         loop {
             match parse_tailed(&sep, &f, pm, pt) {
+                TailedState::Nothing(pt, Error::NestingTooDeep) => {
+                    return Progress::failure(pt, Error::NestingTooDeep);
+                }
                 TailedState::Nothing(pt, _) => {
                     return Progress::success(pt, tailed);
                 }
@@ -2630,6 +4673,9 @@ fn zero_or_more_tailed_values_resume<'s, S, F, T, U>(sep: S, f: F) ->
         };
 
         match one_or_more_tailed_values(sep, f)(pm, pt) {
+            Progress { status: peresil::Status::Failure(Error::NestingTooDeep), point } => {
+                Progress::failure(point, Error::NestingTooDeep)
+            }
             Progress { status: peresil::Status::Failure(_), .. } => {
                 // We parsed the separator, but not another value. Rewind to before the separator
                 Progress::success(spt, Vec::new())
@@ -2649,6 +4695,9 @@ fn zero_or_more_implicitly_tailed_append<'s, S, F, T, U>(append_to: Tailed<T>, s
         let mut tailed = append_to;
         loop {
             match parse_tailed(&sep, &f, pm, pt) {
+                TailedState::Nothing(pt, Error::NestingTooDeep) => {
+                    return Progress::failure(pt, Error::NestingTooDeep);
+                }
                 TailedState::Nothing(pt, _) => {
                     return Progress::success(pt, tailed);
                 }
@@ -2698,11 +4747,23 @@ fn one_or_more_tailed<'s, S, F, T, U>(sep: S, f: F) ->
     impl FnOnce(&mut Master<'s>, Point<'s>) -> Progress<'s, Tailed<T>>
     where S: Fn(&mut Master<'s>, Point<'s>) -> Progress<'s, U>,
           F: Fn(&mut Master<'s>, Point<'s>) -> Progress<'s, T>
+{
+    move |pm, pt| one_or_more_tailed_first(&sep, &f, &f)(pm, pt)
+}
+
+// The first value may be parsed differently from the rest
+// crate::foo::bar
+// ^^^^^
+fn one_or_more_tailed_first<'s, S, F1, F, T, U>(sep: S, first: F1, f: F) ->
+    impl FnOnce(&mut Master<'s>, Point<'s>) -> Progress<'s, Tailed<T>>
+    where S: Fn(&mut Master<'s>, Point<'s>) -> Progress<'s, U>,
+          F1: Fn(&mut Master<'s>, Point<'s>) -> Progress<'s, T>,
+          F: Fn(&mut Master<'s>, Point<'s>) -> Progress<'s, T>
 {
     move |pm, pt| {
         let mut tailed = Tailed::default();
 
-        match parse_tailed(&sep, &f, pm, pt) {
+        match parse_tailed(&sep, &first, pm, pt) {
             TailedState::Nothing(pt, f) => {
                 return Progress::failure(pt, f);
             }
@@ -2727,6 +4788,15 @@ fn one_or_more_tailed_values<'s, S, F, T, U>(sep: S, f: F) ->
     map(one_or_more_tailed(sep, f), |t| t.values)
 }
 
+fn one_or_more_tailed_values_first<'s, S, F1, F, T, U>(sep: S, first: F1, f: F) ->
+    impl FnOnce(&mut Master<'s>, Point<'s>) -> Progress<'s, Vec<T>>
+    where S: Fn(&mut Master<'s>, Point<'s>) -> Progress<'s, U>,
+          F1: Fn(&mut Master<'s>, Point<'s>) -> Progress<'s, T>,
+          F: Fn(&mut Master<'s>, Point<'s>) -> Progress<'s, T>
+{
+    map(one_or_more_tailed_first(sep, first, f), |t| t.values)
+}
+
 // TODO: generic enough to move to library?
 pub fn not<P, E, S, F, T>
     (parser: F, error: E)
@@ -2771,22 +4841,23 @@ pub fn peek<P, E, S, F, T>
 
 fn item<'s>(pm: &mut Master<'s>, pt: Point<'s>) -> Progress<'s, Item> {
     pm.alternate(pt)
-        .one(map(attribute_containing, Item::AttributeContaining))
-        .one(map(p_const, Item::Const))
-        .one(map(extern_crate, Item::ExternCrate))
-        .one(map(extern_block, Item::ExternBlock))
-        .one(map(function, Item::Function))
-        .one(map(item_macro_call, Item::MacroCall))
-        .one(map(module, Item::Module))
-        .one(map(p_enum, Item::Enum))
-        .one(map(p_impl, Item::Impl))
-        .one(map(p_static, Item::Static))
-        .one(map(p_struct, Item::Struct))
-        .one(map(p_trait, Item::Trait))
-        .one(map(p_union, Item::Union))
-        .one(map(p_use, Item::Use))
-        .one(map(type_alias, Item::TypeAlias))
+        .one(boxed(map(attribute_containing, Item::AttributeContaining)))
+        .one(boxed(map(p_const, Item::Const)))
+        .one(boxed(map(extern_crate, Item::ExternCrate)))
+        .one(boxed(map(extern_block, Item::ExternBlock)))
+        .one(boxed(map(function, Item::Function)))
+        .one(boxed(map(item_macro_call, Item::MacroCall)))
+        .one(boxed(map(module, Item::Module)))
+        .one(boxed(map(p_enum, Item::Enum)))
+        .one(boxed(map(p_impl, Item::Impl)))
+        .one(boxed(map(p_static, Item::Static)))
+        .one(boxed(map(p_struct, Item::Struct)))
+        .one(boxed(map(p_trait, Item::Trait)))
+        .one(boxed(map(p_union, Item::Union)))
+        .one(boxed(map(p_use, Item::Use)))
+        .one(boxed(map(type_alias, Item::TypeAlias)))
         .finish()
+        .map(|item| *item)
 }
 
 macro_rules! shim {
@@ -2819,14 +4890,19 @@ shims! [
     (byte_string, Token::into_byte_string, Error::ExpectedByteString),
     (byte_string_raw, Token::into_byte_string_raw, Error::ExpectedByteStringRaw),
 
+    (end_of_file, Token::into_end_of_file, Error::ExpectedEndOfFile),
+
     // Keywords
     (kw_as, Token::into_as, Error::ExpectedAs),
+    (kw_async, Token::into_async, Error::ExpectedAsync),
+    (kw_await, Token::into_await, Error::ExpectedAwait),
     (kw_box, Token::into_box, Error::ExpectedBox),
     (kw_break, Token::into_break, Error::ExpectedBreak),
     (kw_const, Token::into_const, Error::ExpectedConst),
     (kw_continue, Token::into_continue, Error::ExpectedContinue),
     (kw_crate, Token::into_crate, Error::ExpectedCrate),
     (kw_default, Token::into_default, Error::ExpectedDefault),
+    (kw_dyn, Token::into_dyn, Error::ExpectedDyn),
     (kw_else, Token::into_else, Error::ExpectedElse),
     (kw_enum, Token::into_enum, Error::ExpectedEnum),
     (kw_extern, Token::into_extern, Error::ExpectedExtern),
@@ -2848,6 +4924,7 @@ shims! [
     (kw_static, Token::into_static, Error::ExpectedStatic),
     (kw_struct, Token::into_struct, Error::ExpectedStruct),
     (kw_trait, Token::into_trait, Error::ExpectedTrait),
+    (kw_try, Token::into_try, Error::ExpectedTry),
     (kw_type, Token::into_type, Error::ExpectedType),
     (kw_union, Token::into_union, Error::ExpectedUnion),
     (kw_unsafe, Token::into_unsafe, Error::ExpectedUnsafe),
@@ -2919,8 +4996,9 @@ fn token<'s, F, T>(token_convert: F, error: Error) ->
         };
 
         let token = match pt.sub_offset {
-            Some(sub_offset) => {
-                split(original_token, sub_offset).expect("Cannot resume a split token").1
+            Some(sub_offset) => match split(original_token, sub_offset) {
+                Some((_, tail)) => tail,
+                None => return Progress::failure(pt, Error::CannotResumeSplitToken),
             },
             None => original_token,
         };
@@ -3055,6 +5133,7 @@ fn function_qualifiers<'s>(pm: &mut Master<'s>, pt: Point<'s>) ->
         spt        = point;
         is_default = optional(ext(kw_default));
         is_const   = optional(ext(kw_const));
+        is_async   = optional(ext(kw_async_2018));
         is_unsafe  = optional(ext(kw_unsafe));
         is_extern  = optional(function_qualifier_extern);
     }, |pm: &mut Master, pt| {
@@ -3067,6 +5146,7 @@ fn function_qualifiers<'s>(pm: &mut Master<'s>, pt: Point<'s>) ->
             extent: pm.state.ex(spt, pt),
             is_default,
             is_const,
+            is_async,
             is_unsafe,
             is_extern,
             abi,
@@ -3084,6 +5164,13 @@ fn function_qualifier_extern<'s>(pm: &mut Master<'s>, pt: Point<'s>) ->
 }
 
 fn ident<'s>(pm: &mut Master<'s>, pt: Point<'s>) -> Progress<'s, Ident> {
+    pm.alternate(pt)
+        .one(ident_plain)
+        .one(ident_edition_keyword)
+        .finish()
+}
+
+fn ident_plain<'s>(pm: &mut Master<'s>, pt: Point<'s>) -> Progress<'s, Ident> {
     pm.alternate(pt)
         .one(kw_default)
         .one(kw_self_ident)
@@ -3094,6 +5181,50 @@ fn ident<'s>(pm: &mut Master<'s>, pt: Point<'s>) -> Progress<'s, Ident> {
         .map_err(|_| Error::ExpectedIdent)
 }
 
+// `crate` is only an identifier as the first component of a longer path
+fn ident_crate<'s>(pm: &mut Master<'s>, pt: Point<'s>) -> Progress<'s, Ident> {
+    sequence!(pm, pt, {
+        extent = kw_crate;
+        _      = peek(double_colon);
+    }, |_, _| Ident { extent })
+}
+
+fn path_start_ident<'s>(pm: &mut Master<'s>, pt: Point<'s>) -> Progress<'s, Ident> {
+    pm.alternate(pt)
+        .one(ident)
+        .one(ident_crate)
+        .finish()
+}
+
+// Keywords added by Rust 2018 are identifiers in Rust 2015
+fn ident_edition_keyword<'s>(pm: &mut Master<'s>, pt: Point<'s>) -> Progress<'s, Ident> {
+    let keyword = pm.alternate(pt)
+        .one(kw_async)
+        .one(kw_await)
+        .one(kw_dyn)
+        .one(kw_try)
+        .finish();
+
+    match pm.state.edition {
+        Edition::Rust2015 => keyword.map(|extent| Ident { extent }).map_err(|_| Error::ExpectedIdent),
+        Edition::Rust2018 => keyword.and_then(pt, |_| Err(Error::ReservedKeyword)),
+    }
+}
+
+fn kw_async_2018<'s>(pm: &mut Master<'s>, pt: Point<'s>) -> Progress<'s, Extent> {
+    match pm.state.edition {
+        Edition::Rust2015 => Progress::failure(pt, Error::ExpectedAsync),
+        Edition::Rust2018 => kw_async(pm, pt),
+    }
+}
+
+fn kw_await_2018<'s>(pm: &mut Master<'s>, pt: Point<'s>) -> Progress<'s, Extent> {
+    match pm.state.edition {
+        Edition::Rust2015 => Progress::failure(pt, Error::ExpectedAwait),
+        Edition::Rust2018 => kw_await(pm, pt),
+    }
+}
+
 fn generic_declarations<'s>(pm: &mut Master<'s>, pt: Point<'s>) -> Progress<'s, GenericDeclarations> {
     sequence!(pm, pt, {
         spt       = point;
@@ -3205,10 +5336,16 @@ fn self_argument_qualifier<'s>(pm: &mut Master<'s>, pt: Point<'s>) ->
 
 fn function_argument<'s>(pm: &mut Master<'s>, pt: Point<'s>) -> Progress<'s, Argument> {
     sequence!(pm, pt, {
+        spt  = point;
         name = pattern;
         _    = colon;
         typ  = typ;
-    }, |_, _| Argument::Named(NamedArgument { name, typ, whitespace: Vec::new() }))
+    }, |pm: &mut Master, pt| Argument::Named(NamedArgument {
+        extent: pm.state.ex(spt, pt),
+        name,
+        typ,
+        whitespace: Vec::new(),
+    }))
 }
 
 fn function_return_type<'s>(pm: &mut Master<'s>, pt: Point<'s>) -> Progress<'s, Type> {
@@ -3310,7 +5447,7 @@ fn block<'s>(pm: &mut Master<'s>, pt: Point<'s>) -> Progress<'s, Block> {
     sequence!(pm, pt, {
         spt               = point;
         _                 = left_curly;
-        (mut stmts, term) = zero_or_more_implicitly_tailed_values_terminated(semicolon, statement);
+        (mut stmts, term) = nested(zero_or_more_implicitly_tailed_values_terminated(semicolon, statement));
         _                 = right_curly;
     }, |pm: &mut Master, pt| {
         let expr = if !term && stmts.last().map_or(false, Statement::is_expression) {
@@ -3330,10 +5467,11 @@ fn block<'s>(pm: &mut Master<'s>, pt: Point<'s>) -> Progress<'s, Block> {
 
 fn statement<'s>(pm: &mut Master<'s>, pt: Point<'s>) -> Progress<'s, Statement> {
     pm.alternate(pt)
-        .one(map(statement_expression, Statement::Expression))
-        .one(map(attributed(item), Statement::Item))
-        .one(map(statement_empty, Statement::Empty))
+        .one(boxed(map(statement_expression, Statement::Expression)))
+        .one(boxed(map(attributed(item), Statement::Item)))
+        .one(boxed(map(statement_empty, Statement::Empty)))
         .finish()
+        .map(|statement| *statement)
 }
 
 fn statement_empty<'s>(pm: &mut Master<'s>, pt: Point<'s>) -> Progress<'s, Extent> {
@@ -3361,8 +5499,66 @@ fn item_macro_call<'s>(pm: &mut Master<'s>, pt: Point<'s>) -> Progress<'s, Macro
         name = ident;
         _    = bang;
         arg  = optional(ident);
+        apt  = point;
         args = item_macro_call_args;
-    }, |pm: &mut Master, pt| MacroCall { extent: pm.state.ex(spt, pt), name, arg, args })
+    }, |pm: &mut Master, pt| {
+        let extent = pm.state.ex(spt, pt);
+        pm.state.record_macro_args(extent, apt);
+        MacroCall { extent, name, arg, args, expressions: None }
+    })
+}
+
+// Macros such as `println!` and `vec!` take expressions separated by
+// commas. When requested, try to parse the arguments of the calls in
+// an item that way; the arguments of other macros are left alone.
+struct MacroArgs<'s: 'pm, 'pm> {
+    pm: &'pm mut Master<'s>,
+    tokens: &'s [Token],
+}
+
+impl<'s, 'pm> VisitorMut for MacroArgs<'s, 'pm> {
+    fn visit_macro_call_mut(&mut self, call: &mut MacroCall) -> Control {
+        // Calls inside of the arguments are visited next
+        call.expressions = macro_call_expressions(self.pm, self.tokens, call);
+        Control::Continue
+    }
+}
+
+fn macro_call_expressions<'s>(pm: &mut Master<'s>, tokens: &'s [Token], call: &MacroCall) ->
+    Option<Vec<Attributed<Expression>>>
+{
+    let (offset, depth) = match pm.state.macro_args.get(&call.extent.0) {
+        Some(&v) => v,
+        None => return None,
+    };
+
+    // Skip the opening delimiter and stop before the closing one
+    let pt = Point::new(tokens).advance_by(offset + 1);
+    let base_depth = pm.state.depth;
+    let invalid_extent = pm.state.invalid_extent.get();
+    pm.state.depth = depth;
+    let r = zero_or_more_tailed_values(comma, expression)(pm, pt);
+    let r = pm.finish(r);
+    pm.state.depth = base_depth;
+
+    // Arguments that aren't expressions don't fail the parse
+    let is_valid = pm.state.invalid_extent.replace(invalid_extent) == invalid_extent;
+
+    // Expressions can't end inside of a delimiter that they opened,
+    // so the arguments end at the first closing delimiter of the kind
+    // the call was opened with
+    let is_closing: fn(&Token) -> bool = match call.args {
+        MacroCallArgs::Paren(_) => Token::is_right_paren,
+        MacroCallArgs::Square(_) => Token::is_right_square,
+        MacroCallArgs::Curly(_) => Token::is_right_curly,
+    };
+    let is_complete = r.point.sub_offset.is_none() &&
+        r.point.s.first().map_or(false, is_closing);
+
+    match r.status {
+        peresil::Status::Success(v) if is_valid && is_complete => Some(v),
+        _ => None,
+    }
 }
 
 fn item_macro_call_args<'s>(pm: &mut Master<'s>, pt: Point<'s>) -> Progress<'s, MacroCallArgs> {
@@ -3444,15 +5640,19 @@ fn path<'s>(pm: &mut Master<'s>, pt: Point<'s>) -> Progress<'s, Path> {
     sequence!(pm, pt, {
         spt        = point;
         _          = optional(double_colon);
-        components = one_or_more_tailed_values(double_colon, ident);
+        components = one_or_more_tailed_values_first(double_colon, path_start_ident, ident);
     }, |pm: &mut Master, pt| Path { extent: pm.state.ex(spt, pt), components })
 }
 
 fn pathed_ident<'s>(pm: &mut Master<'s>, pt: Point<'s>) -> Progress<'s, PathedIdent> {
+    memoized(|m| &mut m.pathed_ident, pathed_ident_unmemoized)(pm, pt)
+}
+
+fn pathed_ident_unmemoized<'s>(pm: &mut Master<'s>, pt: Point<'s>) -> Progress<'s, PathedIdent> {
     sequence!(pm, pt, {
         spt        = point;
         _          = optional(double_colon);
-        components = one_or_more_tailed_values(double_colon, path_component);
+        components = one_or_more_tailed_values_first(double_colon, path_start_component, path_component);
     }, |pm: &mut Master, pt| PathedIdent { extent: pm.state.ex(spt, pt), components })
 }
 
@@ -3464,6 +5664,13 @@ fn path_component<'s>(pm: &mut Master<'s>, pt: Point<'s>) -> Progress<'s, PathCo
     }, |pm: &mut Master, pt| PathComponent { extent: pm.state.ex(spt, pt), ident, turbofish })
 }
 
+fn path_start_component<'s>(pm: &mut Master<'s>, pt: Point<'s>) -> Progress<'s, PathComponent> {
+    pm.alternate(pt)
+        .one(path_component)
+        .one(map(ident_crate, |ident| PathComponent { extent: ident.extent, ident, turbofish: None }))
+        .finish()
+}
+
 fn turbofish<'s>(pm: &mut Master<'s>, pt: Point<'s>) -> Progress<'s, Turbofish> {
     sequence!(pm, pt, {
         spt       = point;
@@ -3479,7 +5686,7 @@ fn pattern<'s>(pm: &mut Master<'s>, pt: Point<'s>) -> Progress<'s, Pattern> {
     sequence!(pm, pt, {
         spt  = point;
         name = optional(pattern_name);
-        kind = pattern_kind;
+        kind = nested(pattern_kind);
     }, |pm: &mut Master, pt| Pattern { extent: pm.state.ex(spt, pt), name, kind })
 }
 
@@ -3572,7 +5779,7 @@ fn pattern_struct_field<'s>(pm: &mut Master<'s>, pt: Point<'s>) -> Progress<'s,
     pm.alternate(pt)
         .one(map(pattern_struct_field_long, PatternStructField::Long))
         .one(map(map(pattern_ident, |ident| {
-            PatternStructFieldShort { ident }
+            PatternStructFieldShort { extent: ident.extent, ident }
         }), PatternStructField::Short))
         .finish()
 }
@@ -3737,18 +5944,21 @@ fn struct_defn_body_tuple<'s>(pm: &mut Master<'s>, pt: Point<'s>) ->
 {
     sequence!(pm, pt, {
         spt    = point;
-        fields = struct_defn_body_tuple_only;
+        body   = struct_defn_body_tuple_only;
         wheres = optional(where_clause);
         _      = semicolon;
-    }, |pm: &mut Master, pt| (StructDefinitionBodyTuple { extent: pm.state.ex(spt, pt), fields, whitespace: Vec::new() }, wheres))
+    }, |pm: &mut Master, pt| (StructDefinitionBodyTuple { extent: pm.state.ex(spt, pt), ..body }, wheres))
 }
 
-fn struct_defn_body_tuple_only<'s>(pm: &mut Master<'s>, pt: Point<'s>) -> Progress<'s, Vec<Attributed<StructDefinitionFieldUnnamed>>> {
+fn struct_defn_body_tuple_only<'s>(pm: &mut Master<'s>, pt: Point<'s>) ->
+    Progress<'s, StructDefinitionBodyTuple>
+{
     sequence!(pm, pt, {
-        _     = left_paren;
-        types = zero_or_more_tailed_values(comma, attributed(tuple_defn_field));
-        _     = right_paren;
-    }, |_, _| types)
+        spt    = point;
+        _      = left_paren;
+        fields = zero_or_more_tailed_values(comma, attributed(tuple_defn_field));
+        _      = right_paren;
+    }, |pm: &mut Master, pt| StructDefinitionBodyTuple { extent: pm.state.ex(spt, pt), fields, whitespace: Vec::new() })
 }
 
 fn tuple_defn_field<'s>(pm: &mut Master<'s>, pt: Point<'s>) -> Progress<'s, StructDefinitionFieldUnnamed> {
@@ -3840,10 +6050,17 @@ fn enum_variant_body<'s>(pm: &mut Master<'s>, pt: Point<'s>) -> Progress<'s, Enu
     pm.alternate(pt)
         .one(map(struct_defn_body_tuple_only, EnumVariantBody::Tuple))
         .one(map(struct_defn_body_brace_only, EnumVariantBody::Struct))
-        .one(map(optional(enum_discriminant), EnumVariantBody::Unit))
+        .one(map(enum_variant_body_unit, EnumVariantBody::Unit))
         .finish()
 }
 
+fn enum_variant_body_unit<'s>(pm: &mut Master<'s>, pt: Point<'s>) -> Progress<'s, EnumVariantBodyUnit> {
+    sequence!(pm, pt, {
+        spt          = point;
+        discriminant = optional(enum_discriminant);
+    }, |pm: &mut Master, pt| EnumVariantBodyUnit { extent: pm.state.ex(spt, pt), discriminant })
+}
+
 fn enum_discriminant<'s>(pm: &mut Master<'s>, pt: Point<'s>) -> Progress<'s, Attributed<Expression>> {
     sequence!(pm, pt, {
         _     = equals;
@@ -3996,9 +6213,15 @@ fn trait_impl_function_arglist<'s>(pm: &mut Master<'s>, pt: Point<'s>) -> Progre
 
 fn trait_impl_function_argument<'s>(pm: &mut Master<'s>, pt: Point<'s>) -> Progress<'s, TraitImplArgument> {
     sequence!(pm, pt, {
+        spt  = point;
         name = optional(trait_impl_function_argument_name);
         typ  = typ;
-    }, |_, _| TraitImplArgument::Named(TraitImplArgumentNamed { name, typ, whitespace: Vec::new() }))
+    }, |pm: &mut Master, pt| TraitImplArgument::Named(TraitImplArgumentNamed {
+        extent: pm.state.ex(spt, pt),
+        name,
+        typ,
+        whitespace: Vec::new(),
+    }))
 }
 
 fn trait_impl_function_argument_name<'s>(pm: &mut Master<'s>, pt: Point<'s>) ->
@@ -4288,7 +6511,7 @@ fn p_use<'s>(pm: &mut Master<'s>, pt: Point<'s>) -> Progress<'s, Use> {
         visibility = optional(visibility);
         _          = kw_use;
         _          = optional(double_colon);
-        path       = zero_or_more(use_path_component);
+        path       = use_path;
         tail       = use_tail;
         _          = semicolon;
     }, move |pm: &mut Master, pt| {
@@ -4296,6 +6519,20 @@ fn p_use<'s>(pm: &mut Master<'s>, pt: Point<'s>) -> Progress<'s, Use> {
     })
 }
 
+fn use_path<'s>(pm: &mut Master<'s>, pt: Point<'s>) -> Progress<'s, Vec<Ident>> {
+    sequence!(pm, pt, {
+        first = optional(use_path_start_component);
+        rest  = zero_or_more(use_path_component);
+    }, |_, _| first.into_iter().chain(rest).collect())
+}
+
+fn use_path_start_component<'s>(pm: &mut Master<'s>, pt: Point<'s>) -> Progress<'s, Ident> {
+    sequence!(pm, pt, {
+        name = path_start_ident;
+        _    = double_colon;
+    }, |_, _| name)
+}
+
 fn use_path_component<'s>(pm: &mut Master<'s>, pt: Point<'s>) -> Progress<'s, Ident> {
     sequence!(pm, pt, {
         name = ident;
@@ -4384,15 +6621,19 @@ fn module_body_or_not<'s>(pm: &mut Master<'s>, pt: Point<'s>) -> Progress<'s, Op
 fn module_body<'s>(pm: &mut Master<'s>, pt: Point<'s>) -> Progress<'s, Vec<Attributed<Item>>> {
     sequence!(pm, pt, {
         _    = left_curly;
-        body = zero_or_more(attributed(item));
+        body = nested(zero_or_more(attributed(item)));
         _    = right_curly;
     }, |_, _| body)
 }
 
 fn typ<'s>(pm: &mut Master<'s>, pt: Point<'s>) -> Progress<'s, Type> {
+    memoized(|m| &mut m.typ, typ_unmemoized)(pm, pt)
+}
+
+fn typ_unmemoized<'s>(pm: &mut Master<'s>, pt: Point<'s>) -> Progress<'s, Type> {
     sequence!(pm, pt, {
         spt        = point;
-        kind       = typ_kind;
+        kind       = nested(typ_kind);
         additional = zero_or_more_tailed_values_resume(plus, typ_additional);
     }, |pm: &mut Master, pt| Type { extent: pm.state.ex(spt, pt), kind, additional })
 }
@@ -4400,7 +6641,7 @@ fn typ<'s>(pm: &mut Master<'s>, pt: Point<'s>) -> Progress<'s, Type> {
 fn typ_single<'s>(pm: &mut Master<'s>, pt: Point<'s>) -> Progress<'s, Type> {
     sequence!(pm, pt, {
         spt  = point;
-        kind = typ_kind;
+        kind = nested(typ_kind);
     }, |pm: &mut Master, pt| Type { extent: pm.state.ex(spt, pt), kind, additional: vec![] })
 }
 
@@ -4408,6 +6649,7 @@ fn typ_kind<'s>(pm: &mut Master<'s>, pt: Point<'s>) -> Progress<'s, TypeKind> {
     pm.alternate(pt)
         .one(map(typ_array, TypeKind::Array))
         .one(map(typ_disambiguation, TypeKind::Disambiguation))
+        .one(map(typ_dyn_trait, TypeKind::DynTrait))
         .one(map(typ_function, TypeKind::Function))
         .one(map(typ_higher_ranked_trait_bounds, TypeKind::HigherRankedTraitBounds))
         .one(map(typ_impl_trait, TypeKind::ImplTrait))
@@ -4501,6 +6743,26 @@ fn typ_impl_trait<'s>(pm: &mut Master<'s>, pt: Point<'s>) -> Progress<'s, TypeIm
     }, |pm: &mut Master, pt| TypeImplTrait { extent: pm.state.ex(spt, pt), name, whitespace: Vec::new() })
 }
 
+fn typ_dyn_trait<'s>(pm: &mut Master<'s>, pt: Point<'s>) -> Progress<'s, TypeDynTrait> {
+    sequence!(pm, pt, {
+        spt  = point;
+        _    = kw_dyn_type;
+        name = typ_named;
+    }, |pm: &mut Master, pt| TypeDynTrait { extent: pm.state.ex(spt, pt), name, whitespace: Vec::new() })
+}
+
+// In Rust 2015, `dyn` is only a keyword when it doesn't start a path
+fn kw_dyn_type<'s>(pm: &mut Master<'s>, pt: Point<'s>) -> Progress<'s, Extent> {
+    let is_path = pm.state.edition == Edition::Rust2015 &&
+        pt.s.get(1).map_or(false, Token::is_double_colon);
+
+    if is_path {
+        Progress::failure(pt, Error::ExpectedDyn)
+    } else {
+        kw_dyn(pm, pt)
+    }
+}
+
 fn typ_additional<'s>(pm: &mut Master<'s>, pt: Point<'s>) ->
     Progress<'s, TypeAdditional>
 {
@@ -4514,7 +6776,7 @@ fn typ_named<'s>(pm: &mut Master<'s>, pt: Point<'s>) -> Progress<'s, TypeNamed>
     sequence!(pm, pt, {
         spt  = point;
         _    = optional(double_colon);
-        path = one_or_more_tailed_values(double_colon, typ_named_component);
+        path = one_or_more_tailed_values_first(double_colon, typ_named_start_component, typ_named_component);
     }, |pm: &mut Master, pt| TypeNamed { extent: pm.state.ex(spt, pt), path })
 }
 
@@ -4526,6 +6788,13 @@ fn typ_named_component<'s>(pm: &mut Master<'s>, pt: Point<'s>) -> Progress<'s, T
     }, |pm: &mut Master, pt| TypeNamedComponent { extent: pm.state.ex(spt, pt), ident, generics })
 }
 
+fn typ_named_start_component<'s>(pm: &mut Master<'s>, pt: Point<'s>) -> Progress<'s, TypeNamedComponent> {
+    pm.alternate(pt)
+        .one(typ_named_component)
+        .one(map(ident_crate, |ident| TypeNamedComponent { extent: ident.extent, ident, generics: None }))
+        .finish()
+}
+
 fn typ_disambiguation<'s>(pm: &mut Master<'s>, pt: Point<'s>) -> Progress<'s, TypeDisambiguation> {
     sequence!(pm, pt, {
         spt  = point;
@@ -4705,7 +6974,7 @@ where
     move |pm, pt| {
         sequence!(pm, pt, {
             spt        = point;
-            attributes = zero_or_more(attribute);
+            attributes = memoized(|m| &mut m.attributes, zero_or_more(attribute));
             value      = f;
         }, |pm: &mut Master<'s>, pt| Attributed {
             extent: pm.state.ex(spt, pt),
@@ -4777,6 +7046,12 @@ mod test {
         assert_extent!(p, (0, 17))
     }
 
+    #[test]
+    fn parse_use_starting_with_crate() {
+        let p = qp(p_use, "use crate::foo::Bar;");
+        assert_extent!(p, (0, 20))
+    }
+
     #[test]
     fn parse_use_rename() {
         let p = qp(p_use, "use foo as bar;");
@@ -5240,15 +7515,34 @@ mod test {
     }
 
     #[test]
-    fn enum_with_attribute_on_value() {
-        let p = qp(p_enum, "enum Foo { A(#[attr] u8) }");
-        assert_extent!(p, (0, 26))
+    fn enum_with_attribute_on_value() {
+        let p = qp(p_enum, "enum Foo { A(#[attr] u8) }");
+        assert_extent!(p, (0, 26))
+    }
+
+    #[test]
+    fn enum_with_discriminant() {
+        let p = qp(p_enum, "enum Foo { A = 1, B = 2 }");
+        assert_extent!(p, (0, 25))
+    }
+
+    #[test]
+    fn enum_variant_body_tuple() {
+        let p = qp(enum_variant_body, "(u8, u8)");
+        assert_extent!(p, (0, 8))
+    }
+
+    #[test]
+    fn enum_variant_body_discriminant() {
+        let p = qp(enum_variant_body, "= 1");
+        assert_extent!(p, (0, 3))
     }
 
     #[test]
-    fn enum_with_discriminant() {
-        let p = qp(p_enum, "enum Foo { A = 1, B = 2 }");
-        assert_extent!(p, (0, 25))
+    fn enum_variant_body_unit() {
+        let p = qp(p_enum, "enum Foo { A }");
+        let body = p.variants[0].value.body.extent();
+        assert_eq!(body, (13, 13))
     }
 
     #[test]
@@ -5341,6 +7635,24 @@ mod test {
         assert_extent!(p, (0, 13))
     }
 
+    #[test]
+    fn fn_argument() {
+        let p = qp(function_argument, "a: u8");
+        assert_extent!(p, (0, 5))
+    }
+
+    #[test]
+    fn trait_impl_function_argument_without_name() {
+        let p = qp(trait_impl_function_argument, "u8");
+        assert_extent!(p, (0, 2))
+    }
+
+    #[test]
+    fn trait_impl_function_argument_with_name() {
+        let p = qp(trait_impl_function_argument, "a: u8");
+        assert_extent!(p, (0, 5))
+    }
+
     #[test]
     fn fn_with_arguments_all_space() {
         let p = qp(function_header, "fn foo ( a : u8 )");
@@ -5450,6 +7762,12 @@ mod test {
         assert_extent!(p, (0, 33))
     }
 
+    #[test]
+    fn pathed_ident_starting_with_crate() {
+        let p = qp(pathed_ident, "crate::foo");
+        assert_extent!(p, (0, 10))
+    }
+
     #[test]
     fn pathed_ident_with_leading_separator() {
         let p = qp(pathed_ident, "::foo");
@@ -5596,6 +7914,12 @@ mod test {
         assert_extent!(p, (0, 12))
     }
 
+    #[test]
+    fn pattern_struct_field_shorthand() {
+        let p = qp(pattern_struct_field, "ref a");
+        assert_extent!(p, (0, 5))
+    }
+
     #[test]
     fn pattern_with_enum_struct_shorthand() {
         let p = qp(pattern, "Baz { a }");
@@ -5753,6 +8077,12 @@ mod test {
         assert_extent!(p, (0, 5))
     }
 
+    #[test]
+    fn type_starting_with_crate() {
+        let p = qp(typ, "crate::Foo<u8>");
+        assert_extent!(p, (0, 14))
+    }
+
     #[test]
     fn type_tuple() {
         let p = qp(typ, "(u8, u8)");
@@ -5783,6 +8113,19 @@ mod test {
         assert_extent!(p, (0, 8))
     }
 
+    #[test]
+    fn type_dyn_trait() {
+        let p = qp(typ, "dyn Foo");
+        assert_extent!(p, (0, 7))
+    }
+
+    #[test]
+    fn type_dyn_path_in_2015() {
+        let p = qp(typ, "dyn::Foo");
+        assert_extent!(p, (0, 8));
+        assert!(p.kind.is_named());
+    }
+
     #[test]
     fn type_fn_trait() {
         let p = qp(typ, "Fn(u8) -> u8");
@@ -6331,4 +8674,723 @@ mod test {
         let r = parse_rust_file("c!(");
         assert!(r.is_err());
     }
+
+    #[test]
+    fn error_text_reports_character_columns() {
+        let text = "fn é() {}\nfn ü() { ) }";
+        let e = parse_rust_file(text).unwrap_err();
+        let rendered = e.with_text(text).to_string();
+        let mut lines = rendered.lines();
+        assert_eq!(lines.next(), Some("Unable to parse text (line 2, column 10)"));
+        assert_eq!(lines.next(), Some("fn ü() { ) }"));
+        assert_eq!(lines.next(), Some("         ^"));
+    }
+
+    #[test]
+    fn error_text_with_the_wrong_text_reports_the_offset() {
+        let e = parse_rust_file("fn a() { ) }").unwrap_err();
+        let rendered = e.with_text("fn").to_string();
+        assert_eq!(rendered.lines().next(), Some("Unable to parse text (byte 9)"));
+
+        let rendered = e.with_text("fn a() { ) }").with_origin(Origin { offset: 20, ..Origin::default() }).to_string();
+        assert_eq!(rendered.lines().next(), Some("Unable to parse text (byte 9)"));
+    }
+
+    #[test]
+    fn public_fragment_parsers_parse_complete_input() {
+        assert_extent!(parse_expression("a + b").unwrap(), (0, 5));
+        assert_extent!(parse_type("Vec<&'a str>").unwrap(), (0, 12));
+        assert_extent!(parse_pattern("Some(ref x)").unwrap(), (0, 11));
+        assert_extent!(parse_item("#[test] fn a() {}").unwrap(), (0, 17));
+        assert_extent!(parse_statement("let a = 1").unwrap(), (0, 9));
+        assert_extent!(parse_block("{ a; b }").unwrap(), (0, 8));
+    }
+
+    #[test]
+    fn public_fragment_parsers_reject_trailing_input() {
+        let e = parse_type("u8 u16").unwrap_err();
+        let e = unwrap_as!(e, ErrorDetail::Parser);
+        assert_eq!(e.location, 3);
+        assert!(e.errors.contains(&Error::ExpectedEndOfFile));
+    }
+
+    #[test]
+    fn public_fragment_parsers_report_tokenizer_errors() {
+        let e = parse_expression("\"a").unwrap_err();
+        unwrap_as!(e, ErrorDetail::Tokenizer);
+    }
+
+    #[test]
+    fn fragment_extents_are_relative_to_the_document() {
+        let doc = "Some prose\n```\nfn a() {}\n```";
+        let offset = doc.find("fn").unwrap();
+        let fragment = Fragment::new(&doc[offset..doc.rfind("\n```").unwrap()], offset);
+
+        let file = fragment.parse_file().unwrap();
+        assert_extent!(file.items[0], (15, 24));
+        assert_eq!(&doc[15..24], "fn a() {}");
+
+        let e = fragment.parse_expression().unwrap_err();
+        let e = unwrap_as!(e, ErrorDetail::Parser);
+        assert_eq!(e.location, 15);
+    }
+
+    #[test]
+    fn fragment_errors_render_against_the_document() {
+        let doc = "/// ```\n/// let a = ;\n/// ```";
+        let offset = doc.find("let").unwrap();
+        let fragment = Fragment::new("let a = ;", offset)
+            .with_line_column(LineColumn::new(1, 4));
+
+        let e = fragment.parse_statement().unwrap_err();
+        let rendered = fragment.error_text(&e).to_string();
+        let mut lines = rendered.lines();
+        assert_eq!(lines.next(), Some("Unable to parse text (line 2, column 13)"));
+        assert_eq!(lines.next(), Some("let a = ;"));
+        assert_eq!(lines.next(), Some("        ^"));
+    }
+
+    #[test]
+    fn fields_are_available_through_accessors() {
+        let file = parse_rust_file("#[a] struct A<T> { b: Option<T> }").unwrap();
+        let item = &file.items()[0];
+        assert_eq!(item.attributes().len(), 1);
+
+        let s = item.value().as_struct().unwrap();
+        assert!(s.visibility().is_none());
+        assert_extent!(s.name(), (12, 13));
+        assert_eq!(s.generics().map(|g| g.types().len()), Some(1));
+
+        let body = s.body().as_brace().unwrap();
+        assert_extent!(body.fields()[0].name(), (19, 20));
+    }
+
+    #[test]
+    fn mutable_visitor_can_rewrite_nodes() {
+        struct Shift;
+        impl VisitorMut for Shift {
+            fn visit_ident_mut(&mut self, ident: &mut Ident) -> Control {
+                ident.extent = (ident.extent.0 + 10, ident.extent.1 + 10);
+                Control::Continue
+            }
+        }
+
+        #[derive(Default)]
+        struct Idents(Vec<Extent>);
+        impl Visitor for Idents {
+            fn visit_ident(&mut self, ident: &Ident) -> Control {
+                self.0.push(ident.extent);
+                Control::Continue
+            }
+        }
+
+        let mut file = parse_rust_file("fn a(b: C) {}").unwrap();
+        file.visit_mut(&mut Shift);
+
+        let mut idents = Idents::default();
+        file.visit(&mut idents);
+        assert_eq!(idents.0, vec![(13, 14), (15, 16), (18, 19)]);
+    }
+
+    #[test]
+    fn fold_can_replace_nodes() {
+        struct RemoveParens;
+        impl Fold for RemoveParens {
+            fn fold_attributed_expression(&mut self, node: Attributed<Expression>) -> Attributed<Expression> {
+                let node = node.fold_children(self);
+                match node.value {
+                    Expression::Parenthetical(p) => *p.expression,
+                    value => Attributed { value, ..node },
+                }
+            }
+        }
+
+        #[derive(Default)]
+        struct Counts { parens: usize, binaries: usize }
+        impl Visitor for Counts {
+            fn visit_parenthetical(&mut self, _: &Parenthetical) -> Control {
+                self.parens += 1;
+                Control::Continue
+            }
+            fn visit_binary(&mut self, _: &Binary) -> Control {
+                self.binaries += 1;
+                Control::Continue
+            }
+        }
+
+        let file = parse_rust_file("fn a() { ((b + c)) * (d) }").unwrap();
+        let file = file.fold_with(&mut RemoveParens);
+
+        let mut counts = Counts::default();
+        file.visit(&mut counts);
+        assert_eq!(counts.parens, 0);
+        assert_eq!(counts.binaries, 2);
+    }
+
+    #[test]
+    fn operators_are_visited() {
+        #[derive(Default)]
+        struct Operators(Vec<Extent>);
+        impl Visitor for Operators {
+            fn visit_binary_op(&mut self, op: &BinaryOp) -> Control {
+                self.0.push(op.extent());
+                Control::Continue
+            }
+            fn visit_unary_op(&mut self, op: &UnaryOp) -> Control {
+                self.0.push(op.extent());
+                Control::Continue
+            }
+        }
+
+        let file = parse_rust_file("fn a() { b + !c }").unwrap();
+        let mut ops = Operators::default();
+        file.visit(&mut ops);
+        assert_eq!(ops.0, vec![(11, 12), (13, 14)]);
+    }
+
+    #[test]
+    fn stop_halts_the_entire_traversal() {
+        #[derive(Default)]
+        struct FirstIdent { found: Option<Extent>, exits: usize }
+        impl Visitor for FirstIdent {
+            fn visit_ident(&mut self, ident: &Ident) -> Control {
+                self.found = Some(ident.extent);
+                Control::Stop
+            }
+            fn exit_function(&mut self, _: &Function) {
+                self.exits += 1;
+            }
+        }
+
+        let file = parse_rust_file("fn a() {} fn b() {}").unwrap();
+        let mut v = FirstIdent::default();
+        assert_eq!(file.visit(&mut v), Control::Stop);
+        assert_eq!(v.found, Some((3, 4)));
+        assert_eq!(v.exits, 0);
+    }
+
+    #[test]
+    fn break_on_an_attributed_node_skips_its_children() {
+        #[derive(Default)]
+        struct Items { attributed: usize, functions: usize }
+        impl Visitor for Items {
+            fn visit_attributed_item(&mut self, _: &Attributed<Item>) -> Control {
+                self.attributed += 1;
+                Control::Break
+            }
+            fn visit_function(&mut self, _: &Function) -> Control {
+                self.functions += 1;
+                Control::Continue
+            }
+        }
+
+        let file = parse_rust_file("fn a() {} fn b() {}").unwrap();
+        let mut v = Items::default();
+        assert_eq!(file.visit(&mut v), Control::Continue);
+        assert_eq!(v.attributed, 2);
+        assert_eq!(v.functions, 0);
+    }
+
+    #[test]
+    fn ancestors_are_provided_to_the_visitor() {
+        #[derive(Default)]
+        struct FunctionNames(Vec<(Extent, usize)>);
+        impl<'a> AncestorVisitor<'a> for FunctionNames {
+            fn visit(&mut self, node: NodeRef<'a>, ancestors: &[NodeRef<'a>]) -> Control {
+                if let NodeRef::Ident(ident) = node {
+                    if let Some(&NodeRef::FunctionHeader(_)) = ancestors.last() {
+                        assert!(match ancestors[0] { NodeRef::File(_) => true, _ => false });
+                        self.0.push((ident.extent, ancestors.len()));
+                    }
+                }
+                Control::Continue
+            }
+        }
+
+        let file = parse_rust_file("fn a(b: B) {} mod c { fn d() {} }").unwrap();
+        let mut v = FunctionNames::default();
+        NodeRef::from(&file).visit_with_ancestors(&mut v);
+        assert_eq!(v.0, vec![((3, 4), 5), ((25, 26), 8)]);
+    }
+
+    #[test]
+    fn file_includes_surrounding_whitespace_and_comments() {
+        let file = parse_rust_file("// hi\nfn a() {}\n").unwrap();
+        assert_extent!(file, (0, 16));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn file_round_trips_through_serde() {
+        let file = parse_rust_file("fn a(b: u8) -> u8 { b + 1 }").unwrap();
+        let json = serde_json::to_string(&file).unwrap();
+        let again: File = serde_json::from_str(&json).unwrap();
+        assert_eq!(serde_json::to_string(&again).unwrap(), json);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn enums_serialize_externally_tagged() {
+        let op = BinaryOp::Add((2, 3));
+        assert_eq!(serde_json::to_string(&op).unwrap(), r#"{"Add":[2,3]}"#);
+
+        let token = Token::Semicolon((0, 1));
+        assert_eq!(serde_json::to_string(&token).unwrap(), r#"{"Semicolon":[0,1]}"#);
+    }
+
+    #[test]
+    fn walk_visits_nodes_in_pre_order() {
+        let file = parse_rust_file("fn a() { b }").unwrap();
+        let root = NodeRef::from(&file);
+
+        let idents: Vec<_> = root.walk()
+            .filter(|n| n.kind() == NodeKind::Ident)
+            .map(|n| n.extent())
+            .collect();
+        assert_eq!(idents, vec![(3, 4), (9, 10)]);
+
+        let kinds: Vec<_> = root.walk().map(|n| n.kind()).take(4).collect();
+        assert_eq!(kinds, vec![NodeKind::File, NodeKind::AttributedItem, NodeKind::Item, NodeKind::Function]);
+    }
+
+    #[test]
+    fn node_ref_children_are_in_source_order() {
+        let file = parse_rust_file("fn a() { b }").unwrap();
+        let function = NodeRef::from(&file).walk()
+            .find(|n| n.kind() == NodeKind::Function)
+            .unwrap();
+        assert_extent!(function, (0, 12));
+
+        let children: Vec<_> = function.children().iter().map(|n| n.kind()).collect();
+        assert_eq!(children, vec![NodeKind::FunctionHeader, NodeKind::Block]);
+    }
+
+    #[test]
+    fn binary_children_are_in_source_order() {
+        let file = parse_rust_file("fn a() { b + c }").unwrap();
+        let binary = NodeRef::from(&file).walk()
+            .find(|n| n.kind() == NodeKind::Binary)
+            .unwrap();
+        let children: Vec<_> = binary.children().iter().map(|n| n.extent()).collect();
+        assert_eq!(children, vec![(9, 10), (11, 12), (13, 14)]);
+    }
+
+    const VARIED_SOURCE: &str = r#"
+        #![allow(dead_code)]
+        extern crate a;
+        use a::{b, c};
+        use d::*;
+        pub(crate) mod d { pub struct E<'a, T: 'a + Clone = u8> where T: Copy { f: &'a T } }
+        enum G { H(u8, i8), I { j: [u8; 4] }, K = 3 }
+        union L { m: u32 }
+        trait N<T>: O { type P; const Q: u8 = 1; fn r(&self) -> Self::P where T: S; }
+        impl<T> N<T> for G { type P = (); fn r(&self) {} }
+        static mut T: &str = "u";
+        extern "C" { fn v(w: *const u8, ...) -> i32; }
+        macro_rules! x { () => {} }
+        x!();
+        #[inline]
+        unsafe fn y<F>(z: F) -> impl Fn(u8) -> u8 where F: FnOnce() {
+            let (mut a, ref b @ _) = (1..2, [3, 4]);
+            let c = |d: u8| -> u8 { d as u8 + 1 };
+            let e = G::I { j: [0; 4] };
+            let f = E { f };
+            match e {
+                G::H(1...3, _) | G::K if true => {}
+                G::I { ref j, .. } => { a = j[0] << 2; }
+                _ => return move |g| g,
+            }
+            'h: loop { while let Some(i) = b.iter().next() { continue 'h; } break; }
+            for k in 0..10 { if k > 2 { x!() } else if let Ok(_) = z() {} else {} }
+            &mut *Box::new(-!a?.b.0.c::<u8>(1, 2)[3]);
+            c
+        }
+    "#;
+
+    #[test]
+    fn children_are_in_source_order() {
+        for source in &[VARIED_SOURCE, include_str!("expression.rs"), include_str!("source.rs")] {
+            let file = parse_rust_file(source).expect("Unable to parse");
+
+            for node in NodeRef::from(&file).walk() {
+                for pair in node.children().windows(2) {
+                    let (a, b) = (pair[0].extent(), pair[1].extent());
+                    // The name and value of a shorthand struct literal
+                    // field are the same token
+                    assert!(a.1 <= b.0 || a == b, "{:?} children {:?} then {:?}", node.kind(), a, b);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn node_at_finds_the_innermost_node() {
+        let file = parse_rust_file("fn a() { b.c }").unwrap();
+        let chain = file.node_at(9);
+        let kinds: Vec<_> = chain.iter().take(8).map(|n| n.kind()).collect();
+        assert_eq!(kinds, vec![
+            NodeKind::File, NodeKind::AttributedItem, NodeKind::Item, NodeKind::Function,
+            NodeKind::Block, NodeKind::AttributedExpression, NodeKind::Expression,
+            NodeKind::FieldAccess,
+        ]);
+        assert_eq!(chain[chain.len() - 1].kind(), NodeKind::Ident);
+        assert_extent!(chain[chain.len() - 1], (9, 10));
+    }
+
+    #[test]
+    fn node_at_looks_through_nodes_without_an_extent() {
+        let file = parse_rust_file("fn a(b: u8) {}").unwrap();
+        let kinds: Vec<_> = file.node_at(8).iter().skip(4).map(|n| n.kind()).collect();
+        assert_eq!(kinds, vec![
+            NodeKind::FunctionHeader, NodeKind::Argument, NodeKind::NamedArgument,
+            NodeKind::Type, NodeKind::TypeKind, NodeKind::TypeNamed,
+            NodeKind::TypeNamedComponent, NodeKind::Ident,
+        ]);
+    }
+
+    #[test]
+    fn node_at_in_whitespace_is_only_the_file() {
+        let file = parse_rust_file("  fn a() {}").unwrap();
+        let kinds: Vec<_> = file.node_at(0).iter().map(|n| n.kind()).collect();
+        assert_eq!(kinds, vec![NodeKind::File]);
+    }
+
+    #[test]
+    fn nodes_covering_stops_at_the_smallest_enclosing_node() {
+        let file = parse_rust_file("fn a() { b + c }").unwrap();
+        let chain = file.nodes_covering((9, 14));
+        assert_eq!(chain[chain.len() - 1].kind(), NodeKind::Binary);
+    }
+
+    #[test]
+    fn backwards_extents_are_reported_instead_of_panicking() {
+        let buffer = TokenBuffer::new(Tokens::new("a b")).unwrap();
+        let start = Point::new(buffer.tokens());
+        let state = State::new();
+
+        state.ex(start.advance_by(1), start);
+
+        let e = state.invalid_extent_error().expect("No error was recorded");
+        let e = unwrap_as!(e, ErrorDetail::Parser);
+        assert_eq!(e.location, 2);
+        assert!(e.errors.contains(&Error::InvalidExtent));
+    }
+
+    fn nest(open: &str, middle: &str, close: &str, depth: usize) -> ::std::string::String {
+        format!("{}{}{}", open.repeat(depth), middle, close.repeat(depth))
+    }
+
+    fn parse_with_max_depth(s: &str, max_depth: usize) -> Result<File, ErrorDetail> {
+        parse_rust_file_with_options(s, &ParseOptions::default().with_max_depth(max_depth))
+    }
+
+    #[test]
+    fn nesting_within_the_limit_is_allowed() {
+        let s = format!("fn a() {{ {} }}", nest("(", "b", ")", 4));
+        assert!(parse_with_max_depth(&s, 8).is_ok());
+    }
+
+    #[test]
+    fn nesting_too_deeply_is_an_error() {
+        let cases = [
+            format!("fn a() {{ {} }}", nest("(", "b", ")", 20)),
+            format!("fn a() {}", nest("{", "", "}", 20)),
+            format!("fn a() {{ {}b }}", "&".repeat(20)),
+            format!("type A = {}u8;", "&".repeat(20)),
+            format!("type A = {};", nest("Vec<", "u8", ">", 20)),
+            format!("fn a() {{ let {}b = c; }}", "&".repeat(20)),
+            nest("mod a { ", "", "}", 20),
+        ];
+
+        for s in &cases {
+            let e = parse_with_max_depth(s, 8).expect_err(s);
+            let e = unwrap_as!(e, ErrorDetail::Parser);
+            assert_eq!(e.errors.iter().collect::<Vec<_>>(), vec![&Error::NestingTooDeep], "{}", s);
+        }
+    }
+
+    #[test]
+    fn nesting_error_is_at_the_offending_token() {
+        let e = parse_with_max_depth("fn a() { ((((b)))) }", 3).unwrap_err();
+        assert_eq!(e.location(), 11);
+    }
+
+    #[test]
+    fn prefix_operators_only_nest_their_operand() {
+        let s = format!("fn a() {{ {}!b }}", "!b && ".repeat(DEFAULT_MAX_DEPTH * 4));
+        assert!(parse_rust_file(&s).is_ok());
+
+        let s = format!("fn a() {{ {}b }}", "!".repeat(DEFAULT_MAX_DEPTH * 4));
+        assert!(parse_rust_file(&s).is_err());
+    }
+
+    #[test]
+    fn default_nesting_limit_allows_realistic_code() {
+        let s = r#"
+        mod report {
+            impl Report {
+                fn summarize(&self, groups: &[Group]) -> Vec<Summary> {
+                    if self.verbose {
+                        groups.iter()
+                            .filter(|g| !g.items.is_empty())
+                            .map(|g| match g.kind {
+                                Kind::Named(ref name) => {
+                                    if let Some(first) = g.items.first() {
+                                        Summary::new(name, g.items.iter()
+                                            .filter_map(|i| i.children.iter().map(|c| {
+                                                match c.value {
+                                                    Some(v) if v > 0 => {
+                                                        if c.enabled {
+                                                            v.checked_mul(2).map(|x| x + first.offset).unwrap_or(0)
+                                                        } else {
+                                                            c.parents.iter().map(|p| {
+                                                                match p.weight {
+                                                                    Some(w) => {
+                                                                        if w > self.limit {
+                                                                            self.limit
+                                                                        } else {
+                                                                            w.saturating_sub(p.bias.map(|b| b * 2).unwrap_or(0))
+                                                                        }
+                                                                    }
+                                                                    None => 0,
+                                                                }
+                                                            }).sum()
+                                                        }
+                                                    }
+                                                    _ => 0,
+                                                }
+                                            }).max())
+                                            .collect())
+                                    } else {
+                                        Summary::empty(name)
+                                    }
+                                }
+                                Kind::Anonymous => Summary::default(),
+                            })
+                            .collect()
+                    } else {
+                        Vec::new()
+                    }
+                }
+            }
+        }
+        "#;
+        assert!(parse_rust_file(s).is_ok());
+        assert!(parse_with_max_depth(s, 32).is_err());
+    }
+
+    #[test]
+    fn default_nesting_limit_prevents_stack_overflow() {
+        // The stack documented for a debug build
+        let child = ::std::thread::Builder::new().stack_size(16 << 20).spawn(|| {
+            let s = format!("fn a() {{ {} }}", nest("(", "b", ")", 100_000));
+            parse_rust_file(&s).map(|_| ()).unwrap_err().location()
+        }).unwrap();
+
+        let location = child.join().unwrap();
+        assert_eq!(location, 9 + DEFAULT_MAX_DEPTH - 1);
+    }
+
+    #[test]
+    fn edition_2018_reserves_keywords() {
+        let s = "fn async() {}";
+        assert!(parse_rust_file(s).is_ok());
+
+        let e = ParseOptions::default().with_edition(Edition::Rust2018).parse(s).unwrap_err();
+        let e = unwrap_as!(e, ErrorDetail::Parser);
+        assert_eq!(e.location, 3);
+        assert!(e.errors.contains(&Error::ReservedKeyword));
+    }
+
+    fn parse_rust_2018_kinds(s: &str) -> Vec<NodeKind> {
+        let p = ParseOptions::default().with_edition(Edition::Rust2018).parse(s).expect("Unable to parse");
+        NodeRef::from(&p.file).walk().map(|n| n.kind()).collect()
+    }
+
+    #[test]
+    fn edition_2018_dyn_trait_objects() {
+        let kinds = parse_rust_2018_kinds("type A = Box<dyn B>;");
+        assert!(kinds.contains(&NodeKind::TypeDynTrait));
+    }
+
+    #[test]
+    fn edition_2018_async_and_await() {
+        let kinds = parse_rust_2018_kinds("async fn a() { async move { b.await }; }");
+        assert!(kinds.contains(&NodeKind::AsyncBlock));
+        assert!(kinds.contains(&NodeKind::Await));
+        assert!(!kinds.contains(&NodeKind::FieldAccess));
+    }
+
+    #[test]
+    fn edition_2015_keywords_are_identifiers() {
+        let s = "fn a() { let dyn = async.await; }";
+        let file = parse_rust_file(s).expect("Unable to parse");
+        let kinds: Vec<_> = NodeRef::from(&file).walk().map(|n| n.kind()).collect();
+        assert!(kinds.contains(&NodeKind::FieldAccess));
+
+        let e = ParseOptions::default().with_edition(Edition::Rust2018).parse(s).unwrap_err();
+        assert_eq!(e.location(), 13);
+
+        assert!(parse_rust_file("async fn a() {}").is_err());
+    }
+
+    #[test]
+    fn crate_only_starts_a_path() {
+        assert!(parse_rust_file("fn a() { crate::b::c(); }").is_ok());
+        assert!(parse_rust_file("fn crate() {}").is_err());
+        assert!(parse_rust_file("fn a() { let crate = 1; }").is_err());
+        assert!(parse_rust_file("struct crate;").is_err());
+        assert!(parse_rust_file("use a::crate::b;").is_err());
+        assert!(parse_rust_file("fn a() { b::crate(); }").is_err());
+    }
+
+    #[test]
+    fn raw_identifiers_are_identifiers() {
+        let s = "fn r#async() { let r#dyn = 1; }";
+        assert!(parse_rust_file(s).is_ok());
+        assert!(ParseOptions::default().with_edition(Edition::Rust2018).parse(s).is_ok());
+    }
+
+    #[test]
+    fn trivia_is_kept_when_requested() {
+        let s = "// a\nfn b() {}";
+        let p = ParseOptions::default().with_trivia(true).parse(s).expect("Unable to parse");
+        let trivia = p.trivia.expect("Trivia was not kept");
+        assert!(trivia.tokens()[0].is_comment());
+        assert_eq!(trivia.tokens().len(), 4);
+        assert_eq!(trivia.before(0).map(|t| t.len()), Some(2));
+
+        let p = ParseOptions::default().parse(s).expect("Unable to parse");
+        assert!(p.trivia.is_none());
+    }
+
+    #[test]
+    fn recovery_skips_items_that_fail() {
+        let s = "fn a() {}\nstruct B<;\nfn c() {}\nimpl D { x }\nfn e() {}";
+        let p = ParseOptions::default().with_recovery(true).parse(s).expect("Unable to parse");
+        assert_eq!(p.file.items.len(), 3);
+        let locations: Vec<_> = p.errors.iter().map(ErrorDetail::location).collect();
+        assert_eq!(locations, [19, 42]);
+
+        let e = parse_rust_file_with_options(s, &ParseOptions::default().with_recovery(true));
+        assert_eq!(e.unwrap_err().location(), 19);
+    }
+
+    #[test]
+    fn macro_args_are_parsed_as_expressions_when_requested() {
+        let s = "a!(1, b + c);";
+        let options = ParseOptions::default().with_macro_args(true);
+        let mut p = options.parse(s).expect("Unable to parse");
+        let item = p.file.items.remove(0).value;
+        let m = unwrap_as!(item, Item::MacroCall);
+        let expressions = m.expressions.expect("Arguments were not parsed");
+        assert_eq!(expressions.len(), 2);
+        assert_extent!(expressions[1], (6, 11));
+
+        let mut p = ParseOptions::default().parse(s).expect("Unable to parse");
+        let item = p.file.items.remove(0).value;
+        let m = unwrap_as!(item, Item::MacroCall);
+        assert!(m.expressions.is_none());
+    }
+
+    #[test]
+    fn macro_args_followed_by_whitespace_are_parsed() {
+        let s = "fn a() {\n    b!(1, c );\n    println!(\n        \"{}\",\n        d, // e\n    );\n}";
+        let options = ParseOptions::default().with_macro_args(true);
+        let p = options.parse(s).expect("Unable to parse");
+
+        let counts: Vec<_> = NodeRef::from(&p.file).walk()
+            .filter_map(|n| match n {
+                NodeRef::MacroCall(m) => m.expressions.as_ref().map(Vec::len),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(counts, [2, 2]);
+    }
+
+    #[test]
+    fn macro_args_of_nested_calls_are_parsed() {
+        let s = "fn a() { b!(c![], d!(e)); }";
+        let options = ParseOptions::default().with_macro_args(true);
+        let p = options.parse(s).expect("Unable to parse");
+
+        let mut calls = Vec::new();
+        for node in NodeRef::from(&p.file).walk() {
+            if let NodeRef::MacroCall(m) = node {
+                let expressions = m.expressions.as_ref().expect("Arguments were not parsed");
+                calls.push((m.extent, expressions.len()));
+            }
+        }
+        assert_eq!(calls, [((9, 24), 2), ((12, 16), 0), ((18, 23), 1)]);
+    }
+
+    #[test]
+    fn macro_args_that_are_not_expressions_are_left_alone() {
+        let s = "macro_rules! a { () => {} }";
+        let options = ParseOptions::default().with_macro_args(true);
+        let mut p = options.parse(s).expect("Unable to parse");
+        let item = p.file.items.remove(0).value;
+        let m = unwrap_as!(item, Item::MacroCall);
+        assert!(m.expressions.is_none());
+    }
+
+    #[test]
+    fn base_offset_is_added_to_extents_and_errors() {
+        let options = ParseOptions::default().with_base_offset(10);
+        let p = options.parse("fn a() {}").expect("Unable to parse");
+        assert_extent!(p.file, (10, 19));
+        assert_extent!(p.file.items[0], (10, 19));
+
+        let e = options.parse("fn a() {").unwrap_err();
+        assert_eq!(e.location(), 18);
+    }
+
+    #[test]
+    fn memoization_does_not_change_the_result() {
+        let sources = [
+            "fn a() { match b { c::D(e) => {}, c::D { e } => {}, c::D => {} } }",
+            "#[a] #[b] struct C { #[d] e: Vec<f::G<H>>, }",
+            "impl<A: B + C> D for E<A> where A: F { fn g(&self) -> H<[u8; 2]> { I::j::<K>() } }",
+            "fn a() { if b::C { d } else { e } }",
+            "fn a() { let b: c::D<;",
+        ];
+
+        for s in &sources {
+            let plain = ParseOptions::default().parse(s);
+            let memoized = ParseOptions::default().with_memoization(true).parse(s);
+            assert_eq!(format!("{:?}", plain), format!("{:?}", memoized), "{}", s);
+        }
+    }
+
+    // Pieces of Rust that random inputs are built from, to get further
+    // into the parser than random bytes usually do
+    const FRAGMENTS: &[&str] = &[
+        "fn", "a", "(", ")", "{", "}", "[", "]", "<", ">", ">>=", "::", ":", ";", ",", ".",
+        "..", "=", "==", "=>", "->", "&", "&&", "|", "||", "!", "?", "'a", "#", "'b'", "\"c\"",
+        "r#\"d\"#", "1", "2.0", "0x3u8", "let", "mut", "if", "else", "match", "for", "in",
+        "loop", "while", "impl", "trait", "struct", "enum", "where", "pub", "unsafe", "self",
+        "Self", "as", "move", "_", "$", "// e\n", "/* f */", " ", "\n",
+    ];
+
+    quickcheck! {
+        fn parse_rust_file_never_panics_on_random_bytes(bytes: Vec<u8>) -> bool {
+            let text = ::std::string::String::from_utf8_lossy(&bytes);
+            let _ = parse_rust_file(&text);
+            true
+        }
+
+        fn memoization_does_not_change_the_result_of_random_fragments(choices: Vec<u8>) -> bool {
+            let text: Vec<_> = choices.iter().map(|&c| FRAGMENTS[c as usize % FRAGMENTS.len()]).collect();
+            let text = text.join(" ");
+            let plain = ParseOptions::default().parse(&text);
+            let memoized = ParseOptions::default().with_memoization(true).parse(&text);
+            format!("{:?}", plain) == format!("{:?}", memoized)
+        }
+
+        fn parse_rust_file_never_panics_on_random_fragments(choices: Vec<u8>) -> bool {
+            let text: Vec<_> = choices.iter().map(|&c| FRAGMENTS[c as usize % FRAGMENTS.len()]).collect();
+            let _ = parse_rust_file(&text.join(" "));
+            true
+        }
+    }
 }
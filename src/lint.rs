@@ -0,0 +1,591 @@
+//! Pluggable lints over a parsed file.
+//!
+//! A `LintRule` sees every node along with its ancestors and reports
+//! `Diagnostic`s through a `Context`. A `Registry` holds the rules,
+//! runs the enabled ones in a single traversal, and drops any
+//! diagnostic whose rule is named by an `#[allow(...)]` or
+//! `#![allow(...)]` attribute that applies to it.
+
+use std::fmt;
+use std::string::String;
+
+use super::*;
+
+/// How serious a diagnostic is.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Severity::Warning => "warning".fmt(f),
+            Severity::Error => "error".fmt(f),
+        }
+    }
+}
+
+/// A problem reported by a rule.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// The name of the rule that reported this.
+    pub rule: &'static str,
+    pub severity: Severity,
+    pub extent: Extent,
+    pub message: String,
+}
+
+/// A single check that can be run by a `Registry`.
+pub trait LintRule {
+    /// The name used to enable, disable or `#[allow]` the rule.
+    fn name(&self) -> &'static str;
+
+    /// A one-line summary of what the rule looks for.
+    fn description(&self) -> &'static str;
+
+    fn default_severity(&self) -> Severity { Severity::Warning }
+
+    /// Called for each node before its children.
+    fn visit<'a>(&mut self, NodeRef<'a>, &[NodeRef<'a>], &mut Context<'a>) {}
+
+    /// Called for each node after its children.
+    fn exit<'a>(&mut self, NodeRef<'a>, &[NodeRef<'a>], &mut Context<'a>) {}
+}
+
+/// Gives rules access to the source and collects their diagnostics.
+#[derive(Debug)]
+pub struct Context<'a> {
+    source: &'a str,
+    rule: &'static str,
+    severity: Severity,
+    // The rules allowed by each enclosing node
+    allowed: Vec<Vec<&'a str>>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl<'a> Context<'a> {
+    /// The complete source of the file being checked.
+    pub fn source(&self) -> &'a str {
+        self.source
+    }
+
+    /// The source text of an extent.
+    pub fn text(&self, extent: Extent) -> &'a str {
+        &self.source[extent.0..extent.1]
+    }
+
+    /// Report a problem for the current rule, unless the rule is
+    /// allowed at this point.
+    pub fn report<S>(&mut self, extent: Extent, message: S)
+        where S: Into<String>
+    {
+        let rule = self.rule;
+        if self.allowed.iter().any(|names| names.contains(&rule)) {
+            return;
+        }
+
+        self.diagnostics.push(Diagnostic {
+            rule,
+            severity: self.severity,
+            extent,
+            message: message.into(),
+        });
+    }
+}
+
+struct Entry {
+    rule: Box<dyn LintRule>,
+    enabled: bool,
+    severity: Severity,
+}
+
+/// A collection of rules that can be run together.
+pub struct Registry {
+    entries: Vec<Entry>,
+}
+
+impl fmt::Debug for Registry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.entries.iter().map(|e| (e.rule.name(), e.enabled, e.severity))).finish()
+    }
+}
+
+impl Default for Registry {
+    fn default() -> Self {
+        Registry::with_builtin_rules()
+    }
+}
+
+impl Registry {
+    /// A registry without any rules.
+    pub fn new() -> Self {
+        Registry { entries: Vec::new() }
+    }
+
+    /// A registry with every built-in rule enabled.
+    pub fn with_builtin_rules() -> Self {
+        let mut registry = Registry::new();
+        registry.add(UnwrapUsed);
+        registry.add(PanicMacro);
+        registry.add(UndocumentedUnsafe::default());
+        registry.add(TooManyStatements::default());
+        registry
+    }
+
+    /// Add an enabled rule. A rule with the same name is replaced.
+    pub fn add<R>(&mut self, rule: R)
+        where R: LintRule + 'static
+    {
+        let name = rule.name();
+        self.entries.retain(|e| e.rule.name() != name);
+        let severity = rule.default_severity();
+        self.entries.push(Entry { rule: Box::new(rule), enabled: true, severity });
+    }
+
+    /// Replace the rule with the same name, keeping whether it is
+    /// enabled and its severity. Returns false, without adding the
+    /// rule, if there is no rule with this name.
+    pub fn configure<R>(&mut self, rule: R) -> bool
+        where R: LintRule + 'static
+    {
+        let name = rule.name();
+        self.entry_mut(name).map(|e| e.rule = Box::new(rule)).is_some()
+    }
+
+    /// Returns false if there is no rule with this name.
+    pub fn enable(&mut self, name: &str) -> bool {
+        self.entry_mut(name).map(|e| e.enabled = true).is_some()
+    }
+
+    /// Returns false if there is no rule with this name.
+    pub fn disable(&mut self, name: &str) -> bool {
+        self.entry_mut(name).map(|e| e.enabled = false).is_some()
+    }
+
+    /// Returns false if there is no rule with this name.
+    pub fn set_severity(&mut self, name: &str, severity: Severity) -> bool {
+        self.entry_mut(name).map(|e| e.severity = severity).is_some()
+    }
+
+    pub fn is_enabled(&self, name: &str) -> bool {
+        self.entries.iter().any(|e| e.enabled && e.rule.name() == name)
+    }
+
+    /// The rules, in the order they were added.
+    pub fn rules<'a>(&'a self) -> impl Iterator<Item = &'a dyn LintRule> + 'a {
+        self.entries.iter().map(|e| &*e.rule)
+    }
+
+    fn entry_mut(&mut self, name: &str) -> Option<&mut Entry> {
+        self.entries.iter_mut().find(|e| e.rule.name() == name)
+    }
+
+    /// Run the enabled rules over a file parsed from `source`. The
+    /// diagnostics are ordered by extent.
+    pub fn check(&mut self, file: &File, source: &str) -> Vec<Diagnostic> {
+        let mut runner = Runner {
+            entries: &mut self.entries,
+            cx: Context {
+                source,
+                rule: "",
+                severity: Severity::Warning,
+                allowed: Vec::new(),
+                diagnostics: Vec::new(),
+            },
+        };
+        NodeRef::from(file).visit_with_ancestors(&mut runner);
+
+        let mut diagnostics = runner.cx.diagnostics;
+        diagnostics.sort_by_key(|d| d.extent);
+        diagnostics
+    }
+}
+
+struct Runner<'r, 'a> {
+    entries: &'r mut [Entry],
+    cx: Context<'a>,
+}
+
+impl<'r, 'a> AncestorVisitor<'a> for Runner<'r, 'a> {
+    fn visit(&mut self, node: NodeRef<'a>, ancestors: &[NodeRef<'a>]) -> Control {
+        let allowed = allowed_rules(node, self.cx.source);
+        self.cx.allowed.push(allowed);
+
+        for entry in self.entries.iter_mut().filter(|e| e.enabled) {
+            self.cx.rule = entry.rule.name();
+            self.cx.severity = entry.severity;
+            entry.rule.visit(node, ancestors, &mut self.cx);
+        }
+        Control::Continue
+    }
+
+    fn exit(&mut self, node: NodeRef<'a>, ancestors: &[NodeRef<'a>]) {
+        for entry in self.entries.iter_mut().filter(|e| e.enabled) {
+            self.cx.rule = entry.rule.name();
+            self.cx.severity = entry.severity;
+            entry.rule.exit(node, ancestors, &mut self.cx);
+        }
+
+        self.cx.allowed.pop();
+    }
+}
+
+// Outer attributes are children of the `Attributed` node they apply
+// to, while inner attributes are items of the file or module.
+fn allowed_rules<'a>(node: NodeRef, source: &'a str) -> Vec<&'a str> {
+    let mut names = Vec::new();
+
+    for child in node.children() {
+        let text = match child {
+            NodeRef::Attribute(a) => a.text,
+            NodeRef::AttributedItem(a) => match a.value {
+                Item::AttributeContaining(ref a) => a.text,
+                _ => continue,
+            },
+            _ => continue,
+        };
+        names.extend(parse_allow(&source[text.0..text.1]));
+    }
+
+    names
+}
+
+fn parse_allow(text: &str) -> Vec<&str> {
+    let text = text.trim();
+    if !text.starts_with("allow") {
+        return Vec::new();
+    }
+
+    let args = text["allow".len()..].trim();
+    if !args.starts_with('(') || !args.ends_with(')') {
+        return Vec::new();
+    }
+
+    args[1..args.len() - 1].split(',').map(str::trim).filter(|n| !n.is_empty()).collect()
+}
+
+/// Reports calls to `.unwrap()` and `.expect()`.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct UnwrapUsed;
+
+impl LintRule for UnwrapUsed {
+    fn name(&self) -> &'static str { "unwrap_used" }
+
+    fn description(&self) -> &'static str { "calls to `.unwrap()` or `.expect()`" }
+
+    fn visit<'a>(&mut self, node: NodeRef<'a>, _: &[NodeRef<'a>], cx: &mut Context<'a>) {
+        let call = match node {
+            NodeRef::Call(call) => call,
+            _ => return,
+        };
+        let method = match call.target.value {
+            Expression::FieldAccess(ref access) => match access.field {
+                FieldName::Path(ref component) => component.ident.extent,
+                FieldName::Number(_) => return,
+            },
+            _ => return,
+        };
+
+        let name = cx.text(method);
+        if name == "unwrap" || name == "expect" {
+            cx.report(method, format!("called `.{}()`, which panics on failure", name));
+        }
+    }
+}
+
+/// Reports uses of `panic!`, `todo!` and `unimplemented!`.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct PanicMacro;
+
+impl LintRule for PanicMacro {
+    fn name(&self) -> &'static str { "panic_macro" }
+
+    fn description(&self) -> &'static str { "uses of `panic!`, `todo!` or `unimplemented!`" }
+
+    fn visit<'a>(&mut self, node: NodeRef<'a>, _: &[NodeRef<'a>], cx: &mut Context<'a>) {
+        if let NodeRef::MacroCall(m) = node {
+            let name = cx.text(m.name.extent);
+            if name == "panic" || name == "todo" || name == "unimplemented" {
+                cx.report(m.extent, format!("`{}!` panics at runtime", name));
+            }
+        }
+    }
+}
+
+/// Reports `unsafe` blocks that are not preceded by a comment
+/// containing `SAFETY:`.
+///
+/// The comment may be on the lines directly above the one where the
+/// block starts, or earlier on that same line.
+#[derive(Debug, Clone, Default)]
+pub struct UndocumentedUnsafe {
+    // The whitespace and comments of the file being checked
+    trivia: Vec<Token>,
+}
+
+impl LintRule for UndocumentedUnsafe {
+    fn name(&self) -> &'static str { "undocumented_unsafe" }
+
+    fn description(&self) -> &'static str { "`unsafe` blocks without a `// SAFETY:` comment" }
+
+    fn visit<'a>(&mut self, node: NodeRef<'a>, _: &[NodeRef<'a>], cx: &mut Context<'a>) {
+        match node {
+            NodeRef::File(_) => {
+                // The file has been parsed, so it tokenizes
+                self.trivia = Tokens::new(cx.source())
+                    .filter_map(Result::ok)
+                    .filter(Token::is_trivia)
+                    .collect();
+            }
+            NodeRef::UnsafeBlock(block) => {
+                if !has_safety_comment(cx.source(), &self.trivia, block.extent.0) {
+                    cx.report(block.extent, "`unsafe` block without a `// SAFETY:` comment");
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+// Walks back from `start` over the trivia. Code is allowed earlier on
+// the same line, but not on the lines above, which also must not be
+// separated by a blank line.
+fn has_safety_comment(source: &str, trivia: &[Token], start: usize) -> bool {
+    let line_start = source[..start].rfind('\n').map_or(0, |i| i + 1);
+    let before = match trivia.binary_search_by_key(&start, |t| t.extent().0) {
+        Ok(i) | Err(i) => i,
+    };
+
+    let mut next_start = start;
+    for t in trivia[..before].iter().rev() {
+        let (t_start, t_end) = t.extent();
+        if t_end != next_start && t_end < line_start {
+            return false;
+        }
+
+        let text = &source[t_start..t_end];
+        match *t {
+            Token::Whitespace(..) => {
+                if text.matches('\n').count() > 1 {
+                    return false;
+                }
+            }
+            _ => {
+                if text.contains("SAFETY:") {
+                    return true;
+                }
+            }
+        }
+        next_start = t_start;
+    }
+
+    false
+}
+
+/// Reports functions whose bodies contain more than `max` statements,
+/// including statements in nested blocks and closures but not in
+/// nested functions.
+#[derive(Debug, Copy, Clone)]
+pub struct TooManyStatements {
+    pub max: usize,
+}
+
+impl Default for TooManyStatements {
+    fn default() -> Self {
+        TooManyStatements { max: 50 }
+    }
+}
+
+impl LintRule for TooManyStatements {
+    fn name(&self) -> &'static str { "too_many_statements" }
+
+    fn description(&self) -> &'static str { "functions with too many statements" }
+
+    fn visit<'a>(&mut self, node: NodeRef<'a>, _: &[NodeRef<'a>], cx: &mut Context<'a>) {
+        let (name, body) = match node {
+            NodeRef::Function(f) => (f.header.name, &f.body),
+            NodeRef::ImplFunction(f) => (f.header.name, &f.body),
+            NodeRef::TraitMemberFunction(f) => match f.body {
+                Some(ref body) => (f.header.name, body),
+                None => return,
+            },
+            _ => return,
+        };
+
+        let count = count_statements(NodeRef::from(body));
+        if count > self.max {
+            let message = format!("function `{}` has {} statements, more than the maximum of {}",
+                                  cx.text(name.extent), count, self.max);
+            cx.report(name.extent, message);
+        }
+    }
+}
+
+fn count_statements(body: NodeRef) -> usize {
+    let mut count = 0;
+    let mut stack = vec![body];
+
+    while let Some(node) = stack.pop() {
+        match node {
+            NodeRef::Function(_) | NodeRef::ImplFunction(_) | NodeRef::TraitMemberFunction(_) => continue,
+            NodeRef::Statement(_) => count += 1,
+            _ => {}
+        }
+        stack.extend(node.children());
+    }
+
+    count
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn check(registry: &mut Registry, s: &str) -> Vec<(&'static str, String)> {
+        let file = parse_rust_file(s).expect("Unable to parse");
+        registry.check(&file, s).into_iter()
+            .map(|d| (d.rule, s[d.extent.0..d.extent.1].to_string()))
+            .collect()
+    }
+
+    fn check_builtin(s: &str) -> Vec<(&'static str, String)> {
+        check(&mut Registry::with_builtin_rules(), s)
+    }
+
+    #[test]
+    fn unwrap_and_expect_calls() {
+        let d = check_builtin("fn a() { b.unwrap(); c().expect(\"d\"); e.unwrap_or(f); g.unwrap; }");
+        assert_eq!(d, vec![("unwrap_used", "unwrap".into()), ("unwrap_used", "expect".into())]);
+    }
+
+    #[test]
+    fn panicking_macros() {
+        let d = check_builtin("fn a() { panic!(\"b\"); todo!(); unimplemented!(); println!(); }");
+        let rules: Vec<_> = d.iter().map(|&(r, _)| r).collect();
+        assert_eq!(rules, vec!["panic_macro"; 3]);
+        assert_eq!(d[0].1, "panic!(\"b\")");
+    }
+
+    #[test]
+    fn unsafe_blocks_need_a_safety_comment() {
+        let s = "fn a() {
+    unsafe { b() }
+    // SAFETY: c is valid
+    unsafe { c() }
+    // SAFETY: d is valid
+
+    unsafe { d() }
+    let e = /* SAFETY: e is valid */ unsafe { e() };
+    let f = \"SAFETY: f is valid\"; unsafe { f() }
+    /* SAFETY: g is valid
+     */
+    let g = unsafe { g() };
+}";
+        let d = check_builtin(s);
+        assert_eq!(d, vec![
+            ("undocumented_unsafe", "unsafe { b() }".into()),
+            ("undocumented_unsafe", "unsafe { d() }".into()),
+            ("undocumented_unsafe", "unsafe { f() }".into()),
+        ]);
+    }
+
+    #[test]
+    fn configuring_a_rule_keeps_its_state() {
+        let mut registry = Registry::new();
+        registry.add(TooManyStatements { max: 2 });
+        registry.set_severity("too_many_statements", Severity::Error);
+        registry.disable("too_many_statements");
+
+        assert!(registry.configure(TooManyStatements { max: 0 }));
+        assert!(!registry.is_enabled("too_many_statements"));
+
+        registry.enable("too_many_statements");
+        let file = parse_rust_file("fn a() { b; }").expect("Unable to parse");
+        let d = registry.check(&file, "fn a() { b; }");
+        assert_eq!(d.len(), 1);
+        assert_eq!(d[0].severity, Severity::Error);
+
+        assert!(!Registry::new().configure(PanicMacro));
+    }
+
+    #[test]
+    fn functions_with_too_many_statements() {
+        let mut registry = Registry::new();
+        registry.add(TooManyStatements { max: 2 });
+
+        let d = check(&mut registry, "fn a() { b; c; } fn d() { if e { f; g; } h; fn i() { j; } }");
+        assert_eq!(d, vec![("too_many_statements", "d".into())]);
+    }
+
+    #[test]
+    fn rules_can_be_disabled() {
+        let mut registry = Registry::with_builtin_rules();
+        assert!(registry.disable("unwrap_used"));
+        assert!(!registry.disable("no_such_rule"));
+
+        assert_eq!(check(&mut registry, "fn a() { b.unwrap(); }"), vec![]);
+
+        assert!(registry.enable("unwrap_used"));
+        assert_eq!(check(&mut registry, "fn a() { b.unwrap(); }").len(), 1);
+    }
+
+    #[test]
+    fn severity_can_be_changed() {
+        let mut registry = Registry::with_builtin_rules();
+        assert!(registry.set_severity("panic_macro", Severity::Error));
+
+        let file = parse_rust_file("fn a() { panic!() }").unwrap();
+        let d = registry.check(&file, "fn a() { panic!() }");
+        assert_eq!(d[0].severity, Severity::Error);
+    }
+
+    #[test]
+    fn allow_attributes_suppress_rules() {
+        let s = "#[allow(unwrap_used, dead_code)] fn a() { b.unwrap(); panic!() }
+fn c() { #[allow(panic_macro)] { panic!() } d.unwrap(); }";
+        let d = check_builtin(s);
+        assert_eq!(d, vec![
+            ("panic_macro", "panic!()".into()),
+            ("unwrap_used", "unwrap".into()),
+        ]);
+    }
+
+    #[test]
+    fn inner_allow_attributes_apply_to_the_file_and_modules() {
+        let s = "#![allow(panic_macro)] fn a() { panic!() } mod b { #![allow(unwrap_used)] fn c() { d.unwrap() } }";
+        assert_eq!(check_builtin(s), vec![]);
+    }
+
+    #[test]
+    fn custom_rules() {
+        struct NoBlah;
+
+        impl LintRule for NoBlah {
+            fn name(&self) -> &'static str { "no_blah" }
+            fn description(&self) -> &'static str { "identifiers named `blah`" }
+            fn default_severity(&self) -> Severity { Severity::Error }
+
+            fn visit<'a>(&mut self, node: NodeRef<'a>, _: &[NodeRef<'a>], cx: &mut Context<'a>) {
+                if let NodeRef::Ident(ident) = node {
+                    if cx.text(ident.extent) == "blah" {
+                        cx.report(ident.extent, "no blah");
+                    }
+                }
+            }
+        }
+
+        let mut registry = Registry::new();
+        registry.add(NoBlah);
+        let file = parse_rust_file("fn blah() {}").unwrap();
+        let d = registry.check(&file, "fn blah() {}");
+
+        assert_eq!(d, vec![Diagnostic {
+            rule: "no_blah",
+            severity: Severity::Error,
+            extent: (3, 7),
+            message: "no blah".into(),
+        }]);
+    }
+}
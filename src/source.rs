@@ -0,0 +1,496 @@
+//! Conversions between byte offsets and human-oriented positions.
+//!
+//! Every `Extent` produced by the parser is measured in bytes. Editors
+//! and terminals prefer to talk about lines and columns, and they do
+//! not agree on what a column is: a CLI usually counts characters
+//! while the Language Server Protocol counts UTF-16 code units.
+//!
+//! When working with more than one file, a `SourceMap` remembers
+//! which file each `Span` belongs to.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::mem;
+
+use super::{Extent, ErrorDetail, ErrorDetailText};
+
+/// What a column counts.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ColumnUnit {
+    /// UTF-8 bytes
+    Byte,
+    /// Unicode scalar values
+    Char,
+    /// UTF-16 code units
+    Utf16,
+}
+
+/// A zero-based line and column pair.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct LineColumn {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl LineColumn {
+    pub fn new(line: usize, column: usize) -> Self {
+        LineColumn { line, column }
+    }
+}
+
+// A character that is more than one byte wide. `start` is relative
+// to the start of its line.
+#[derive(Debug, Copy, Clone)]
+struct WideChar {
+    start: usize,
+    len_utf8: usize,
+    len_utf16: usize,
+}
+
+impl WideChar {
+    fn end(&self) -> usize { self.start + self.len_utf8 }
+
+    fn len(&self, unit: ColumnUnit) -> usize {
+        match unit {
+            ColumnUnit::Byte => self.len_utf8,
+            ColumnUnit::Char => 1,
+            ColumnUnit::Utf16 => self.len_utf16,
+        }
+    }
+}
+
+/// Maps byte offsets to lines and columns, and back again.
+///
+/// Build one per source text and reuse it; lookups are logarithmic
+/// in the number of lines.
+#[derive(Debug, Clone)]
+pub struct LineIndex {
+    len: usize,
+    line_starts: Vec<usize>,
+    wide_chars: BTreeMap<usize, Vec<WideChar>>,
+}
+
+impl LineIndex {
+    pub fn new(text: &str) -> Self {
+        let mut line_starts = vec![0];
+        let mut wide_chars = BTreeMap::new();
+        let mut line_wide_chars = Vec::new();
+
+        for (i, c) in text.char_indices() {
+            let line_start = line_starts[line_starts.len() - 1];
+
+            if c == '\n' {
+                if !line_wide_chars.is_empty() {
+                    let line = line_starts.len() - 1;
+                    wide_chars.insert(line, mem::replace(&mut line_wide_chars, Vec::new()));
+                }
+                line_starts.push(i + 1);
+            } else if c.len_utf8() > 1 {
+                line_wide_chars.push(WideChar {
+                    start: i - line_start,
+                    len_utf8: c.len_utf8(),
+                    len_utf16: c.len_utf16(),
+                });
+            }
+        }
+
+        if !line_wide_chars.is_empty() {
+            wide_chars.insert(line_starts.len() - 1, line_wide_chars);
+        }
+
+        LineIndex { len: text.len(), line_starts, wide_chars }
+    }
+
+    /// The length in bytes of the indexed text.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The number of lines. An empty text has one empty line.
+    pub fn line_count(&self) -> usize {
+        self.line_starts.len()
+    }
+
+    /// The byte range of a line, not including the newline.
+    pub fn line_extent(&self, line: usize) -> Option<Extent> {
+        let start = *self.line_starts.get(line)?;
+        let end = self.line_starts.get(line + 1).map_or(self.len, |&next| next - 1);
+        Some((start, end))
+    }
+
+    /// Find the line and column of a byte offset. An offset inside a
+    /// multibyte character is reported as the start of that character.
+    pub fn to_line_column(&self, offset: usize, unit: ColumnUnit) -> Option<LineColumn> {
+        if offset > self.len { return None }
+
+        let line = match self.line_starts.binary_search(&offset) {
+            Ok(line) => line,
+            Err(next_line) => next_line - 1,
+        };
+        let wide_chars = self.wide_chars_of(line);
+
+        let mut byte_column = offset - self.line_starts[line];
+        if let Some(wc) = wide_chars.iter().find(|wc| wc.start < byte_column && byte_column < wc.end()) {
+            byte_column = wc.start;
+        }
+
+        let column = wide_chars.iter()
+            .take_while(|wc| wc.end() <= byte_column)
+            .fold(byte_column, |column, wc| column - wc.len_utf8 + wc.len(unit));
+
+        Some(LineColumn { line, column })
+    }
+
+    /// Find the byte offset of a line and column. Fails if the
+    /// position is past the end of the line or lands inside of a
+    /// character.
+    pub fn to_offset(&self, position: LineColumn, unit: ColumnUnit) -> Option<usize> {
+        let (start, end) = self.line_extent(position.line)?;
+        let mut byte_column = position.column;
+        let mut column = 0;
+        let mut consumed_bytes = 0;
+
+        for wc in self.wide_chars_of(position.line) {
+            let wc_column = column + (wc.start - consumed_bytes);
+            if position.column <= wc_column { break }
+            if position.column < wc_column + wc.len(unit) { return None }
+
+            byte_column = byte_column + wc.len_utf8 - wc.len(unit);
+            column = wc_column + wc.len(unit);
+            consumed_bytes = wc.end();
+        }
+
+        let offset = start + byte_column;
+        if offset > end { return None }
+        Some(offset)
+    }
+
+    /// Convert both ends of an extent to lines and columns.
+    pub fn to_line_columns(&self, extent: Extent, unit: ColumnUnit) -> Option<(LineColumn, LineColumn)> {
+        let start = self.to_line_column(extent.0, unit)?;
+        let end = self.to_line_column(extent.1, unit)?;
+        Some((start, end))
+    }
+
+    /// Convert a pair of lines and columns to an extent.
+    pub fn to_extent(&self, start: LineColumn, end: LineColumn, unit: ColumnUnit) -> Option<Extent> {
+        let start = self.to_offset(start, unit)?;
+        let end = self.to_offset(end, unit)?;
+        if start > end { return None }
+        Some((start, end))
+    }
+
+    fn wide_chars_of(&self, line: usize) -> &[WideChar] {
+        self.wide_chars.get(&line).map_or(&[], |w| &w[..])
+    }
+}
+
+/// Where a fragment of text starts inside of a larger document, such
+/// as a code block inside of Markdown.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct Origin {
+    /// The byte offset of the fragment in the document.
+    pub offset: usize,
+    /// The line and column of the fragment in the document. The
+    /// column only applies to the first line of the fragment.
+    pub line_column: LineColumn,
+}
+
+impl Origin {
+    /// Find the line and column in the document of an offset in the
+    /// document, using an index of only the fragment. The column is
+    /// counted in the same unit as the origin's column.
+    pub fn to_line_column(&self, index: &LineIndex, offset: usize, unit: ColumnUnit) -> Option<LineColumn> {
+        let offset = offset.checked_sub(self.offset)?;
+        let position = index.to_line_column(offset, unit)?;
+
+        let column = if position.line == 0 {
+            self.line_column.column + position.column
+        } else {
+            position.column
+        };
+
+        Some(LineColumn { line: self.line_column.line + position.line, column })
+    }
+}
+
+/// Identifies one file inside of a `SourceMap`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FileId(usize);
+
+/// An extent that knows which file it belongs to.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Span {
+    pub file: FileId,
+    pub extent: Extent,
+}
+
+/// A single file owned by a `SourceMap`.
+#[derive(Debug)]
+pub struct SourceFile {
+    id: FileId,
+    name: String,
+    text: String,
+    start: usize,
+    index: LineIndex,
+}
+
+impl SourceFile {
+    pub fn id(&self) -> FileId { self.id }
+    pub fn name(&self) -> &str { &self.name }
+    pub fn text(&self) -> &str { &self.text }
+    pub fn line_index(&self) -> &LineIndex { &self.index }
+
+    /// The range of global offsets occupied by this file.
+    pub fn global_extent(&self) -> Extent {
+        (self.start, self.start + self.text.len())
+    }
+
+    /// Attach this file to an extent produced by parsing its text.
+    pub fn span(&self, extent: Extent) -> Span {
+        Span { file: self.id, extent }
+    }
+
+    /// Render an error that occurred while parsing this file.
+    pub fn error_text<'a>(&'a self, detail: &'a ErrorDetail) -> ErrorDetailText<'a> {
+        detail.with_text(&self.text).with_index(&self.index)
+    }
+}
+
+/// Owns the text of many files.
+///
+/// Each file is assigned a `FileId` as well as a distinct range of
+/// global offsets, so a single number can also identify a position
+/// across every file.
+#[derive(Debug, Default)]
+pub struct SourceMap {
+    files: Vec<SourceFile>,
+}
+
+impl SourceMap {
+    pub fn new() -> Self {
+        SourceMap::default()
+    }
+
+    pub fn add_file<N, T>(&mut self, name: N, text: T) -> FileId
+        where N: Into<String>,
+              T: Into<String>,
+    {
+        // Leave a gap after each file so that the end of one file is
+        // not also the start of the next.
+        let start = self.files.last().map_or(0, |f| f.global_extent().1 + 1);
+        let id = FileId(self.files.len());
+        let text = text.into();
+        let index = LineIndex::new(&text);
+
+        self.files.push(SourceFile { id, name: name.into(), text, start, index });
+        id
+    }
+
+    pub fn file(&self, id: FileId) -> &SourceFile {
+        &self.files[id.0]
+    }
+
+    pub fn files<'a>(&'a self) -> impl Iterator<Item = &'a SourceFile> + 'a {
+        self.files.iter()
+    }
+
+    /// Find the file containing a global offset.
+    pub fn file_at(&self, global_offset: usize) -> Option<&SourceFile> {
+        let idx = match self.files.binary_search_by_key(&global_offset, |f| f.start) {
+            Ok(idx) => idx,
+            Err(0) => return None,
+            Err(next_idx) => next_idx - 1,
+        };
+        let file = &self.files[idx];
+        if global_offset <= file.global_extent().1 { Some(file) } else { None }
+    }
+
+    /// Convert a span to a pair of global offsets.
+    pub fn to_global(&self, span: Span) -> Extent {
+        let start = self.file(span.file).start;
+        (start + span.extent.0, start + span.extent.1)
+    }
+
+    /// Convert a pair of global offsets to a span. Both offsets must
+    /// be inside of the same file.
+    pub fn from_global(&self, global: Extent) -> Option<Span> {
+        let file = self.file_at(global.0)?;
+        let (start, end) = file.global_extent();
+        if global.1 < global.0 || global.1 > end { return None }
+        Some(file.span((global.0 - start, global.1 - start)))
+    }
+
+    /// The text covered by a span.
+    pub fn source_text(&self, span: Span) -> &str {
+        &self.file(span.file).text[span.extent.0..span.extent.1]
+    }
+
+    /// Find the file name, lines and columns of a span.
+    pub fn resolve(&self, span: Span, unit: ColumnUnit) -> Option<SpanLocation> {
+        let file = self.file(span.file);
+        let (start, end) = file.index.to_line_columns(span.extent, unit)?;
+        Some(SpanLocation { file: span.file, name: &file.name, start, end })
+    }
+}
+
+/// A span resolved to human-oriented positions.
+///
+/// Displays as `name:line:column`, counting lines and columns from
+/// one.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct SpanLocation<'a> {
+    pub file: FileId,
+    pub name: &'a str,
+    pub start: LineColumn,
+    pub end: LineColumn,
+}
+
+impl<'a> fmt::Display for SpanLocation<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}:{}", self.name, self.start.line + 1, self.start.column + 1)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn lc(line: usize, column: usize) -> LineColumn {
+        LineColumn::new(line, column)
+    }
+
+    #[test]
+    fn empty_text_has_one_line() {
+        let idx = LineIndex::new("");
+        assert_eq!(idx.line_count(), 1);
+        assert_eq!(idx.line_extent(0), Some((0, 0)));
+        assert_eq!(idx.to_line_column(0, ColumnUnit::Byte), Some(lc(0, 0)));
+        assert_eq!(idx.to_line_column(1, ColumnUnit::Byte), None);
+    }
+
+    #[test]
+    fn ascii_offsets_map_to_lines_and_columns() {
+        let idx = LineIndex::new("fn a() {}\nstruct B;\n");
+        assert_eq!(idx.line_count(), 3);
+        assert_eq!(idx.to_line_column(3, ColumnUnit::Byte), Some(lc(0, 3)));
+        assert_eq!(idx.to_line_column(9, ColumnUnit::Byte), Some(lc(0, 9)));
+        assert_eq!(idx.to_line_column(10, ColumnUnit::Byte), Some(lc(1, 0)));
+        assert_eq!(idx.to_line_column(20, ColumnUnit::Char), Some(lc(2, 0)));
+        assert_eq!(idx.line_extent(1), Some((10, 19)));
+    }
+
+    #[test]
+    fn columns_can_be_counted_in_chars_or_utf16() {
+        // 'é' is 2 bytes / 1 UTF-16 unit, '😀' is 4 bytes / 2 UTF-16 units
+        let text = "x\nlet é = '😀'; y";
+        let idx = LineIndex::new(text);
+        let y = text.find('y').unwrap();
+
+        assert_eq!(idx.to_line_column(y, ColumnUnit::Byte), Some(lc(1, 17)));
+        assert_eq!(idx.to_line_column(y, ColumnUnit::Char), Some(lc(1, 13)));
+        assert_eq!(idx.to_line_column(y, ColumnUnit::Utf16), Some(lc(1, 14)));
+    }
+
+    #[test]
+    fn offsets_inside_a_character_snap_to_its_start() {
+        let text = "a😀b";
+        let idx = LineIndex::new(text);
+        assert_eq!(idx.to_line_column(2, ColumnUnit::Char), Some(lc(0, 1)));
+        assert_eq!(idx.to_line_column(3, ColumnUnit::Utf16), Some(lc(0, 1)));
+        assert_eq!(idx.to_line_column(4, ColumnUnit::Byte), Some(lc(0, 1)));
+    }
+
+    #[test]
+    fn line_columns_map_back_to_offsets() {
+        let text = "x\nlet é = '😀'; y\n";
+        let idx = LineIndex::new(text);
+        let y = text.find('y').unwrap();
+
+        assert_eq!(idx.to_offset(lc(1, 17), ColumnUnit::Byte), Some(y));
+        assert_eq!(idx.to_offset(lc(1, 13), ColumnUnit::Char), Some(y));
+        assert_eq!(idx.to_offset(lc(1, 14), ColumnUnit::Utf16), Some(y));
+        assert_eq!(idx.to_offset(lc(2, 0), ColumnUnit::Char), Some(text.len()));
+    }
+
+    #[test]
+    fn invalid_line_columns_have_no_offset() {
+        let idx = LineIndex::new("a😀b\nc");
+        assert_eq!(idx.to_offset(lc(0, 4), ColumnUnit::Char), None);
+        assert_eq!(idx.to_offset(lc(0, 2), ColumnUnit::Utf16), None);
+        assert_eq!(idx.to_offset(lc(0, 3), ColumnUnit::Byte), None);
+        assert_eq!(idx.to_offset(lc(2, 0), ColumnUnit::Byte), None);
+    }
+
+    #[test]
+    fn extents_round_trip() {
+        let text = "fn ä() {\n    '😀'\n}";
+        let idx = LineIndex::new(text);
+        let extent = (text.find('\'').unwrap(), text.rfind('\'').unwrap() + 1);
+
+        for &unit in &[ColumnUnit::Byte, ColumnUnit::Char, ColumnUnit::Utf16] {
+            let (start, end) = idx.to_line_columns(extent, unit).unwrap();
+            assert_eq!(start.line, 1);
+            assert_eq!(idx.to_extent(start, end, unit), Some(extent));
+        }
+    }
+
+    #[test]
+    fn source_map_assigns_distinct_global_ranges() {
+        let mut sm = SourceMap::new();
+        let a = sm.add_file("a.rs", "fn a() {}");
+        let b = sm.add_file("b.rs", "");
+        let c = sm.add_file("c.rs", "struct C;");
+
+        assert_eq!(sm.file(a).global_extent(), (0, 9));
+        assert_eq!(sm.file(b).global_extent(), (10, 10));
+        assert_eq!(sm.file(c).global_extent(), (11, 20));
+
+        assert_eq!(sm.file_at(9).map(SourceFile::id), Some(a));
+        assert_eq!(sm.file_at(10).map(SourceFile::id), Some(b));
+        assert_eq!(sm.file_at(11).map(SourceFile::id), Some(c));
+        assert_eq!(sm.file_at(21).map(SourceFile::id), None);
+    }
+
+    #[test]
+    fn spans_convert_to_and_from_global_offsets() {
+        let mut sm = SourceMap::new();
+        sm.add_file("a.rs", "fn a() {}");
+        let b = sm.add_file("b.rs", "struct B;");
+
+        let span = Span { file: b, extent: (7, 8) };
+        let global = sm.to_global(span);
+        assert_eq!(global, (17, 18));
+        assert_eq!(sm.from_global(global), Some(span));
+        assert_eq!(sm.from_global((5, 15)), None);
+        assert_eq!(sm.source_text(span), "B");
+    }
+
+    #[test]
+    fn spans_resolve_to_file_line_and_column() {
+        let mut sm = SourceMap::new();
+        sm.add_file("a.rs", "fn a() {}");
+        let b = sm.add_file("src/b.rs", "// ü\nstruct B;");
+
+        let loc = sm.resolve(Span { file: b, extent: (13, 14) }, ColumnUnit::Char).unwrap();
+        assert_eq!(loc.name, "src/b.rs");
+        assert_eq!(loc.start, lc(1, 7));
+        assert_eq!(loc.end, lc(1, 8));
+        assert_eq!(loc.to_string(), "src/b.rs:2:8");
+    }
+
+    #[test]
+    fn origin_positions_are_relative_to_the_document() {
+        let idx = LineIndex::new("a\nbc");
+        let origin = Origin { offset: 100, line_column: lc(10, 4) };
+
+        assert_eq!(origin.to_line_column(&idx, 100, ColumnUnit::Char), Some(lc(10, 4)));
+        assert_eq!(origin.to_line_column(&idx, 101, ColumnUnit::Char), Some(lc(10, 5)));
+        assert_eq!(origin.to_line_column(&idx, 103, ColumnUnit::Char), Some(lc(11, 1)));
+        assert_eq!(origin.to_line_column(&idx, 99, ColumnUnit::Char), None);
+    }
+}
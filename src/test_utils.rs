@@ -19,15 +19,14 @@ pub fn parse_full<'s, F, T>(f: F, s: &'s str) -> TestResult<T>
     where F: for<'a> FnOnce(&mut Master<'a>, Point<'a>) -> Progress<'a, T>
 {
     // TODO: Master::once()?
-    let tokens = Tokens::new(s).collect::<Result<Vec<_>, _>>().expect("Unable to tokenize");
-    let (_ws, tokens): (Vec<_>, Vec<_>) = tokens.into_iter().partition(|t| {
-        t.is_whitespace() || t.is_comment() || t.is_doc_comment() || t.is_comment_block() || t.is_doc_comment_block()
-    });
+    let buffer = TokenBuffer::new(Tokens::new(s)).expect("Unable to tokenize");
 
     let mut pm = Master::with_state(State::new());
-    let pt = Point::new(&tokens);
+    let pt = Point::new(buffer.tokens());
     let r = f(&mut pm, pt);
-    match pm.finish(r) {
+    let r = pm.finish(r);
+    assert_eq!(pm.state.invalid_extent.get(), None, "Calculated an invalid extent");
+    match r {
         peresil::Progress { status: peresil::Status::Success(v), point } => {
             Ok((point.offset, v))
         }
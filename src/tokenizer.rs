@@ -6,8 +6,10 @@ use peresil;
 use peresil::combinators::*;
 
 use super::{Extent, HumanTextError, not, peek};
+use source::{LineIndex, Origin};
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Decompose)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Token {
     // Paired delimiters
     LeftAngle(Extent),
@@ -70,12 +72,15 @@ pub enum Token {
 
     // Keywords
     As(Extent),
+    Async(Extent),
+    Await(Extent),
     Box(Extent),
     Break(Extent),
     Const(Extent),
     Continue(Extent),
     Crate(Extent),
     Default(Extent),
+    Dyn(Extent),
     Else(Extent),
     Enum(Extent),
     Extern(Extent),
@@ -97,6 +102,7 @@ pub enum Token {
     Static(Extent),
     Struct(Extent),
     Trait(Extent),
+    Try(Extent),
     Type(Extent),
     Union(Extent),
     Unsafe(Extent),
@@ -133,7 +139,9 @@ impl Token {
             AmpersandEquals(s)     |
             As(s)                  |
             Asterisk(s)            |
+            Async(s)               |
             At(s)                  |
+            Await(s)               |
             Backslash(s)           |
             Bang(s)                |
             Box(s)                 |
@@ -163,6 +171,7 @@ impl Token {
             DoublePeriod(s)        |
             DoublePipe(s)          |
             DoubleRightAngle(s)    |
+            Dyn(s)                 |
             Else(s)                |
             EndOfFile(s)           |
             Enum(s)                |
@@ -221,6 +230,7 @@ impl Token {
             TimesEquals(s)         |
             Trait(s)               |
             TriplePeriod(s)        |
+            Try(s)                 |
             Type(s)                |
             Union(s)               |
             Unsafe(s)              |
@@ -232,9 +242,20 @@ impl Token {
             Number(s) => s.extent(),
         }
     }
+
+    /// Whitespace and comments, which the parser skips.
+    pub fn is_trivia(&self) -> bool {
+        use self::Token::*;
+
+        match *self {
+            Whitespace(..) | Comment(..) | CommentBlock(..) | DocComment(..) | DocCommentBlock(..) => true,
+            _ => false,
+        }
+    }
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Decompose)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Number {
     Binary(NumberBinary),
     Decimal(NumberDecimal),
@@ -275,6 +296,7 @@ impl Number {
 macro_rules! number {
     ($name:ident) => {
         #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+        #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
         pub struct $name {
             pub extent: Extent,
             pub integral: Extent,
@@ -319,6 +341,9 @@ pub enum Error {
 
     // Internal parsing errors, should be recovered
     InvalidFollowForFractionalNumber,
+
+    // The tokenizer got confused; this is a bug in the tokenizer
+    UnableToMakeProgress,
 }
 
 impl peresil::Recoverable for Error {
@@ -333,23 +358,56 @@ pub struct ErrorDetail {
 
 impl ErrorDetail {
     pub fn with_text<'a>(&'a self, text: &'a str) -> ErrorDetailText<'a> {
-        ErrorDetailText { detail: self, text }
+        ErrorDetailText { detail: self, text, index: None, origin: Origin::default() }
+    }
+
+    /// Like `with_text`, but reuses an existing index of the text.
+    pub fn with_text_and_index<'a>(&'a self, text: &'a str, index: &'a LineIndex) -> ErrorDetailText<'a> {
+        self.with_text(text).with_index(index)
     }
+
+    /// The byte offset of the text that could not be tokenized.
+    pub fn location(&self) -> usize { self.location }
+
+    /// What was expected at the location.
+    pub fn errors(&self) -> &BTreeSet<Error> { &self.errors }
 }
 
 #[derive(Debug)]
 pub struct ErrorDetailText<'a> {
     detail: &'a ErrorDetail,
     text: &'a str,
+    index: Option<&'a LineIndex>,
+    origin: Origin,
+}
+
+impl<'a> ErrorDetailText<'a> {
+    /// Reuse an existing index of the text instead of building one.
+    pub fn with_index(self, index: &'a LineIndex) -> Self {
+        ErrorDetailText { index: Some(index), ..self }
+    }
+
+    /// Treat the text as a fragment of a larger document.
+    pub fn with_origin(self, origin: Origin) -> Self {
+        ErrorDetailText { origin, ..self }
+    }
 }
 
 impl<'a> fmt::Display for ErrorDetailText<'a> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let human = HumanTextError::new(self.text, self.detail.location);
-
-        writeln!(f, "Unable to tokenize text (line {}, column {})", human.line, human.column)?;
-        writeln!(f, "{}{}", human.head_of_line, human.tail_of_line)?;
-        writeln!(f, "{:>width$}", "^", width = human.column)?;
+        let built;
+        let index = match self.index {
+            Some(index) => index,
+            None => { built = LineIndex::new(self.text); &built }
+        };
+        match HumanTextError::new(self.text, index, self.origin, self.detail.location) {
+            Some(human) => {
+                writeln!(f, "Unable to tokenize text (line {}, column {})", human.line, human.column)?;
+                writeln!(f, "{}{}", human.head_of_line, human.tail_of_line)?;
+                writeln!(f, "{:>width$}", "^", width = human.caret_column)?;
+            }
+            None => writeln!(f, "Unable to tokenize text (byte {})", self.detail.location)?,
+        }
         writeln!(f, "Expected:")?;
         for e in &self.detail.errors {
             writeln!(f, "  {:?}", e)?; // TODO: should be Display
@@ -370,9 +428,16 @@ pub struct Tokens<'s> {
 
 impl<'s> Tokens<'s> {
     pub fn new(code: &'s str) -> Self {
+        Tokens::with_offset(code, 0)
+    }
+
+    /// Tokenize code that starts `offset` bytes into a larger
+    /// document. All extents and error locations will be relative to
+    /// the start of that document.
+    pub fn with_offset(code: &'s str, offset: usize) -> Self {
         Tokens {
             pm: Master::new(),
-            pt: Point::new(code),
+            pt: Point { s: code, offset },
             is_exhausted: false,
         }
     }
@@ -395,8 +460,14 @@ impl<'s> Iterator for Tokens<'s> {
         let tok = self.pm.finish(tok);
 
         match tok {
+            peresil::Progress { status: peresil::Status::Success(_), point } if point.offset == self.pt.offset => {
+                self.is_exhausted = true;
+                Some(Err(ErrorDetail {
+                    location: point.offset,
+                    errors: Some(Error::UnableToMakeProgress).into_iter().collect(),
+                }))
+            }
             peresil::Progress { status: peresil::Status::Success(value), point } => {
-                assert_ne!(self.pt.offset, point.offset, "Tokenizer did not make progress");
                 self.pt = point;
                 Some(Ok(value))
             }
@@ -410,6 +481,97 @@ impl<'s> Iterator for Tokens<'s> {
     }
 }
 
+/// The whitespace and comments of some text, along with where they
+/// fall between the other tokens.
+#[derive(Debug, Default)]
+pub struct Trivia {
+    tokens: Vec<Token>,
+    // For each other token, how much trivia comes before it
+    ends: Vec<u32>,
+}
+
+impl Trivia {
+    /// The whitespace and comments, in order.
+    pub fn tokens(&self) -> &[Token] { &self.tokens }
+
+    /// The whitespace and comments between the token at `index` and
+    /// the one before it, or `None` if there is no token at `index`.
+    pub fn before(&self, index: usize) -> Option<&[Token]> {
+        self.ends.get(index).map(|&end| {
+            let start = if index == 0 { 0 } else { self.ends[index - 1] };
+            &self.tokens[start as usize..end as usize]
+        })
+    }
+}
+
+/// The tokens of some text, gathered in a single pass. The tokens
+/// that the parser uses are kept apart from the whitespace and
+/// comments, which are only kept when asked for.
+#[derive(Debug)]
+pub struct TokenBuffer {
+    tokens: Vec<Token>,
+    trivia: Trivia,
+    extent: Extent,
+}
+
+impl TokenBuffer {
+    /// Collects the tokens, discarding whitespace and comments.
+    pub fn new(tokens: Tokens) -> Result<Self, ErrorDetail> {
+        TokenBuffer::build(tokens, false)
+    }
+
+    /// Collects the tokens, keeping whitespace and comments.
+    pub fn with_trivia(tokens: Tokens) -> Result<Self, ErrorDetail> {
+        TokenBuffer::build(tokens, true)
+    }
+
+    fn build(tokens: Tokens, keep_trivia: bool) -> Result<Self, ErrorDetail> {
+        let mut buffer = TokenBuffer {
+            tokens: Vec::new(),
+            trivia: Trivia::default(),
+            extent: (tokens.pt.offset, tokens.pt.offset),
+        };
+
+        for token in tokens {
+            let token = token?;
+            buffer.extent.1 = token.extent().1;
+
+            if token.is_trivia() {
+                if keep_trivia {
+                    buffer.trivia.tokens.push(token);
+                }
+            } else {
+                buffer.trivia.ends.push(buffer.trivia.tokens.len() as u32);
+                buffer.tokens.push(token);
+            }
+        }
+
+        Ok(buffer)
+    }
+
+    /// The tokens other than whitespace and comments, ending with
+    /// `Token::EndOfFile`.
+    pub fn tokens(&self) -> &[Token] { &self.tokens }
+
+    /// The whitespace and comments. Empty unless created by
+    /// `with_trivia`.
+    pub fn trivia(&self) -> &[Token] { self.trivia.tokens() }
+
+    /// The whitespace and comments between the token at `index` and
+    /// the one before it, or `None` if there is no token at
+    /// `index`. Always empty unless created by `with_trivia`.
+    pub fn trivia_before(&self, index: usize) -> Option<&[Token]> {
+        self.trivia.before(index)
+    }
+
+    /// Spans all of the text, including leading and trailing
+    /// whitespace and comments.
+    pub fn extent(&self) -> Extent { self.extent }
+
+    /// Discards the tokens, keeping only the whitespace and comments.
+    pub fn into_trivia(self) -> Trivia { self.trivia }
+}
+
 fn single_token<'s>(pm: &mut Master<'s>, pt: Point<'s>) -> Progress<'s, Token> {
     pm.alternate(pt)
         .one(comment_or_doc_comment)
@@ -491,12 +653,15 @@ fn keyword_or_ident<'s>(pm: &mut Master<'s>, pt: Point<'s>) -> Progress<'s, Toke
     ident_raw(pm, pt).map(|(s, extent)| {
         match s {
             "as" => Token::As(extent),
+            "async" => Token::Async(extent),
+            "await" => Token::Await(extent),
             "box" => Token::Box(extent),
             "break" => Token::Break(extent),
             "const" => Token::Const(extent),
             "continue" => Token::Continue(extent),
             "crate" => Token::Crate(extent),
             "default" => Token::Default(extent),
+            "dyn" => Token::Dyn(extent),
             "else" => Token::Else(extent),
             "enum" => Token::Enum(extent),
             "extern" => Token::Extern(extent),
@@ -518,6 +683,7 @@ fn keyword_or_ident<'s>(pm: &mut Master<'s>, pt: Point<'s>) -> Progress<'s, Toke
             "static" => Token::Static(extent),
             "struct" => Token::Struct(extent),
             "trait" => Token::Trait(extent),
+            "try" => Token::Try(extent),
             "type" => Token::Type(extent),
             "use" => Token::Use(extent),
             "union" => Token::Union(extent),
@@ -534,17 +700,25 @@ fn ident<'s>(pm: &mut Master<'s>, pt: Point<'s>) -> Progress<'s, Extent> {
 }
 
 fn ident_raw<'s>(_pm: &mut Master<'s>, pt: Point<'s>) -> Progress<'s, (&'s str, Extent)> {
-    let mut ci = pt.s.chars();
-    let mut idx = 0;
-
-    if let Some(c) = ci.next() {
-        if UnicodeXID::is_xid_start(c) || c == '_' {
-            idx += c.len_utf8();
-
-            idx += ci.take_while(|&c| UnicodeXID::is_xid_continue(c)).map(|c| c.len_utf8()).sum::<usize>();
+    fn ident_len(s: &str) -> usize {
+        let mut ci = s.chars();
+        match ci.next() {
+            Some(c) if UnicodeXID::is_xid_start(c) || c == '_' => {
+                c.len_utf8() + ci.take_while(|&c| UnicodeXID::is_xid_continue(c)).map(|c| c.len_utf8()).sum::<usize>()
+            }
+            _ => 0,
         }
     }
 
+    // Raw identifiers keep their prefix, so they are never keywords
+    let idx = match ident_len(pt.s) {
+        1 if pt.s.starts_with("r#") => match ident_len(&pt.s[2..]) {
+            0 => 1,
+            len => 2 + len,
+        },
+        len => len,
+    };
+
     split_point_at_non_zero_offset(pt, idx, Error::ExpectedIdent)
 }
 
@@ -1101,6 +1275,31 @@ mod test {
         assert_eq!(s, (0, 3));
     }
 
+    #[test]
+    fn keywords_reserved_by_2018_are_tokens() {
+        tokenize_as!("async", Token::Async);
+        tokenize_as!("await", Token::Await);
+        tokenize_as!("dyn", Token::Dyn);
+        tokenize_as!("try", Token::Try);
+    }
+
+    #[test]
+    fn raw_ident() {
+        let s = tokenize_as!("r#match", Token::Ident);
+        assert_eq!(s, (0, 7));
+    }
+
+    #[test]
+    fn raw_ident_requires_an_ident() {
+        let toks = tok("r#1");
+
+        let s = unwrap_as!(toks[0], Token::Ident);
+        assert_eq!(s, (0, 1));
+
+        let s = unwrap_as!(toks[1], Token::Hash);
+        assert_eq!(s, (1, 2));
+    }
+
     #[test]
     fn number_followed_by_range_is_not_fractional() {
         let toks = tok("1..2");
@@ -1164,4 +1363,33 @@ mod test {
         let s = tokenize_as!("", Token::EndOfFile);
         assert_eq!(s, (0, 0))
     }
+
+    #[test]
+    fn buffer_discards_trivia() {
+        let b = TokenBuffer::new(Tokens::new(" a /* b */ c ")).expect("Tokenization failed");
+        assert_eq!(b.tokens().len(), 3);
+        assert!(b.tokens()[2].is_end_of_file());
+        assert!(b.trivia().is_empty());
+        assert_eq!(b.trivia_before(1), Some(&[][..]));
+        assert_eq!(b.trivia_before(3), None);
+        assert_eq!(b.extent(), (0, 13));
+    }
+
+    #[test]
+    fn buffer_keeps_trivia_between_tokens() {
+        let b = TokenBuffer::with_trivia(Tokens::new(" a /* b */ c ")).expect("Tokenization failed");
+        assert_eq!(b.tokens().len(), 3);
+        assert_eq!(b.trivia().len(), 5);
+        assert_eq!(b.trivia_before(0).map(|t| t.len()), Some(1));
+        assert_eq!(b.trivia_before(1).map(|t| t.len()), Some(3));
+        assert!(b.trivia_before(1).unwrap()[1].is_comment_block());
+        assert_eq!(b.trivia_before(2).map(|t| t.len()), Some(1));
+        assert_eq!(b.trivia_before(3), None);
+    }
+
+    #[test]
+    fn buffer_extent_is_relative_to_the_document() {
+        let b = TokenBuffer::new(Tokens::with_offset("a", 10)).expect("Tokenization failed");
+        assert_eq!(b.extent(), (10, 11));
+    }
 }